@@ -9,10 +9,29 @@ use clap::{Args, Parser};
 #[derive(Debug, Parser)]
 #[command(author, version, about, arg_required_else_help(true))]
 pub struct Cli {
-    /// name of the two timers report files to compare - old report first, new report second
+    /// name of the two timers report sets to compare - old reports first, new reports second; replicates of a set are comma-separated
     #[arg(short = 'T', long = "timers-comparison", num_args(2))]
     pub comparison: Option<Vec<String>>,
 
+    /// name of the two tallies report sets used to compare the figure of merit - same format as the timers comparison
+    #[arg(
+        short = 'F',
+        long = "fom-comparison",
+        num_args(2),
+        requires("comparison")
+    )]
+    pub fom_comparison: Option<Vec<String>>,
+
+    /// significance level of the comparison tests, strictly between 0 and 1
+    #[arg(
+        short = 'a',
+        long = "alpha",
+        num_args(1),
+        default_value("0.05"),
+        value_parser(parse_alpha)
+    )]
+    pub alpha: f64,
+
     /// name of the tallies file to analyze
     #[arg(short = 'E', long = "event-correlation", num_args(1))]
     pub correlation: Option<String>,
@@ -38,6 +57,10 @@ pub struct Cli {
     #[command(flatten)]
     pub scaling_params: ScalingParams,
 
+    /// path of a dataset produced by fastiron-sweep
+    #[arg(short = 'D', long = "sweep-dataset", num_args(1))]
+    pub sweep_dataset: Option<String>,
+
     /// if present, plot the results of all computed metrics
     #[arg(short = 'p', long = "plot", num_args(0))]
     pub plot: bool,
@@ -59,6 +82,16 @@ pub struct ScalingParams {
     pub t_factor: Option<usize>,
 }
 
+/// Parses a significance level, which must lie strictly between 0 and 1.
+pub fn parse_alpha(alpha: &str) -> Result<f64, String> {
+    match alpha.parse::<f64>() {
+        Ok(value) if (value > 0.0) & (value < 1.0) => Ok(value),
+        _ => Err(format!(
+            "invalid significance level `{alpha}`, expected a value in (0, 1)"
+        )),
+    }
+}
+
 //=============
 // Unit tests
 //=============
@@ -82,6 +115,50 @@ mod tests {
         assert_eq!(cli.scaling_params.t_factor.unwrap(), 2);
     }
 
+    #[test]
+    fn replicate_comparison() {
+        use super::*;
+        let cmd_line = "./fastiron-stats -T old1.csv,old2.csv new1.csv,new2.csv -F oldt1.csv,oldt2.csv newt1.csv,newt2.csv -a 0.01";
+        let cli = Cli::parse_from(cmd_line.split(' '));
+        assert_eq!(
+            cli.comparison.unwrap(),
+            vec![
+                "old1.csv,old2.csv".to_string(),
+                "new1.csv,new2.csv".to_string()
+            ]
+        );
+        assert_eq!(cli.fom_comparison.unwrap().len(), 2);
+        assert_eq!(cli.alpha, 0.01);
+
+        // the significance level must lie in (0, 1)
+        for alpha in ["0", "1", "2.5", "-0.1", "NaN", "five"] {
+            let cmd_line = format!("./fastiron-stats -T old.csv new.csv --alpha={alpha}");
+            let cli = Cli::try_parse_from(cmd_line.split(' '));
+            assert_eq!(
+                cli.unwrap_err().kind(),
+                clap::error::ErrorKind::ValueValidation
+            );
+        }
+
+        // the figure of merit is only compared along with the timers
+        let cmd_line = "./fastiron-stats -F old.csv new.csv";
+        let cli = Cli::try_parse_from(cmd_line.split(' '));
+        assert_eq!(
+            cli.unwrap_err().kind(),
+            clap::error::ErrorKind::MissingRequiredArgument
+        );
+    }
+
+    #[test]
+    fn sweep_dataset() {
+        use super::*;
+        let cmd_line = "./fastiron-stats -D sweep/sweep.csv -p";
+        let cli = Cli::parse_from(cmd_line.split(' '));
+        assert_eq!(cli.sweep_dataset.unwrap(), "sweep/sweep.csv");
+        assert!(cli.strong_scaling_root.is_none());
+        assert!(cli.plot);
+    }
+
     #[test]
     fn missing_scaling_param() {
         use super::*;
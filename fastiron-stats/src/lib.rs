@@ -16,7 +16,11 @@
 //!
 //! Options:
 //!   -T, --timers-comparison <COMPARISON> <COMPARISON>
-//!           name of the two timers report files to compare - old report first, new report second
+//!           name of the two timers report sets to compare - old reports first, new reports second; replicates of a set are comma-separated
+//!   -F, --fom-comparison <FOM_COMPARISON> <FOM_COMPARISON>
+//!           name of the two tallies report sets used to compare the figure of merit - same format as the timers comparison
+//!   -a, --alpha <ALPHA>
+//!           significance level of the comparison tests, strictly between 0 and 1 [default: 0.05]
 //!   -E, --event-correlation <CORRELATION>
 //!           name of the tallies file to analyze
 //!   -W, --weak-scaling <WEAK_SCALING_ROOT>
@@ -29,6 +33,8 @@
 //!           starting number of threads for scaling data
 //!   -f, --thread_step_factor <T_FACTOR>
 //!           starting number of threads for scaling data
+//!   -D, --sweep-dataset <SWEEP_DATASET>
+//!           path of a dataset produced by fastiron-sweep
 //!   -p, --plot
 //!           if present, plot the results of all computed metrics
 //!   -h, --help
@@ -40,4 +46,5 @@
 //! [Fastiron]: https://github.com/cea-hpc/fastiron
 
 pub mod command_line;
+pub mod statistics;
 pub mod structures;
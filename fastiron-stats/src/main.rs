@@ -3,8 +3,10 @@ use std::fs::File;
 use fastiron_stats::{
     command_line::Cli,
     structures::{
-        processed::{ComparisonResults, CorrelationResults, ScalingResults, ScalingType},
-        raw::{TalliesReport, TimerReport},
+        processed::{
+            ComparisonResults, CorrelationResults, ScalingResults, ScalingType, SweepResults,
+        },
+        raw::{SweepDataset, TalliesReport, TimerReport},
     },
 };
 
@@ -14,13 +16,22 @@ fn main() {
     // Input handling
     let cli = Cli::parse();
 
+    let mut regression = false;
     if let Some(filenames) = cli.comparison {
         println!("Comparing timers...");
         // Get data, process it, save results
-        let old_timer_report = TimerReport::from(File::open(&filenames[0]).unwrap());
-        let new_timer_report = TimerReport::from(File::open(&filenames[1]).unwrap());
-        let results = ComparisonResults::from((old_timer_report, new_timer_report));
+        let old_timer_reports: Vec<TimerReport> = read_reports(&filenames[0]);
+        let new_timer_reports: Vec<TimerReport> = read_reports(&filenames[1]);
+        let mut results =
+            ComparisonResults::from((&old_timer_reports[..], &new_timer_reports[..], cli.alpha));
+        if let Some(filenames) = cli.fom_comparison {
+            let old_tallies_reports: Vec<TalliesReport> = read_reports(&filenames[0]);
+            let new_tallies_reports: Vec<TalliesReport> = read_reports(&filenames[1]);
+            results.add_figure_of_merit(&old_tallies_reports, &new_tallies_reports);
+        }
+        results.print();
         results.save();
+        regression = results.has_regression();
         println!("Done!");
 
         if cli.plot {
@@ -79,5 +90,41 @@ fn main() {
             println!("Plotted results");
         }
     }
-    println!("Finished! All data is ready for use.")
+    if let Some(dataset) = cli.sweep_dataset {
+        println!("Processing sweep data...");
+        // Get data, process it, save results
+        let dataset = SweepDataset::from(File::open(dataset).unwrap());
+        let results = SweepResults::from(dataset);
+        results.save();
+        println!("Done!");
+
+        if let Some(scaling) = results.scaling() {
+            println!("Processing scaling data of the sweep...");
+            scaling.save_tracking();
+            scaling.save_others();
+            println!("Done!");
+
+            if cli.plot {
+                scaling.plot_tracking();
+                scaling.plot_se();
+                scaling.plot_others();
+                println!("Plotted results");
+            }
+        }
+    }
+    println!("Finished! All data is ready for use.");
+
+    // fail if the comparison found a regression, e.g. for use in CI
+    if regression {
+        println!("Significant regression found");
+        std::process::exit(1);
+    }
+}
+
+/// Reads a comma-separated list of reports.
+fn read_reports<R: From<File>>(filenames: &str) -> Vec<R> {
+    filenames
+        .split(',')
+        .map(|filename| R::from(File::open(filename).unwrap()))
+        .collect()
 }
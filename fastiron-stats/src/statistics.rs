@@ -0,0 +1,231 @@
+//! Statistical testing code
+//!
+//! This module contains the functions used to compare two sets of replicate
+//! measurements: estimation of the mean & its confidence interval, and
+//! Welch's t-test, which does not assume the variances of the two sets to be
+//! equal.
+//!
+//! The Student's t-distribution is evaluated using the regularized incomplete
+//! beta function, computed from its continued fraction representation.
+
+/// Maximum number of iterations used to evaluate continued fractions &
+/// invert the distribution function.
+const MAX_ITER: usize = 200;
+
+/// Relative precision targeted by iterative evaluations.
+const EPSILON: f64 = 1.0e-12;
+
+/// Structure used to hold the statistics of a set of measurements.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sample {
+    /// Number of measurements.
+    pub n: usize,
+    /// Mean of the measurements.
+    pub mean: f64,
+    /// Unbiased variance of the measurements. Zero if there is a single one.
+    pub variance: f64,
+}
+
+impl Sample {
+    /// Constructor.
+    pub fn new(values: &[f64]) -> Self {
+        let n = values.len();
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let variance = if n > 1 {
+            values.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        Self { n, mean, variance }
+    }
+
+    /// Returns the half-width of the confidence interval of the mean at the
+    /// `1 - alpha` level. The value is NaN if there are less than two
+    /// measurements.
+    pub fn confidence_interval(&self, alpha: f64) -> f64 {
+        if self.n < 2 {
+            return f64::NAN;
+        }
+        let df = (self.n - 1) as f64;
+        student_t_quantile(1.0 - alpha / 2.0, df) * (self.variance / self.n as f64).sqrt()
+    }
+}
+
+/// Performs a two-sided Welch's t-test on the means of two samples & returns
+/// its p-value. Returns `None` if either sample has less than two
+/// measurements.
+pub fn welch_t_test(a: &Sample, b: &Sample) -> Option<f64> {
+    if (a.n < 2) || (b.n < 2) {
+        return None;
+    }
+    let (va, vb) = (a.variance / a.n as f64, b.variance / b.n as f64);
+    if va + vb == 0.0 {
+        // no noise: any difference is significant
+        return Some(if a.mean == b.mean { 1.0 } else { 0.0 });
+    }
+    let t = (a.mean - b.mean) / (va + vb).sqrt();
+    // Welch-Satterthwaite equation
+    let df = (va + vb).powi(2) / (va * va / (a.n - 1) as f64 + vb * vb / (b.n - 1) as f64);
+    Some(2.0 * (1.0 - student_t_cdf(t.abs(), df)))
+}
+
+/// Returns the value of the cumulative distribution function of the
+/// Student's t-distribution with `df` degrees of freedom.
+pub fn student_t_cdf(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// Returns the quantile of order `p` of the Student's t-distribution with
+/// `df` degrees of freedom.
+pub fn student_t_quantile(p: f64, df: f64) -> f64 {
+    if p < 0.5 {
+        return -student_t_quantile(1.0 - p, df);
+    }
+    // bracket the quantile, then bisect
+    let (mut low, mut high) = (0.0, 1.0);
+    while student_t_cdf(high, df) < p {
+        low = high;
+        high *= 2.0;
+    }
+    for _ in 0..MAX_ITER {
+        let mid = 0.5 * (low + high);
+        if student_t_cdf(mid, df) < p {
+            low = mid;
+        } else {
+            high = mid;
+        }
+        if high - low < EPSILON * high {
+            break;
+        }
+    }
+    0.5 * (low + high)
+}
+
+//==================
+// Private functions
+//==================
+
+/// Natural logarithm of the gamma function, using Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.120_865_097_386_617_9e-2,
+        -0.539_523_938_495_3e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFS
+        .iter()
+        .enumerate()
+        .fold(1.000_000_000_190_015, |acc, (idx, coeff)| {
+            acc + coeff / (x + 1.0 + idx as f64)
+        });
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+/// Regularized incomplete beta function `I_x(a, b)`.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // the continued fraction converges quickly for x < (a + 1) / (a + b + 2)
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Continued fraction of the incomplete beta function, evaluated using the
+/// modified Lentz's method.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1.0e-300;
+    let clamp = |v: f64| if v.abs() < TINY { TINY } else { v };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..MAX_ITER {
+        let m = m as f64;
+        // even step
+        let num = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + num * d);
+        c = clamp(1.0 + num / c);
+        h *= d * c;
+        // odd step
+        let num = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + num * d);
+        c = clamp(1.0 + num / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!(
+            (value - expected).abs() < 1.0e-5,
+            "{value} != {expected} (expected)"
+        );
+    }
+
+    #[test]
+    fn distribution() {
+        assert_close(student_t_cdf(0.0, 5.0), 0.5);
+        assert_close(student_t_cdf(2.0, 10.0), 0.963306);
+        assert_close(student_t_cdf(-2.0, 10.0), 0.036694);
+        // Cauchy distribution
+        assert_close(student_t_cdf(1.0, 1.0), 0.75);
+        assert_close(student_t_quantile(0.975, 4.0), 2.776445);
+        assert_close(student_t_quantile(0.975, 1.0e6), 1.959966);
+        assert_close(student_t_quantile(0.025, 4.0), -2.776445);
+    }
+
+    #[test]
+    fn welch() {
+        let a = Sample::new(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        let b = Sample::new(&[3.0, 4.0, 5.0, 6.0, 7.0]);
+        assert_close(a.mean, 3.0);
+        assert_close(a.variance, 2.5);
+        // t = -2, df = 8
+        assert_close(welch_t_test(&a, &b).unwrap(), 0.080516);
+        assert_close(a.confidence_interval(0.05), 1.963243);
+
+        // unequal variances
+        let c = Sample::new(&[10.0, 12.0, 11.0, 30.0]);
+        assert_close(welch_t_test(&a, &c).unwrap(), 0.073869);
+
+        // not enough measurements
+        let d = Sample::new(&[1.0]);
+        assert!(welch_t_test(&a, &d).is_none());
+        assert!(d.confidence_interval(0.05).is_nan());
+        // no noise
+        let e = Sample::new(&[1.0, 1.0]);
+        let f = Sample::new(&[2.0, 2.0]);
+        assert_eq!(welch_t_test(&e, &f), Some(0.0));
+        assert_eq!(welch_t_test(&e, &e), Some(1.0));
+    }
+}
@@ -4,6 +4,8 @@
 //! by the user.
 
 use std::{
+    collections::BTreeMap,
+    fmt::Display,
     fs::{File, OpenOptions},
     io::Write,
 };
@@ -20,27 +22,158 @@ use gnuplot::{
     TickOption::{Inward, Mirror},
 };
 
-use crate::command_line::ScalingParams;
+use crate::{
+    command_line::ScalingParams,
+    statistics::{welch_t_test, Sample},
+};
 
 use super::raw::{
-    correlation, TalliedData, TalliesReport, TimerReport, TimerSV, N_TIMERS, TIMERS_ARR,
+    correlation, SweepConfig, SweepDataset, TalliedData, TalliedVariable, TalliesReport,
+    TimerReport, TimerSV, N_TIMERS, TIMERS_ARR,
 };
 
 //~~~~~~~~~~~~~~~~~
 // Comparison data
 //~~~~~~~~~~~~~~~~~
 
+/// Enum used to represent the outcome of the comparison of a metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The new value is significantly better than the old one.
+    Improvement,
+    /// The new value is significantly worse than the old one.
+    Regression,
+    /// The difference between the two values is not significant.
+    NotSignificant,
+    /// There are not enough replicates to test the difference.
+    Undetermined,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Verdict::Improvement => "improvement",
+                Verdict::Regression => "regression",
+                Verdict::NotSignificant => "not significant",
+                Verdict::Undetermined => "undetermined",
+            }
+        )
+    }
+}
+
+/// Structure used to hold the comparison of a single metric.
+#[derive(Debug, Clone, Copy)]
+pub struct Comparison {
+    /// Statistics of the old measurements.
+    pub old: Sample,
+    /// Statistics of the new measurements.
+    pub new: Sample,
+    /// Half-width of the confidence interval of the old mean.
+    pub old_ci: f64,
+    /// Half-width of the confidence interval of the new mean.
+    pub new_ci: f64,
+    /// Relative change of the mean in percents.
+    pub change: f64,
+    /// p-value of Welch's t-test, if there are enough replicates.
+    pub p_value: Option<f64>,
+    /// Outcome of the comparison.
+    pub verdict: Verdict,
+}
+
+impl Comparison {
+    /// Constructor. The difference is significant if the p-value of the test
+    /// is lower than `alpha`; `higher_is_better` sets the direction of an
+    /// improvement.
+    pub fn new(old: &[f64], new: &[f64], alpha: f64, higher_is_better: bool) -> Self {
+        let (old, new) = (Sample::new(old), Sample::new(new));
+        let p_value = welch_t_test(&old, &new);
+        let verdict = match p_value {
+            None => Verdict::Undetermined,
+            Some(p) if p >= alpha => Verdict::NotSignificant,
+            Some(_) => {
+                if (new.mean > old.mean) == higher_is_better {
+                    Verdict::Improvement
+                } else {
+                    Verdict::Regression
+                }
+            }
+        };
+        Self {
+            old,
+            new,
+            old_ci: old.confidence_interval(alpha),
+            new_ci: new.confidence_interval(alpha),
+            change: (new.mean - old.mean) / old.mean * 100.0,
+            p_value,
+            verdict,
+        }
+    }
+}
+
 /// Structure used to hold comparison study results.
 pub struct ComparisonResults {
-    /// Old timer data.
-    pub old: TimerReport,
-    /// New timer data.
-    pub new: TimerReport,
-    /// Relative change in percents.
-    pub percents: [f64; N_TIMERS],
+    /// Significance level of the tests.
+    pub alpha: f64,
+    /// Comparison of the total time spent in each section.
+    pub sections: [Comparison; N_TIMERS],
+    /// Comparison of the figure of merit, if tallies reports were provided.
+    pub figure_of_merit: Option<Comparison>,
 }
 
 impl ComparisonResults {
+    /// Compares the figure of merit of two sets of tallies reports.
+    pub fn add_figure_of_merit(&mut self, old: &[TalliesReport], new: &[TalliesReport]) {
+        let fom = |reports: &[TalliesReport]| -> Vec<f64> {
+            reports
+                .iter()
+                .map(|report| report.figure_of_merit())
+                .collect()
+        };
+        self.figure_of_merit = Some(Comparison::new(&fom(old), &fom(new), self.alpha, true));
+    }
+
+    /// Returns true if a significant regression was found.
+    pub fn has_regression(&self) -> bool {
+        self.comparisons()
+            .iter()
+            .any(|(_, comparison)| comparison.verdict == Verdict::Regression)
+    }
+
+    /// Prints the results. The output looks like the following:
+    ///
+    /// ```shell
+    /// metric                        |  old mean ± CI (95%)  |  new mean ± CI (95%)  | change (%) | p-value | verdict
+    /// Section::Main                 |   5.6257e5 ± 3.9339e4 |   5.1022e5 ± 1.1830e4 |      -9.31 |  0.0210 | improvement
+    /// Section::PopulationControl    |   3.0900e3 ± 4.9681e2 |   3.1215e3 ± 5.9020e2 |       1.02 |  0.8302 | not significant
+    /// ```
+    pub fn print(&self) {
+        let level = format!("CI ({}%)", (1.0 - self.alpha) * 100.0);
+        println!(
+            "{:<29} | {:^21} | {:^21} | change (%) | p-value | verdict",
+            "metric",
+            format!("old mean ± {level}"),
+            format!("new mean ± {level}"),
+        );
+        self.comparisons().iter().for_each(|(name, comparison)| {
+            println!(
+                "{:<29} | {:>10.4e} ± {:<8.4e} | {:>10.4e} ± {:<8.4e} | {:>10.2} | {:>7} | {}",
+                name,
+                comparison.old.mean,
+                comparison.old_ci,
+                comparison.new.mean,
+                comparison.new_ci,
+                comparison.change,
+                comparison
+                    .p_value
+                    .map_or("-".to_string(), |p| format!("{p:.4}")),
+                comparison.verdict,
+            )
+        });
+    }
+
     /// Serializing function.
     pub fn save(&self) {
         let mut file = OpenOptions::new()
@@ -49,15 +182,28 @@ impl ComparisonResults {
             .truncate(true)
             .open("comparison.csv")
             .unwrap();
-        writeln!(file, "section,old,new,change").unwrap();
-        TIMERS_ARR.iter().for_each(|section| {
+        writeln!(
+            file,
+            "metric,n_old,old,old_ci,n_new,new,new_ci,change,p_value,verdict"
+        )
+        .unwrap();
+        self.comparisons().iter().for_each(|(name, comparison)| {
             writeln!(
                 file,
-                "{},{},{},{}",
-                *section,
-                self.old[*section].total,
-                self.new[*section].total,
-                self.percents[*section as usize]
+                "{},{},{},{},{},{},{},{},{},{}",
+                name,
+                comparison.old.n,
+                comparison.old.mean,
+                comparison.old_ci,
+                comparison.new.n,
+                comparison.new.mean,
+                comparison.new_ci,
+                comparison.change,
+                comparison
+                    .p_value
+                    .map(|p| p.to_string())
+                    .unwrap_or_default(),
+                comparison.verdict,
             )
             .unwrap();
         });
@@ -86,13 +232,25 @@ impl ComparisonResults {
         });
         let width = 0.25;
 
-        let old_y: [f64; N_TIMERS] = TIMERS_ARR.map(|t| self.old[t].total / 1.0e6);
-        let new_y: [f64; N_TIMERS] = TIMERS_ARR.map(|t| self.new[t].total / 1.0e6);
-        let new_color = if self.percents[0] > 0.0 {
-            Color(ColorType::RGBString("#FF0000")) // total exec time increase => red
-        } else {
-            Color(ColorType::RGBString("#00BB00")) // total exec time decrease => green
+        // confidence intervals are not defined without replicates
+        let ci = |half_width: f64| {
+            if half_width.is_nan() {
+                0.0
+            } else {
+                half_width / 1.0e6
+            }
+        };
+        let old_y: [f64; N_TIMERS] = self.sections.map(|c| c.old.mean / 1.0e6);
+        let new_y: [f64; N_TIMERS] = self.sections.map(|c| c.new.mean / 1.0e6);
+        let old_ci: [f64; N_TIMERS] = self.sections.map(|c| ci(c.old_ci));
+        let new_ci: [f64; N_TIMERS] = self.sections.map(|c| ci(c.new_ci));
+        let new_color = match self.sections[TimerSV::Main as usize].verdict {
+            Verdict::Regression => Color(ColorType::RGBString("#FF0000")), // total exec time increase => red
+            Verdict::Improvement => Color(ColorType::RGBString("#00BB00")), // total exec time decrease => green
+            _ => Color(ColorType::RGBString("#777777")), // no significant change => grey
         };
+        let old_x = x_coords.map(|x| x as f64 - width / 2.0);
+        let new_x = x_coords.map(|x| x as f64 + width / 2.0);
 
         // plot data
         fg.axes2d()
@@ -103,38 +261,47 @@ impl ComparisonResults {
             .set_y_label("Total Time Spent in Section (s)", &[])
             .set_y_log(Some(10.0))
             .boxes(
-                x_coords.iter().map(|x| *x as f64 - width / 2.0),
+                old_x,
                 old_y,
                 &[Caption("Old times"), Color(ColorType::RGBString("#000077"))],
             )
-            .boxes(
-                x_coords.iter().map(|x| *x as f64 + width / 2.0),
-                new_y,
-                &[Caption("New times"), new_color],
-            )
+            .boxes(new_x, new_y, &[Caption("New times"), new_color])
+            .y_error_bars(old_x, old_y, old_ci, &[Color(ColorType::Black)])
+            .y_error_bars(new_x, new_y, new_ci, &[Color(ColorType::Black)])
             .set_box_width(width, false);
 
         fg.show().unwrap();
     }
+
+    /// Returns the comparisons of all metrics, along with their name.
+    fn comparisons(&self) -> Vec<(String, Comparison)> {
+        let mut comparisons: Vec<(String, Comparison)> = TIMERS_ARR
+            .iter()
+            .map(|section| (section.to_string(), self.sections[*section as usize]))
+            .collect();
+        if let Some(fom) = self.figure_of_merit {
+            comparisons.push(("FigureOfMerit".to_string(), fom));
+        }
+        comparisons
+    }
 }
 
 /// Custom [`From`] implementation used to process the raw data at initialization.
-impl From<(TimerReport, TimerReport)> for ComparisonResults {
-    fn from((old, new): (TimerReport, TimerReport)) -> Self {
-        let relative_change =
-            |section: TimerSV| (new[section].mean - old[section].mean) / old[section].mean;
-
-        let percents = [
-            TimerSV::Main,
-            TimerSV::PopulationControl,
-            TimerSV::CycleTracking,
-            TimerSV::CycleTrackingProcess,
-            TimerSV::CycleTrackingSort,
-            TimerSV::CycleSync,
-        ]
-        .map(|section| relative_change(section) * 100.0);
-
-        Self { old, new, percents }
+/// Each section is compared using its total time over the replicates of each set.
+impl From<(&[TimerReport], &[TimerReport], f64)> for ComparisonResults {
+    fn from((old, new, alpha): (&[TimerReport], &[TimerReport], f64)) -> Self {
+        let totals = |reports: &[TimerReport], section: TimerSV| -> Vec<f64> {
+            reports.iter().map(|report| report[section].total).collect()
+        };
+        let sections = TIMERS_ARR.map(|section| {
+            Comparison::new(&totals(old, section), &totals(new, section), alpha, false)
+        });
+
+        Self {
+            alpha,
+            sections,
+            figure_of_merit: None,
+        }
     }
 }
 
@@ -340,7 +507,9 @@ impl ScalingResults {
         for idx in 0..n_elem {
             let ideal = match self.scaling_type {
                 ScalingType::Weak => avg_ref,
-                ScalingType::Strong(factor) => avg_ref / (factor.pow(idx as u32) as f64),
+                ScalingType::Strong(_) => {
+                    avg_ref * self.n_threads[0] as f64 / self.n_threads[idx] as f64
+                }
             };
             writeln!(
                 file,
@@ -464,11 +633,10 @@ impl ScalingResults {
         let y_ideal: Vec<f64> = self
             .n_threads
             .iter()
-            .enumerate()
-            .map(|(idx, _)| match self.scaling_type {
+            .map(|n_thread| match self.scaling_type {
                 ScalingType::Weak => self.tracking_avgs[0],
-                ScalingType::Strong(factor) => {
-                    self.tracking_avgs[0] / (factor.pow(idx as u32) as f64)
+                ScalingType::Strong(_) => {
+                    self.tracking_avgs[0] * self.n_threads[0] as f64 / *n_thread as f64
                 }
             })
             .collect();
@@ -618,3 +786,188 @@ impl From<(&str, &ScalingParams, ScalingType)> for ScalingResults {
         }
     }
 }
+
+//~~~~~~~~~~~~
+// Sweep data
+//~~~~~~~~~~~~
+
+/// Structure used to hold sweep study results, i.e. the statistics of each
+/// configuration of the sweep over its replicates.
+pub struct SweepResults {
+    /// Configurations of the sweep, in increasing order.
+    pub configs: Vec<SweepConfig>,
+    /// Number of replicates of each configuration.
+    pub n_replicates: Vec<usize>,
+    /// Average over replicates of the mean value of each timer.
+    pub means: Vec<[f64; N_TIMERS]>,
+    /// Standard deviation over replicates of the mean value of each timer.
+    pub stddevs: Vec<[f64; N_TIMERS]>,
+}
+
+impl SweepResults {
+    /// Serializing function.
+    pub fn save(&self) {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open("sweep_summary.csv")
+            .unwrap();
+        let timer_columns: Vec<String> = TIMERS_ARR
+            .iter()
+            .map(|timer| format!("{0}_mean,{0}_stddev", timer.name()))
+            .collect();
+        writeln!(
+            file,
+            "threads,chunk_size,particles,seed,nx,ny,nz,n_replicates,{}",
+            timer_columns.join(",")
+        )
+        .unwrap();
+        let opt = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_default();
+        for (idx, config) in self.configs.iter().enumerate() {
+            let mesh: [String; 3] = config
+                .mesh
+                .map(|mesh| mesh.map(|n| n.to_string()))
+                .unwrap_or_default();
+            let timers: Vec<String> = (0..N_TIMERS)
+                .map(|timer_idx| {
+                    format!(
+                        "{},{}",
+                        self.means[idx][timer_idx], self.stddevs[idx][timer_idx]
+                    )
+                })
+                .collect();
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{}",
+                opt(config.threads),
+                opt(config.chunk_size),
+                opt(config.particles),
+                opt(config.seed),
+                mesh.join(","),
+                self.n_replicates[idx],
+                timers.join(",")
+            )
+            .unwrap();
+        }
+    }
+
+    /// Returns the scaling results of the sweep. This is only possible if the
+    /// number of threads was swept, with a single configuration per number of
+    /// threads, at least two of them. The scaling is weak if the number of
+    /// particles or the mesh changes with the number of threads, strong
+    /// otherwise.
+    pub fn scaling(&self) -> Option<ScalingResults> {
+        let n_threads: Vec<usize> = self
+            .configs
+            .iter()
+            .map(|config| config.threads.map(|n| n as usize))
+            .collect::<Option<Vec<usize>>>()?;
+        // configurations are sorted by number of threads first
+        if n_threads.len() < 2 || n_threads.windows(2).any(|pair| pair[0] == pair[1]) {
+            return None;
+        }
+        let constant_size = self.configs.iter().all(|config| {
+            (config.particles == self.configs[0].particles) && (config.mesh == self.configs[0].mesh)
+        });
+        let scaling_type = if constant_size {
+            ScalingType::Strong((n_threads[1] / n_threads[0]).max(2))
+        } else {
+            ScalingType::Weak
+        };
+        let timer = |timer: TimerSV| -> Vec<f64> {
+            self.means
+                .iter()
+                .map(|means| means[timer as usize])
+                .collect()
+        };
+
+        Some(ScalingResults {
+            n_threads,
+            total_exec_times: timer(TimerSV::Main),
+            population_control_avgs: timer(TimerSV::PopulationControl),
+            tracking_avgs: timer(TimerSV::CycleTracking),
+            tracking_process_avgs: timer(TimerSV::CycleTrackingProcess),
+            tracking_sort_avgs: timer(TimerSV::CycleTrackingSort),
+            sync_avgs: timer(TimerSV::CycleSync),
+            scaling_type,
+        })
+    }
+}
+
+/// Custom [`From`] implementation used to process the raw data at initialization.
+impl From<SweepDataset> for SweepResults {
+    fn from(dataset: SweepDataset) -> Self {
+        // group replicates by configuration
+        let mut groups: BTreeMap<SweepConfig, Vec<TimerReport>> = BTreeMap::new();
+        dataset
+            .records
+            .into_iter()
+            .for_each(|record| groups.entry(record.config).or_default().push(record.timers));
+
+        let mut configs = Vec::with_capacity(groups.len());
+        let mut n_replicates = Vec::with_capacity(groups.len());
+        let mut means = Vec::with_capacity(groups.len());
+        let mut stddevs = Vec::with_capacity(groups.len());
+        groups.into_iter().for_each(|(config, reports)| {
+            let mut mean = [0.0; N_TIMERS];
+            let mut stddev = [0.0; N_TIMERS];
+            TIMERS_ARR.iter().for_each(|timer| {
+                let values: Vec<f64> = reports.iter().map(|report| report[*timer].mean).collect();
+                let variable = TalliedVariable::new(&values);
+                mean[*timer as usize] = variable.mean;
+                stddev[*timer as usize] = variable.variance.sqrt();
+            });
+            configs.push(config);
+            n_replicates.push(reports.len());
+            means.push(mean);
+            stddevs.push(stddev);
+        });
+
+        Self {
+            configs,
+            n_replicates,
+            means,
+            stddevs,
+        }
+    }
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verdicts() {
+        let fast = [10.0, 11.0, 10.5];
+        let slow = [20.0, 21.0, 20.5];
+        // timers: lower is better
+        assert_eq!(
+            Comparison::new(&fast, &slow, 0.05, false).verdict,
+            Verdict::Regression
+        );
+        assert_eq!(
+            Comparison::new(&slow, &fast, 0.05, false).verdict,
+            Verdict::Improvement
+        );
+        // figure of merit: higher is better
+        assert_eq!(
+            Comparison::new(&fast, &slow, 0.05, true).verdict,
+            Verdict::Improvement
+        );
+        // noisy
+        let noisy = [5.0, 15.0, 11.0];
+        assert_eq!(
+            Comparison::new(&fast, &noisy, 0.05, false).verdict,
+            Verdict::NotSignificant
+        );
+        // single replicate
+        let comparison = Comparison::new(&fast[..1], &slow[..1], 0.05, false);
+        assert_eq!(comparison.verdict, Verdict::Undetermined);
+        assert_eq!(comparison.change, 100.0);
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! This module contains all code used to model data produced by the main executable.
 
-use std::{fmt::Display, fs::File, iter::zip, ops::Index};
+use std::{fmt::Display, fs::File, iter::zip, ops::Index, str::FromStr};
 
 //~~~~~~~~~~~~~~
 // Tallies data
@@ -171,6 +171,16 @@ impl From<File> for TalliesReport {
     }
 }
 
+impl TalliesReport {
+    /// Returns the figure of merit of the run, i.e. the number of segments
+    /// computed per second of tracking.
+    pub fn figure_of_merit(&self) -> f64 {
+        let segments: f64 = self[TalliedData::NumSeg].values.iter().sum();
+        let tracking: f64 = self[TalliedData::CycleTracking].values.iter().sum();
+        segments / tracking
+    }
+}
+
 impl Index<TalliedData> for TalliesReport {
     type Output = TalliedVariable;
 
@@ -207,6 +217,20 @@ pub enum TimerSV {
     CycleSync = 5,
 }
 
+impl TimerSV {
+    /// Returns the name of the section, in snake case.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TimerSV::Main => "main",
+            TimerSV::PopulationControl => "population_control",
+            TimerSV::CycleTracking => "cycle_tracking",
+            TimerSV::CycleTrackingProcess => "cycle_tracking_process",
+            TimerSV::CycleTrackingSort => "cycle_tracking_sort",
+            TimerSV::CycleSync => "cycle_sync",
+        }
+    }
+}
+
 /// Custom [`Display`] implementation for easier tics generation when plotting.
 impl Display for TimerSV {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -272,3 +296,206 @@ impl Index<TimerSV> for TimerReport {
         &self.timers_data[timer as usize]
     }
 }
+
+//~~~~~~~~~~~~
+// Sweep data
+//~~~~~~~~~~~~
+
+/// Number of configuration columns in a sweep dataset, i.e. columns preceding
+/// the timers data.
+pub const N_SWEEP_CONFIG: usize = 10;
+
+/// Structure used to represent the configuration of a sweep run. Parameters
+/// that were not swept are set to `None`, meaning the value of the base input
+/// file (or the default value of `fastiron`) was used.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SweepConfig {
+    /// Number of threads.
+    pub threads: Option<u64>,
+    /// Size of the chunks used to process particles.
+    pub chunk_size: Option<u64>,
+    /// Target number of particles.
+    pub particles: Option<u64>,
+    /// Seed of the simulation.
+    pub seed: Option<u64>,
+    /// Number of mesh elements along each axis.
+    pub mesh: Option<[usize; 3]>,
+}
+
+/// Structure used to represent a single run of a sweep dataset.
+pub struct SweepRecord {
+    /// Name of the run, i.e. of the directory holding its files.
+    pub run: String,
+    /// Configuration of the run.
+    pub config: SweepConfig,
+    /// Index of the replicate of the configuration.
+    pub replicate: usize,
+    /// Wall time of the run, in seconds.
+    pub wall_time: f64,
+    /// Timers report of the run.
+    pub timers: TimerReport,
+}
+
+impl SweepRecord {
+    /// Returns the header line of a sweep dataset. Timer columns are named
+    /// after the section & the statistic, e.g. `cycle_tracking_mean`.
+    pub fn header() -> String {
+        let mut columns: Vec<String> = [
+            "run",
+            "threads",
+            "chunk_size",
+            "particles",
+            "seed",
+            "nx",
+            "ny",
+            "nz",
+            "replicate",
+            "wall_time",
+        ]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+        TIMERS_ARR.iter().for_each(|timer| {
+            ["lowest", "mean", "highest", "total"]
+                .iter()
+                .for_each(|stat| columns.push(format!("{}_{}", timer.name(), stat)))
+        });
+        columns.join(";")
+    }
+
+    /// Returns the line of the record in a sweep dataset.
+    pub fn to_row(&self) -> String {
+        fn opt<U: ToString>(value: Option<U>) -> String {
+            value.map(|v| v.to_string()).unwrap_or_default()
+        }
+        // empty columns if the mesh was not swept
+        let mesh: [String; 3] = self
+            .config
+            .mesh
+            .map(|mesh| mesh.map(|n| n.to_string()))
+            .unwrap_or_default();
+        let mut columns: Vec<String> = vec![
+            self.run.clone(),
+            opt(self.config.threads),
+            opt(self.config.chunk_size),
+            opt(self.config.particles),
+            opt(self.config.seed),
+        ];
+        columns.extend(mesh);
+        columns.push(self.replicate.to_string());
+        columns.push(self.wall_time.to_string());
+        self.timers.timers_data.iter().for_each(|timer| {
+            columns.extend(
+                [timer.lowest, timer.mean, timer.highest, timer.total].map(|v| v.to_string()),
+            )
+        });
+        columns.join(";")
+    }
+}
+
+/// Structure modelling a dataset produced by `fastiron-sweep`.
+pub struct SweepDataset {
+    /// Runs of the sweep.
+    pub records: Vec<SweepRecord>,
+}
+
+/// Custom [`From`] implementation for processing at initialization.
+impl From<File> for SweepDataset {
+    fn from(file: File) -> Self {
+        fn opt<U: FromStr>(value: &str) -> Option<U> {
+            if value.is_empty() {
+                None
+            } else {
+                value.parse().ok()
+            }
+        }
+        let mut reader = csv::ReaderBuilder::new().delimiter(b';').from_reader(file);
+        let records = reader
+            .records()
+            .map(|result| {
+                let mut record = result.unwrap();
+                record.trim();
+                let col = |idx: usize| record.get(idx).unwrap();
+                let mesh = match (opt(col(5)), opt(col(6)), opt(col(7))) {
+                    (Some(nx), Some(ny), Some(nz)) => Some([nx, ny, nz]),
+                    _ => None,
+                };
+                let mut timers_data = [SummarizedVariable::default(); N_TIMERS];
+                timers_data
+                    .iter_mut()
+                    .enumerate()
+                    .for_each(|(timer_idx, timer)| {
+                        let offset = N_SWEEP_CONFIG + 4 * timer_idx;
+                        timer.lowest = col(offset).parse().unwrap();
+                        timer.mean = col(offset + 1).parse().unwrap();
+                        timer.highest = col(offset + 2).parse().unwrap();
+                        timer.total = col(offset + 3).parse().unwrap();
+                    });
+                SweepRecord {
+                    run: col(0).to_string(),
+                    config: SweepConfig {
+                        threads: opt(col(1)),
+                        chunk_size: opt(col(2)),
+                        particles: opt(col(3)),
+                        seed: opt(col(4)),
+                        mesh,
+                    },
+                    replicate: col(8).parse().unwrap(),
+                    wall_time: col(9).parse().unwrap(),
+                    timers: TimerReport { timers_data },
+                }
+            })
+            .collect();
+        Self { records }
+    }
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn sweep_round_trip() {
+        let mut timers_data = [SummarizedVariable::default(); N_TIMERS];
+        timers_data[TimerSV::CycleTracking as usize] = SummarizedVariable {
+            mean: 2.5,
+            lowest: 1.0,
+            highest: 4.0,
+            total: 10.0,
+        };
+        let record = SweepRecord {
+            run: "000_r4_m8x4x2".to_string(),
+            config: SweepConfig {
+                threads: Some(4),
+                mesh: Some([8, 4, 2]),
+                ..Default::default()
+            },
+            replicate: 1,
+            wall_time: 0.5,
+            timers: TimerReport { timers_data },
+        };
+        let header = SweepRecord::header();
+        assert_eq!(header.split(';').count(), N_SWEEP_CONFIG + 4 * N_TIMERS);
+        assert!(header.contains(";cycle_tracking_mean;"));
+
+        let path = std::env::temp_dir().join("fastiron_stats_sweep.csv");
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "{header}\n{}", record.to_row()).unwrap();
+        let dataset = SweepDataset::from(File::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(dataset.records.len(), 1);
+        let read = &dataset.records[0];
+        assert_eq!(read.run, record.run);
+        assert_eq!(read.config, record.config);
+        assert_eq!(read.replicate, 1);
+        assert_eq!(read.wall_time, 0.5);
+        assert_eq!(read.timers[TimerSV::CycleTracking].total, 10.0);
+        assert_eq!(read.timers[TimerSV::Main].mean, 0.0);
+    }
+}
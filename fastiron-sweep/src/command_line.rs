@@ -0,0 +1,124 @@
+//! Module used for I/O handling
+//!
+//! This module holds all the code defining the CLI, i.e. the entire I/O system of
+//! the program.
+
+use clap::Parser;
+
+/// fastiron-sweep, a parameter sweep runner for Fastiron
+#[derive(Debug, Parser)]
+#[command(author, version, about, arg_required_else_help(true))]
+pub struct Cli {
+    /// base input file of the runs
+    #[arg(short = 'i', long = "input-file", num_args(1), required(true))]
+    pub input_file: String,
+
+    /// directory the runs & the dataset are written to
+    #[arg(short = 'o', long = "output-dir", num_args(1), default_value("sweep"))]
+    pub output_dir: String,
+
+    /// comma-separated list of numbers of threads
+    #[arg(short = 'r', long = "threads", value_delimiter(','))]
+    pub threads: Vec<u64>,
+
+    /// comma-separated list of chunk sizes
+    #[arg(short = 'C', long = "chunk-sizes", value_delimiter(','))]
+    pub chunk_sizes: Vec<u64>,
+
+    /// comma-separated list of target numbers of particles
+    #[arg(short = 'n', long = "particles", value_delimiter(','))]
+    pub particles: Vec<u64>,
+
+    /// comma-separated list of seeds
+    #[arg(short = 's', long = "seeds", value_delimiter(','))]
+    pub seeds: Vec<u64>,
+
+    /// comma-separated list of meshes, each given as NXxNYxNZ
+    #[arg(
+        short = 'm',
+        long = "meshes",
+        value_delimiter(','),
+        value_parser(parse_mesh)
+    )]
+    pub meshes: Vec<[usize; 3]>,
+
+    /// size of a mesh element (cm) -- if present, the problem size follows the swept meshes
+    #[arg(long = "cell-size", num_args(1), requires("meshes"))]
+    pub cell_size: Option<f64>,
+
+    /// number of runs of each configuration
+    #[arg(long = "replicates", num_args(1), default_value("1"))]
+    pub replicates: usize,
+
+    /// if present, combine lists element-wise instead of taking their product
+    #[arg(long = "zip", num_args(0))]
+    pub zip: bool,
+
+    /// path to the fastiron executable -- defaults to the one next to this executable, or in the PATH
+    #[arg(long = "fastiron", num_args(1))]
+    pub fastiron: Option<String>,
+
+    /// if present, print the runs of the sweep without executing them
+    #[arg(long = "dry-run", num_args(0))]
+    pub dry_run: bool,
+
+    /// if present, replace the dataset & the run directories of a previous sweep
+    #[arg(long = "overwrite", num_args(0))]
+    pub overwrite: bool,
+
+    /// additional arguments passed to every run
+    #[arg(last(true))]
+    pub extra_args: Vec<String>,
+}
+
+/// Parses a mesh given as `NXxNYxNZ`.
+pub fn parse_mesh(mesh: &str) -> Result<[usize; 3], String> {
+    let dims: Vec<usize> = mesh
+        .split('x')
+        .map(|dim| dim.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid mesh `{mesh}`, expected NXxNYxNZ"))?;
+    dims.try_into()
+        .map_err(|_| format!("invalid mesh `{mesh}`, expected NXxNYxNZ"))
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn verify_cli_parsing() {
+        use super::*;
+        let cmd_line = "./fastiron-sweep -i input.inp -o out -r 1,2,4 -n 1000 -m 16x16x16,32x16x16 --cell-size 1 --replicates 3 --zip -- --bind-threads";
+        let cli = Cli::parse_from(cmd_line.split(' '));
+        assert_eq!(cli.input_file, "input.inp");
+        assert_eq!(cli.output_dir, "out");
+        assert_eq!(cli.threads, vec![1, 2, 4]);
+        assert!(cli.chunk_sizes.is_empty());
+        assert_eq!(cli.particles, vec![1000]);
+        assert!(cli.seeds.is_empty());
+        assert_eq!(cli.meshes, vec![[16, 16, 16], [32, 16, 16]]);
+        assert_eq!(cli.cell_size, Some(1.0));
+        assert_eq!(cli.replicates, 3);
+        assert!(cli.zip);
+        assert!(!cli.dry_run);
+        assert!(!cli.overwrite);
+        assert_eq!(cli.extra_args, vec!["--bind-threads".to_string()]);
+    }
+
+    #[test]
+    fn invalid_mesh() {
+        use super::*;
+        assert_eq!(parse_mesh("8x4x2"), Ok([8, 4, 2]));
+        assert!(parse_mesh("8x4").is_err());
+        assert!(parse_mesh("8x4xa").is_err());
+        let cmd_line = "./fastiron-sweep -i input.inp -m 16x16";
+        let cli = Cli::try_parse_from(cmd_line.split(' '));
+        assert_eq!(
+            cli.unwrap_err().kind(),
+            clap::error::ErrorKind::ValueValidation
+        );
+    }
+}
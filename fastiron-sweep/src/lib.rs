@@ -0,0 +1,54 @@
+//! Parameter Sweeps of [Fastiron]
+//!
+//! This tool runs `fastiron` over a set of configurations built from a base
+//! input file, and gathers the timers of all runs in a single dataset that can
+//! be processed by `fastiron-stats`. The program can be run like any other
+//! cargo projects:
+//!
+//! ```shell
+//! cargo run --release --bin=fastiron-sweep
+//! ```
+//!
+//! This will print out the command line help message:
+//!
+//! ```shell
+//! fastiron-sweep, a parameter sweep runner for Fastiron
+//!
+//! Usage: fastiron-sweep [OPTIONS] --input-file <INPUT_FILE> [-- <EXTRA_ARGS>...]
+//!
+//! Arguments:
+//!   [EXTRA_ARGS]...  additional arguments passed to every run
+//!
+//! Options:
+//!   -i, --input-file <INPUT_FILE>    base input file of the runs
+//!   -o, --output-dir <OUTPUT_DIR>    directory the runs & the dataset are written to [default: sweep]
+//!   -r, --threads <THREADS>          comma-separated list of numbers of threads
+//!   -C, --chunk-sizes <CHUNK_SIZES>  comma-separated list of chunk sizes
+//!   -n, --particles <PARTICLES>      comma-separated list of target numbers of particles
+//!   -s, --seeds <SEEDS>              comma-separated list of seeds
+//!   -m, --meshes <MESHES>            comma-separated list of meshes, each given as NXxNYxNZ
+//!       --cell-size <CELL_SIZE>      size of a mesh element (cm) -- if present, the problem size follows the swept meshes
+//!       --replicates <REPLICATES>    number of runs of each configuration [default: 1]
+//!       --zip                        if present, combine lists element-wise instead of taking their product
+//!       --fastiron <FASTIRON>        path to the fastiron executable -- defaults to the one next to this executable, or in the PATH
+//!       --dry-run                    if present, print the runs of the sweep without executing them
+//!       --overwrite                  if present, replace the dataset & the run directories of a previous sweep
+//!   -h, --help                       Print help
+//!   -V, --version                    Print version
+//! ```
+//!
+//! Each run is executed in its own directory, `<OUTPUT_DIR>/runs/<RUN_NAME>`,
+//! which holds its input file, its output and its reports. Records are added
+//! to the `<OUTPUT_DIR>/sweep.csv` dataset as runs complete; failed runs are
+//! reported and left out of the dataset. The sweep does not start if the
+//! dataset or the directory of a run already exists, unless `--overwrite` is
+//! specified.
+//!
+//! [Fastiron]: https://github.com/cea-hpc/fastiron
+
+pub mod command_line;
+pub mod runner;
+pub mod spec;
+
+/// Name of the dataset file, written in the output directory.
+pub const DATASET: &str = "sweep.csv";
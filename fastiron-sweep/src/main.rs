@@ -0,0 +1,73 @@
+use std::io::{BufWriter, Write};
+
+use fastiron_sweep::{command_line::Cli, runner::Runner, spec};
+
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use fastiron_stats::structures::raw::SweepRecord;
+
+fn main() {
+    // Input handling
+    let cli = Cli::parse();
+    let runs = spec::expand(&cli)
+        .unwrap_or_else(|msg| Cli::command().error(ErrorKind::ValueValidation, msg).exit());
+    let runner = Runner::new(&cli)
+        .unwrap_or_else(|e| exit_with_error(&format!("could not read {}: {e}", cli.input_file)));
+
+    if cli.dry_run {
+        runs.iter()
+            .for_each(|run| println!("{}: {:?}", run.name, runner.command(run)));
+        println!("{} runs", runs.len());
+        return;
+    }
+
+    // Outputs of a previous sweep are only replaced if asked to
+    let existing = runner.existing_outputs(&runs);
+    if !existing.is_empty() {
+        existing.iter().for_each(|path| {
+            println!(
+                "[ERROR] {} already exists, use --overwrite to replace it",
+                path.display()
+            )
+        });
+        std::process::exit(1);
+    }
+
+    // Run the sweep, writing records as they come
+    let dataset_path = runner.dataset.display();
+    let mut dataset = BufWriter::new(
+        runner
+            .create_dataset()
+            .unwrap_or_else(|e| exit_with_error(&format!("could not create {dataset_path}: {e}"))),
+    );
+    let write_error = |e| exit_with_error(&format!("could not write to {dataset_path}: {e}"));
+    writeln!(dataset, "{}", SweepRecord::header()).unwrap_or_else(write_error);
+    let mut failed: Vec<&str> = Vec::new();
+    for (idx, run) in runs.iter().enumerate() {
+        println!("[{}/{}] {}", idx + 1, runs.len(), run.name);
+        match runner.run(run) {
+            Ok(record) => {
+                writeln!(dataset, "{}", record.to_row()).unwrap_or_else(write_error);
+                dataset.flush().unwrap_or_else(write_error);
+            }
+            Err(e) => {
+                println!("[ERROR] {}: {e}", run.name);
+                failed.push(&run.name);
+            }
+        }
+    }
+
+    println!(
+        "Finished! {} of {} runs written to {dataset_path}",
+        runs.len() - failed.len(),
+        runs.len(),
+    );
+    if !failed.is_empty() {
+        println!("[ERROR] failed runs: {}", failed.join(", "));
+        std::process::exit(1);
+    }
+}
+
+fn exit_with_error(msg: &str) -> ! {
+    println!("[ERROR] {msg}");
+    std::process::exit(1);
+}
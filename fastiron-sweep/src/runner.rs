@@ -0,0 +1,236 @@
+//! Run execution code
+//!
+//! This module contains the code used to execute the runs of the sweep. Each
+//! run is executed in its own directory, holding its input file, the output
+//! of `fastiron` and the reports it produces.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Error, Result},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Instant,
+};
+
+use fastiron_stats::structures::raw::{SweepRecord, TimerReport};
+
+use crate::{
+    command_line::Cli,
+    spec::{self, Run},
+    DATASET,
+};
+
+/// Name of the input file of a run.
+pub const RUN_INPUT: &str = "input.inp";
+/// Name of the file holding the output of a run.
+pub const RUN_LOG: &str = "fastiron.log";
+/// Name of the timers report produced by `fastiron`.
+pub const TIMERS_REPORT: &str = "timers_report.csv";
+
+/// Structure used to execute the runs of a sweep.
+#[derive(Debug)]
+pub struct Runner {
+    /// Path to the `fastiron` executable.
+    pub fastiron: PathBuf,
+    /// Path to the dataset of the sweep.
+    pub dataset: PathBuf,
+    /// Directory holding the directories of the runs.
+    pub runs_dir: PathBuf,
+    /// Content of the base input file.
+    pub base_input: String,
+    /// Directory of the base input file.
+    pub base_dir: PathBuf,
+    /// Size of a mesh element, if the problem size follows the mesh.
+    pub cell_size: Option<f64>,
+    /// Additional arguments passed to every run.
+    pub extra_args: Vec<String>,
+    /// If `true`, outputs of a previous sweep are replaced.
+    pub overwrite: bool,
+}
+
+impl Runner {
+    /// Constructor. Reads the base input file.
+    pub fn new(cli: &Cli) -> Result<Self> {
+        let base_input = std::fs::read_to_string(&cli.input_file)?;
+        let base_dir = std::fs::canonicalize(&cli.input_file)?
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        Ok(Self {
+            fastiron: cli
+                .fastiron
+                .as_ref()
+                .map_or_else(default_fastiron, PathBuf::from),
+            dataset: Path::new(&cli.output_dir).join(DATASET),
+            runs_dir: Path::new(&cli.output_dir).join("runs"),
+            base_input,
+            base_dir,
+            cell_size: cli.cell_size,
+            extra_args: cli.extra_args.clone(),
+            overwrite: cli.overwrite,
+        })
+    }
+
+    /// Returns the directory of a run.
+    pub fn run_dir(&self, run: &Run) -> PathBuf {
+        self.runs_dir.join(&run.name)
+    }
+
+    /// Returns the dataset & the run directories that would be replaced by
+    /// the sweep. The list is empty if the `overwrite` switch is set.
+    pub fn existing_outputs(&self, runs: &[Run]) -> Vec<PathBuf> {
+        if self.overwrite {
+            return Vec::new();
+        }
+        std::iter::once(self.dataset.clone())
+            .chain(runs.iter().map(|run| self.run_dir(run)))
+            .filter(|path| path.exists())
+            .collect()
+    }
+
+    /// Creates the dataset of the sweep. This fails if the file already
+    /// exists, unless the `overwrite` switch is set.
+    pub fn create_dataset(&self) -> Result<File> {
+        if let Some(parent) = self.dataset.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut options = OpenOptions::new();
+        options.write(true);
+        if self.overwrite {
+            options.create(true).truncate(true);
+        } else {
+            options.create_new(true);
+        }
+        options.open(&self.dataset)
+    }
+
+    /// Returns the command executing a run. Reports are written in the
+    /// directory of the run, without prefix. Existing reports are only
+    /// replaced if the `overwrite` switch is set.
+    pub fn command(&self, run: &Run) -> Command {
+        let run_dir = self.run_dir(run);
+        let mut command = Command::new(&self.fastiron);
+        command
+            .arg("-i")
+            .arg(run_dir.join(RUN_INPUT))
+            .arg("--output-dir")
+            .arg(&run_dir)
+            .args(["--run-name", "", "-c"]);
+        if self.overwrite {
+            command.arg("--overwrite");
+        }
+        if let Some(threads) = run.config.threads {
+            command.arg("-r").arg(threads.to_string());
+        }
+        if let Some(chunk_size) = run.config.chunk_size {
+            command.arg("-C").arg(chunk_size.to_string());
+        }
+        command.args(&self.extra_args);
+        command
+    }
+
+    /// Executes a run & returns its record. Fails if `fastiron` could not be
+    /// executed, if it exited with an error, or if its timers report cannot be
+    /// read.
+    pub fn run(&self, run: &Run) -> Result<SweepRecord> {
+        let run_dir = self.run_dir(run);
+        std::fs::create_dir_all(&run_dir)?;
+        let input = spec::write_input(
+            &self.base_input,
+            &self.base_dir,
+            &run.config,
+            self.cell_size,
+        );
+        std::fs::write(run_dir.join(RUN_INPUT), input)?;
+
+        let log = File::create(run_dir.join(RUN_LOG))?;
+        let start = Instant::now();
+        let status = self
+            .command(run)
+            .stdout(Stdio::from(log.try_clone()?))
+            .stderr(Stdio::from(log))
+            .status()
+            .map_err(|e| {
+                Error::new(
+                    e.kind(),
+                    format!("could not execute {}: {e}", self.fastiron.display()),
+                )
+            })?;
+        let wall_time = start.elapsed().as_secs_f64();
+        if !status.success() {
+            return Err(Error::other(format!(
+                "fastiron exited with {status}, see {}",
+                run_dir.join(RUN_LOG).display()
+            )));
+        }
+
+        let timers = TimerReport::from(File::open(run_dir.join(TIMERS_REPORT))?);
+        Ok(SweepRecord {
+            run: run.name.clone(),
+            config: run.config,
+            replicate: run.replicate,
+            wall_time,
+            timers,
+        })
+    }
+}
+
+//==================
+// Private functions
+//==================
+
+fn default_fastiron() -> PathBuf {
+    // binaries of the workspace are built in the same directory
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("fastiron")))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from("fastiron"))
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn no_clobber() {
+        let dir = std::env::temp_dir().join("fastiron_sweep_no_clobber");
+        let _ = std::fs::remove_dir_all(&dir);
+        let cmd_line = format!(
+            "./fastiron-sweep -i ../input_files/debug/sources.inp -o {} -r 1,2",
+            dir.display()
+        );
+        let cli = Cli::parse_from(cmd_line.split(' '));
+        let runs = spec::expand(&cli).unwrap();
+        let runner = Runner::new(&cli).unwrap();
+        let has_overwrite = |runner: &Runner| {
+            runner
+                .command(&runs[0])
+                .get_args()
+                .any(|arg| arg == "--overwrite")
+        };
+        assert!(!has_overwrite(&runner));
+        assert!(runner.existing_outputs(&runs).is_empty());
+
+        // outputs of a previous sweep are not replaced
+        assert!(runner.create_dataset().is_ok());
+        std::fs::create_dir_all(runner.run_dir(&runs[1])).unwrap();
+        assert_eq!(
+            runner.existing_outputs(&runs),
+            vec![dir.join(DATASET), runner.run_dir(&runs[1])]
+        );
+        assert!(runner.create_dataset().is_err());
+
+        let cli = Cli::parse_from(cmd_line.split(' ').chain(["--overwrite"]));
+        let runner = Runner::new(&cli).unwrap();
+        assert!(has_overwrite(&runner));
+        assert!(runner.existing_outputs(&runs).is_empty());
+        assert!(runner.create_dataset().is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
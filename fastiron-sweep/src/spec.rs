@@ -0,0 +1,288 @@
+//! Sweep specification code
+//!
+//! This module contains the code used to expand the lists of the command line
+//! into the runs of the sweep, and to write the input file of each run.
+//!
+//! Since the values of an input file take precedence over the command line
+//! arguments of `fastiron`, swept simulation parameters are written in a copy
+//! of the base input file rather than passed as arguments. Only the number of
+//! threads & the chunk size, which cannot be set from an input file, are
+//! passed on the command line.
+
+use std::path::Path;
+
+use fastiron_stats::structures::raw::SweepConfig;
+
+use crate::command_line::Cli;
+
+/// Keys of the input file whose value is a path. Relative paths are resolved
+/// from the directory of the input file, so they are made absolute in the
+/// copies.
+const PATH_KEYS: [&str; 3] = ["weightWindows", "file", "nuBarFile"];
+
+/// Indentation of the fields of an input block.
+const INDENT: &str = "   ";
+
+/// Structure used to represent a run of the sweep.
+#[derive(Debug, Clone)]
+pub struct Run {
+    /// Name of the run, used as the name of its directory.
+    pub name: String,
+    /// Configuration of the run.
+    pub config: SweepConfig,
+    /// Index of the replicate of the configuration.
+    pub replicate: usize,
+}
+
+/// Expands the lists of the command line into the runs of the sweep. Lists
+/// are either combined using their cartesian product or, if the `zip` switch
+/// is set, element-wise; in the latter case, a list holding a single value is
+/// used for all runs. Each configuration is run `replicates` times.
+pub fn expand(cli: &Cli) -> Result<Vec<Run>, String> {
+    // an empty list means the parameter is not swept
+    fn dim<U: Copy>(values: &[U]) -> Vec<Option<U>> {
+        if values.is_empty() {
+            vec![None]
+        } else {
+            values.iter().copied().map(Some).collect()
+        }
+    }
+    let threads = dim(&cli.threads);
+    let chunk_sizes = dim(&cli.chunk_sizes);
+    let particles = dim(&cli.particles);
+    let seeds = dim(&cli.seeds);
+    let meshes = dim(&cli.meshes);
+
+    let configs: Vec<SweepConfig> = if cli.zip {
+        let lens = [
+            threads.len(),
+            chunk_sizes.len(),
+            particles.len(),
+            seeds.len(),
+            meshes.len(),
+        ];
+        let n_configs = *lens.iter().max().unwrap();
+        if lens.iter().any(|len| (*len != 1) && (*len != n_configs)) {
+            return Err(format!(
+                "zipped lists must hold either 1 or {n_configs} values"
+            ));
+        }
+        let pick = |len: usize, idx: usize| if len == 1 { 0 } else { idx };
+        (0..n_configs)
+            .map(|idx| SweepConfig {
+                threads: threads[pick(threads.len(), idx)],
+                chunk_size: chunk_sizes[pick(chunk_sizes.len(), idx)],
+                particles: particles[pick(particles.len(), idx)],
+                seed: seeds[pick(seeds.len(), idx)],
+                mesh: meshes[pick(meshes.len(), idx)],
+            })
+            .collect()
+    } else {
+        let mut configs = Vec::new();
+        for &mesh in &meshes {
+            for &particles in &particles {
+                for &seed in &seeds {
+                    for &chunk_size in &chunk_sizes {
+                        for &threads in &threads {
+                            configs.push(SweepConfig {
+                                threads,
+                                chunk_size,
+                                particles,
+                                seed,
+                                mesh,
+                            })
+                        }
+                    }
+                }
+            }
+        }
+        configs
+    };
+
+    let mut runs = Vec::with_capacity(configs.len() * cli.replicates);
+    configs.iter().for_each(|config| {
+        (0..cli.replicates).for_each(|replicate| {
+            let mut name = format!("{:03}", runs.len());
+            if let Some(threads) = config.threads {
+                name.push_str(&format!("_r{threads}"));
+            }
+            if let Some(chunk_size) = config.chunk_size {
+                name.push_str(&format!("_C{chunk_size}"));
+            }
+            if let Some(particles) = config.particles {
+                name.push_str(&format!("_n{particles}"));
+            }
+            if let Some(seed) = config.seed {
+                name.push_str(&format!("_s{seed}"));
+            }
+            if let Some([nx, ny, nz]) = config.mesh {
+                name.push_str(&format!("_m{nx}x{ny}x{nz}"));
+            }
+            if cli.replicates > 1 {
+                name.push_str(&format!("_rep{replicate}"));
+            }
+            runs.push(Run {
+                name,
+                config: *config,
+                replicate,
+            })
+        })
+    });
+    Ok(runs)
+}
+
+/// Returns the content of the input file of a run. Swept simulation
+/// parameters replace the values of the base input's Simulation block, or are
+/// added to it; the block is created if needed. Relative paths are resolved
+/// from `base_dir`, the directory of the base input file. If `cell_size` is
+/// specified, the size of the problem is set according to the mesh.
+pub fn write_input(
+    base: &str,
+    base_dir: &Path,
+    config: &SweepConfig,
+    cell_size: Option<f64>,
+) -> String {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    if let Some(particles) = config.particles {
+        fields.push(("nParticles", particles.to_string()));
+    }
+    if let Some(seed) = config.seed {
+        fields.push(("seed", seed.to_string()));
+    }
+    if let Some([nx, ny, nz]) = config.mesh {
+        fields.push(("nx", nx.to_string()));
+        fields.push(("ny", ny.to_string()));
+        fields.push(("nz", nz.to_string()));
+        if let Some(size) = cell_size {
+            fields.push(("lx", (nx as f64 * size).to_string()));
+            fields.push(("ly", (ny as f64 * size).to_string()));
+            fields.push(("lz", (nz as f64 * size).to_string()));
+        }
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_simulation = false;
+    let mut has_simulation = false;
+    // adds the fields that were not found in the block
+    let flush = |lines: &mut Vec<String>, fields: &mut Vec<(&str, String)>| {
+        fields
+            .drain(..)
+            .for_each(|(key, val)| lines.push(format!("{INDENT}{key}: {val}")));
+    };
+
+    for line in base.lines() {
+        let trimmed = line.trim();
+        let is_header = !trimmed.is_empty() && !line.starts_with(char::is_whitespace);
+        if in_simulation && (trimmed.is_empty() || is_header) {
+            flush(&mut lines, &mut fields);
+            in_simulation = false;
+        }
+        if is_header {
+            in_simulation = trimmed == "Simulation:";
+            has_simulation |= in_simulation;
+            lines.push(line.to_string());
+            continue;
+        }
+
+        match trimmed.split_once(':') {
+            Some((key, val)) if !trimmed.starts_with('#') => {
+                let (key, val) = (key.trim(), val.trim());
+                let swept = if in_simulation {
+                    fields.iter().position(|(k, _)| *k == key)
+                } else {
+                    None
+                };
+                if let Some(pos) = swept {
+                    let (_, new_val) = fields.remove(pos);
+                    lines.push(format!("{INDENT}{key}: {new_val}"));
+                } else if PATH_KEYS.contains(&key) && Path::new(val).is_relative() {
+                    let path = base_dir.join(val);
+                    lines.push(format!("{INDENT}{key}: {}", path.display()));
+                } else {
+                    lines.push(line.to_string());
+                }
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    if in_simulation {
+        flush(&mut lines, &mut fields);
+    }
+
+    let mut input = String::new();
+    if !has_simulation && !fields.is_empty() {
+        input.push_str("Simulation:\n");
+        fields
+            .iter()
+            .for_each(|(key, val)| input.push_str(&format!("{INDENT}{key}: {val}\n")));
+        input.push('\n');
+    }
+    lines.iter().for_each(|line| {
+        input.push_str(line);
+        input.push('\n');
+    });
+    input
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn product() {
+        let cmd_line = "./fastiron-sweep -i input.inp -r 1,2,4 -s 1,2 --replicates 2";
+        let runs = expand(&Cli::parse_from(cmd_line.split(' '))).unwrap();
+        assert_eq!(runs.len(), 12);
+        assert_eq!(runs[0].name, "000_r1_s1_rep0");
+        assert_eq!(runs[1].name, "001_r1_s1_rep1");
+        assert_eq!(runs[11].name, "011_r4_s2_rep1");
+        assert_eq!(runs[11].config.threads, Some(4));
+        assert_eq!(runs[11].config.seed, Some(2));
+        assert_eq!(runs[11].config.particles, None);
+        assert_eq!(runs[11].replicate, 1);
+    }
+
+    #[test]
+    fn zipped() {
+        let cmd_line = "./fastiron-sweep -i input.inp -r 1,2,4 -n 10,20,40 -C 64 --zip";
+        let runs = expand(&Cli::parse_from(cmd_line.split(' '))).unwrap();
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[2].name, "002_r4_C64_n40");
+        assert_eq!(runs[2].config.chunk_size, Some(64));
+
+        let cmd_line = "./fastiron-sweep -i input.inp -r 1,2,4 -n 10,20 --zip";
+        assert!(expand(&Cli::parse_from(cmd_line.split(' '))).is_err());
+    }
+
+    #[test]
+    fn input() {
+        let base = "Simulation:\n   nParticles: 100\n   nx: 4\n   weightWindows: ww.txt\n\nCrossSection:\n   name: flat\n   file: /data/xs.txt\n   nuBarFile: nu.txt\n";
+        let config = SweepConfig {
+            particles: Some(1000),
+            mesh: Some([8, 4, 2]),
+            ..Default::default()
+        };
+        let input = write_input(base, Path::new("/inputs"), &config, Some(2.0));
+        assert_eq!(
+            input,
+            "Simulation:\n   nParticles: 1000\n   nx: 8\n   weightWindows: /inputs/ww.txt\n   ny: 4\n   nz: 2\n   lx: 16\n   ly: 8\n   lz: 4\n\nCrossSection:\n   name: flat\n   file: /data/xs.txt\n   nuBarFile: /inputs/nu.txt\n"
+        );
+
+        // missing simulation block
+        let base = "Geometry:\n   shape: brick\n";
+        let config = SweepConfig {
+            seed: Some(7),
+            ..Default::default()
+        };
+        let input = write_input(base, Path::new("/inputs"), &config, None);
+        assert_eq!(
+            input,
+            "Simulation:\n   seed: 7\n\nGeometry:\n   shape: brick\n"
+        );
+    }
+}
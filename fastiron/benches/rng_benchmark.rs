@@ -1,4 +1,6 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use fastiron::utils::mc_rng_state::rng_sample;
 use rand::prelude::*;
 
@@ -15,7 +17,7 @@ fn custom_sample(times: usize) {
 fn rand_std_sample(times: usize) {
     let mut rng: StdRng = rand::SeedableRng::seed_from_u64(9017476812930);
     (0..times).for_each(|_| {
-        let res: f64 = rng.gen();
+        let res: f64 = rng.random();
         black_box(res);
     });
 }
@@ -23,15 +25,15 @@ fn rand_std_sample(times: usize) {
 fn rand_small_sample(times: usize) {
     let mut rng: SmallRng = rand::SeedableRng::seed_from_u64(9017476812930);
     (0..times).for_each(|_| {
-        let res: f64 = rng.gen();
+        let res: f64 = rng.random();
         black_box(res);
     });
 }
 
 fn rand_thread_sample(times: usize) {
-    let mut rng: ThreadRng = rand::thread_rng();
+    let mut rng: ThreadRng = rand::rng();
     (0..times).for_each(|_| {
-        let res: f64 = rng.gen();
+        let res: f64 = rng.random();
         black_box(res);
     });
 }
@@ -31,6 +31,7 @@ use std::{
 };
 
 use num::{Float, FromPrimitive};
+use serde::Serialize;
 
 //=======================
 // custom traits & types
@@ -79,6 +80,8 @@ pub trait CustomFloat:
     + Debug
     + Display
     + LowerExp
+    // reports
+    + Serialize
     // parallel-safe
     + Send
     + Sync
@@ -100,6 +103,10 @@ pub trait CustomFloat:
         T::from(9.39565e+2).unwrap()
     }
 
+    fn electron_mass_energy<T: CustomFloat>() -> T {
+        T::from(5.10999e-1).unwrap()
+    }
+
     fn pi<T: CustomFloat>() -> T {
         T::from(T::PI).unwrap()
     }
@@ -153,6 +160,7 @@ impl<
             + Debug
             + Display
             + LowerExp
+            + Serialize
             + Send
             + Sync,
     > CustomFloat for T
@@ -174,5 +182,22 @@ pub mod sim {
     /// Number of timers, i.e. numbers of section we keep track of
     pub const N_TIMERS: usize = 6;
     /// Number of particle species
-    pub const N_SPECIES: usize = 1;
+    pub const N_SPECIES: usize = 2;
+    /// Maximum number of particles a particle can be split into when
+    /// exceeding the upper bound of a weight window.
+    pub const MAX_WINDOW_SPLIT: usize = 10;
+    /// Number of emission points sampled to estimate the share of each
+    /// external source emitted in a unit.
+    pub const N_SOURCE_SHARE_SAMPLES: u64 = 10_000;
+}
+
+/// Energy group structures
+///
+/// The constants here hold the inner boundaries (in MeV) of named group structures.
+/// The outer boundaries are the energy bounds of the problem.
+pub mod groups {
+    /// Inner boundary of the CASMO 2-group structure, i.e. the thermal cutoff.
+    pub const CASMO_2: [f64; 1] = [6.25e-7];
+    /// Inner boundaries of the CASMO 4-group structure.
+    pub const CASMO_4: [f64; 3] = [6.25e-7, 5.53e-3, 8.21e-1];
 }
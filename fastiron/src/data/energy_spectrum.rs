@@ -4,19 +4,19 @@
 //! energy spectrum from the problem's population.
 
 use std::fmt::Debug;
-use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Result, Write};
 
 use crate::constants::CustomFloat;
 use crate::montecarlo::MonteCarloData;
+use crate::utils::output;
 
 /// Structure used to represent the energy spectrum
 /// of the problem, i.e. the distribution of particles
 /// among energy levels.
 ///
 /// The spectrum is simply done by sorting particles into energy groups and
-/// counting the number of particles in each group. Note that the entire range of
-/// values is separated in segments using a logarithmic scale.\
+/// counting the number of particles in each group. Note that the groups are the
+/// ones of the problem's group structure, logarithmically spaced by default.\
 /// The spectrum is printed as a MarkDown table if an output name file is specified
 /// at launch.
 #[derive(Debug)]
@@ -38,34 +38,33 @@ impl EnergySpectrum {
 
     /// Print the spectrum. This function does nothing if no output file were
     /// specified at launch.
-    pub fn print_spectrum<T: CustomFloat>(&self, mcdata: &MonteCarloData<T>) {
+    pub fn print_spectrum<T: CustomFloat>(&self, mcdata: &MonteCarloData<T>) -> Result<()> {
         if self.file_name.is_empty() {
-            return;
+            return Ok(());
         }
         let levels = mcdata.nuclear_data.energies.len();
-        let mut path = self.file_name.to_owned();
-        path.push_str(".dat");
-        let mut file = File::create(path).unwrap();
+        let mut file = BufWriter::new(output::create_output(
+            &mcdata.params.simulation_params,
+            &(self.file_name.to_owned() + ".dat"),
+        )?);
 
         writeln!(
             file,
             "energy level index |         energy level |         count"
-        )
-        .unwrap();
+        )?;
 
         writeln!(
             file,
             "-------------------|----------------------|--------------"
-        )
-        .unwrap();
+        )?;
 
-        (0..levels).for_each(|ii| {
+        for ii in 0..levels {
             writeln!(
                 file,
                 "{:>18} | {:>20.15} | {:>13}",
                 ii, mcdata.nuclear_data.energies[ii], self.census_energy_spectrum[ii]
-            )
-            .unwrap();
-        })
+            )?;
+        }
+        file.flush()
     }
 }
@@ -0,0 +1,484 @@
+//! External sources of particles
+//!
+//! This module contains the structures used to model sources that are
+//! independent of the problem's materials: their spatial, angular, energy
+//! and time distributions. See
+//! [`source_now()`][crate::simulation::population_control::source_now] for the
+//! way they are used.
+
+use num::{one, zero, FromPrimitive};
+
+use crate::{
+    constants::CustomFloat,
+    data::{mc_vector::MCVector, nuclear_data::FissionSpectrum},
+    parameters::{
+        AngularDistribution, BoundaryFace, EnergyDistribution, SimulationParameters,
+        SourceParameters, SourceShape, TimeProfile,
+    },
+    particles::mc_particle::Species,
+    utils::mc_rng_state::rng_sample,
+};
+
+/// Enum used to represent the spatial distribution of a source.
+#[derive(Debug, Clone)]
+pub enum SourcePosition<T: CustomFloat> {
+    /// Single emission point.
+    Point(MCVector<T>),
+    /// Rectangular cuboid, clipped to the problem.
+    Box {
+        /// Lower corner of the cuboid.
+        low: MCVector<T>,
+        /// Upper corner of the cuboid.
+        high: MCVector<T>,
+    },
+    /// Sphere. Points outside of the problem are rejected.
+    Sphere {
+        /// Center of the sphere.
+        center: MCVector<T>,
+        /// Radius of the sphere.
+        radius: T,
+    },
+    /// Boundary face of the problem.
+    Surface(BoundaryFace),
+}
+
+/// Enum used to represent the energy distribution of a source.
+#[derive(Debug, Clone)]
+pub enum SourceEnergy<T: CustomFloat> {
+    /// Mono-energetic source.
+    Mono(T),
+    /// Uniform distribution between two bounds.
+    Uniform {
+        /// Lower bound, in MeV.
+        low: T,
+        /// Upper bound, in MeV.
+        high: T,
+    },
+    /// Watt spectrum.
+    Watt(FissionSpectrum<T>),
+    /// Histogram distribution.
+    Histogram {
+        /// Bin boundaries, in MeV.
+        boundaries: Vec<T>,
+        /// Normalized cumulative weights of the bins. The first value is zero
+        /// and the last one is one.
+        cumulative: Vec<T>,
+    },
+}
+
+impl<T: CustomFloat> SourceEnergy<T> {
+    /// Samples an energy (MeV) using the random number seed passed as argument.
+    pub fn sample(&self, seed: &mut u64) -> T {
+        match self {
+            Self::Mono(energy) => *energy,
+            Self::Uniform { low, high } => *low + rng_sample::<T>(seed) * (*high - *low),
+            Self::Watt(spectrum) => spectrum.sample(seed),
+            Self::Histogram {
+                boundaries,
+                cumulative,
+            } => {
+                let rr: T = rng_sample(seed);
+                // first bin whose cumulative weight exceeds the random number
+                let bin = cumulative[1..]
+                    .iter()
+                    .position(|cc| rr < *cc)
+                    .unwrap_or(cumulative.len() - 2);
+                boundaries[bin] + rng_sample::<T>(seed) * (boundaries[bin + 1] - boundaries[bin])
+            }
+        }
+    }
+}
+
+/// Structure used to represent the time dependence of a source.
+#[derive(Debug, Clone)]
+pub struct SourceTime<T: CustomFloat> {
+    /// Shape of the intensity over time.
+    pub profile: TimeProfile,
+    /// Start of the emission, in seconds.
+    pub start: T,
+    /// End of the emission, in seconds.
+    pub end: T,
+    /// Characteristic time of an exponential profile, in seconds.
+    pub decay_time: T,
+}
+
+impl<T: CustomFloat> SourceTime<T> {
+    /// Returns the intersection of the interval `[t0; t1]` with the
+    /// emission window, if it isn't empty.
+    fn overlap(&self, t0: T, t1: T) -> Option<(T, T)> {
+        let low = t0.max(self.start);
+        let high = t1.min(self.end);
+        (high > low).then_some((low, high))
+    }
+
+    /// Relative intensity at time `tt`, the source being active.
+    fn decay(&self, tt: T) -> T {
+        (-(tt - self.start) / self.decay_time).exp()
+    }
+
+    /// Integrates the relative intensity over `[t0; t1]`, in seconds.
+    pub fn integral(&self, t0: T, t1: T) -> T {
+        match self.overlap(t0, t1) {
+            None => zero(),
+            Some((low, high)) => match self.profile {
+                TimeProfile::Constant => high - low,
+                TimeProfile::Exponential => self.decay_time * (self.decay(low) - self.decay(high)),
+            },
+        }
+    }
+
+    /// Samples an emission time in `[t0; t1]` according to the profile. The
+    /// interval is expected to overlap the emission window.
+    pub fn sample(&self, t0: T, t1: T, seed: &mut u64) -> T {
+        let (low, high) = self.overlap(t0, t1).unwrap_or((t0, t1));
+        let rr: T = rng_sample(seed);
+        match self.profile {
+            TimeProfile::Constant => low + rr * (high - low),
+            TimeProfile::Exponential => {
+                let (d_low, d_high) = (self.decay(low), self.decay(high));
+                let tt = self.start - self.decay_time * (d_low - rr * (d_low - d_high)).ln();
+                tt.max(low).min(high)
+            }
+        }
+    }
+}
+
+/// Structure used to represent an external source.
+#[derive(Debug, Clone)]
+pub struct ExternalSource<T: CustomFloat> {
+    /// Species of the emitted particles.
+    pub species: Species,
+    /// Intensity of the source, in particles per second.
+    pub strength: T,
+    /// Spatial distribution.
+    pub position: SourcePosition<T>,
+    /// Angular distribution.
+    pub angular: AngularDistribution,
+    /// Normalized direction of a mono-directional source.
+    pub direction: MCVector<T>,
+    /// Energy distribution.
+    pub energy: SourceEnergy<T>,
+    /// Time dependence.
+    pub time: SourceTime<T>,
+    /// Size of the problem, used to locate boundary faces.
+    pub extent: MCVector<T>,
+}
+
+impl<T: CustomFloat> ExternalSource<T> {
+    /// Constructor. Unspecified bounds of a uniform energy distribution are
+    /// replaced by the energy bounds of the problem.
+    pub fn new(sp: &SourceParameters<T>, simulation_params: &SimulationParameters<T>) -> Self {
+        let extent = MCVector {
+            x: simulation_params.lx,
+            y: simulation_params.ly,
+            z: simulation_params.lz,
+        };
+        let position = match sp.shape {
+            SourceShape::Point => SourcePosition::Point(MCVector {
+                x: sp.x_center,
+                y: sp.y_center,
+                z: sp.z_center,
+            }),
+            SourceShape::Box => SourcePosition::Box {
+                low: MCVector {
+                    x: sp.x_min.max(zero()),
+                    y: sp.y_min.max(zero()),
+                    z: sp.z_min.max(zero()),
+                },
+                high: MCVector {
+                    x: sp.x_max.min(extent.x),
+                    y: sp.y_max.min(extent.y),
+                    z: sp.z_max.min(extent.z),
+                },
+            },
+            SourceShape::Sphere => SourcePosition::Sphere {
+                center: MCVector {
+                    x: sp.x_center,
+                    y: sp.y_center,
+                    z: sp.z_center,
+                },
+                radius: sp.radius,
+            },
+            SourceShape::Surface => SourcePosition::Surface(sp.face),
+        };
+        let direction = if sp.direction.len() == 3 {
+            let mut dir = MCVector {
+                x: sp.direction[0],
+                y: sp.direction[1],
+                z: sp.direction[2],
+            };
+            dir /= dir.length();
+            dir
+        } else {
+            inward_normal(sp.face)
+        };
+        let energy = match sp.energy_distribution {
+            EnergyDistribution::Mono => SourceEnergy::Mono(sp.energy),
+            EnergyDistribution::Uniform => {
+                if sp.e_high == zero() {
+                    SourceEnergy::Uniform {
+                        low: simulation_params.e_min,
+                        high: simulation_params.e_max,
+                    }
+                } else {
+                    SourceEnergy::Uniform {
+                        low: sp.e_low,
+                        high: sp.e_high,
+                    }
+                }
+            }
+            EnergyDistribution::Watt => SourceEnergy::Watt(FissionSpectrum::Watt {
+                a: sp.watt_a,
+                b: sp.watt_b,
+            }),
+            EnergyDistribution::Histogram => {
+                let total: T = sp.histogram_weights.iter().copied().sum();
+                let mut cumulative = vec![zero()];
+                sp.histogram_weights.iter().for_each(|weight| {
+                    let last = *cumulative.last().unwrap();
+                    cumulative.push(last + *weight / total);
+                });
+                SourceEnergy::Histogram {
+                    boundaries: sp.histogram_boundaries.clone(),
+                    cumulative,
+                }
+            }
+        };
+
+        Self {
+            species: sp.species,
+            strength: sp.strength,
+            position,
+            angular: sp.angular_distribution,
+            direction,
+            energy,
+            time: SourceTime {
+                profile: sp.time_profile,
+                start: sp.t_start,
+                end: sp.t_end,
+                decay_time: sp.decay_time,
+            },
+            extent,
+        }
+    }
+
+    /// Returns the total weight emitted by the source during the time step
+    /// `[time; time + dt]`.
+    pub fn weight(&self, time: T, dt: T) -> T {
+        self.strength * self.time.integral(time, time + dt)
+    }
+
+    /// Samples an emission point using the random number seed passed as argument.
+    pub fn sample_position(&self, seed: &mut u64) -> MCVector<T> {
+        let extent = self.extent;
+        match &self.position {
+            SourcePosition::Point(point) => *point,
+            SourcePosition::Box { low, high } => MCVector {
+                x: low.x + rng_sample::<T>(seed) * (high.x - low.x),
+                y: low.y + rng_sample::<T>(seed) * (high.y - low.y),
+                z: low.z + rng_sample::<T>(seed) * (high.z - low.z),
+            },
+            SourcePosition::Sphere { center, radius } => {
+                let two: T = FromPrimitive::from_f64(2.0).unwrap();
+                loop {
+                    let offset = MCVector {
+                        x: rng_sample::<T>(seed) * two - one(),
+                        y: rng_sample::<T>(seed) * two - one(),
+                        z: rng_sample::<T>(seed) * two - one(),
+                    };
+                    let rr = *center + offset * *radius;
+                    let in_problem = (rr.x >= zero())
+                        & (rr.x <= extent.x)
+                        & (rr.y >= zero())
+                        & (rr.y <= extent.y)
+                        & (rr.z >= zero())
+                        & (rr.z <= extent.z);
+                    if (offset.length() <= one()) & in_problem {
+                        return rr;
+                    }
+                }
+            }
+            SourcePosition::Surface(face) => {
+                // slightly inside the problem so that the particle is in a cell
+                let shift = extent * T::small_float();
+                let mut rr = MCVector {
+                    x: rng_sample::<T>(seed) * extent.x,
+                    y: rng_sample::<T>(seed) * extent.y,
+                    z: rng_sample::<T>(seed) * extent.z,
+                };
+                match face {
+                    BoundaryFace::XMin => rr.x = shift.x,
+                    BoundaryFace::XMax => rr.x = extent.x - shift.x,
+                    BoundaryFace::YMin => rr.y = shift.y,
+                    BoundaryFace::YMax => rr.y = extent.y - shift.y,
+                    BoundaryFace::ZMin => rr.z = shift.z,
+                    BoundaryFace::ZMax => rr.z = extent.z - shift.z,
+                }
+                rr
+            }
+        }
+    }
+
+    /// Samples an emission direction using the random number seed passed as
+    /// argument. Surface sources only emit towards the inside of the problem.
+    pub fn sample_direction(&self, seed: &mut u64) -> MCVector<T> {
+        let normal = match self.position {
+            SourcePosition::Surface(face) => Some(inward_normal(face)),
+            _ => None,
+        };
+        match (self.angular, normal) {
+            (AngularDistribution::Mono, _) => self.direction,
+            (AngularDistribution::Isotropic, None) => isotropic_direction(seed),
+            (AngularDistribution::Isotropic, Some(normal)) => {
+                let dir = isotropic_direction(seed);
+                if dir.dot(&normal) < zero() {
+                    dir - normal * (dir.dot(&normal) * FromPrimitive::from_f64(2.0).unwrap())
+                } else {
+                    dir
+                }
+            }
+            (AngularDistribution::Cosine, normal) => {
+                // cosine distribution around the normal: mu = sqrt(r)
+                let normal = normal.unwrap_or(self.direction);
+                let two: T = FromPrimitive::from_f64(2.0).unwrap();
+                let mu: T = rng_sample::<T>(seed).sqrt();
+                let phi: T = two * T::pi() * rng_sample::<T>(seed);
+                let sin_theta = (one::<T>() - mu * mu).sqrt();
+                // tangential vectors of an axis-aligned normal
+                let (t1, t2) = (
+                    MCVector {
+                        x: normal.y.abs() + normal.z.abs(),
+                        y: normal.x.abs(),
+                        z: zero(),
+                    },
+                    MCVector {
+                        x: zero(),
+                        y: normal.z.abs(),
+                        z: normal.x.abs() + normal.y.abs(),
+                    },
+                );
+                normal * mu + t1 * (sin_theta * phi.cos()) + t2 * (sin_theta * phi.sin())
+            }
+        }
+    }
+}
+
+/// Returns the unit normal of a boundary face, pointing towards the inside
+/// of the problem.
+fn inward_normal<T: CustomFloat>(face: BoundaryFace) -> MCVector<T> {
+    let (zero, one): (T, T) = (zero(), one());
+    let (x, y, z) = match face {
+        BoundaryFace::XMin => (one, zero, zero),
+        BoundaryFace::XMax => (-one, zero, zero),
+        BoundaryFace::YMin => (zero, one, zero),
+        BoundaryFace::YMax => (zero, -one, zero),
+        BoundaryFace::ZMin => (zero, zero, one),
+        BoundaryFace::ZMax => (zero, zero, -one),
+    };
+    MCVector { x, y, z }
+}
+
+/// Samples a direction uniformly over the unit sphere.
+fn isotropic_direction<T: CustomFloat>(seed: &mut u64) -> MCVector<T> {
+    let one: T = one();
+    let two: T = FromPrimitive::from_f64(2.0).unwrap();
+    let pi: T = T::pi();
+
+    let zz: T = one - two * rng_sample::<T>(seed);
+    let sine = (one - zz * zz).sqrt();
+    let phi: T = pi * (two * rng_sample::<T>(seed) - one);
+    MCVector {
+        x: sine * phi.cos(),
+        y: sine * phi.sin(),
+        z: zz,
+    }
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_profiles() {
+        let constant: SourceTime<f64> = SourceTime {
+            profile: TimeProfile::Constant,
+            start: 1.0,
+            end: 3.0,
+            decay_time: 0.0,
+        };
+        assert_eq!(constant.integral(0.0, 1.0), 0.0);
+        assert_eq!(constant.integral(0.0, 2.0), 1.0);
+        assert_eq!(constant.integral(2.5, 4.0), 0.5);
+
+        let exponential: SourceTime<f64> = SourceTime {
+            profile: TimeProfile::Exponential,
+            start: 0.0,
+            end: f64::MAX,
+            decay_time: 2.0,
+        };
+        let expected = 2.0 * (1.0 - (-0.5_f64).exp());
+        assert!((exponential.integral(0.0, 1.0) - expected).abs() < 1e-12);
+        // half of the emission occurs before t = tau * ln(2)
+        let mut seed: u64 = 90374384094798327;
+        let n_samples = 100000;
+        let median = 2.0 * 2.0_f64.ln();
+        let n_early = (0..n_samples)
+            .filter(|_| exponential.sample(0.0, 100.0, &mut seed) < median)
+            .count();
+        assert!((n_early as f64 / n_samples as f64 - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn histogram_energy() {
+        let energy: SourceEnergy<f64> = SourceEnergy::Histogram {
+            boundaries: vec![1.0, 2.0, 4.0],
+            cumulative: vec![0.0, 0.25, 1.0],
+        };
+        let mut seed: u64 = 90374384094798327;
+        let n_samples = 100000;
+        let samples: Vec<f64> = (0..n_samples).map(|_| energy.sample(&mut seed)).collect();
+        assert!(samples.iter().all(|ee| (1.0..=4.0).contains(ee)));
+        let n_low = samples.iter().filter(|ee| **ee < 2.0).count();
+        assert!((n_low as f64 / n_samples as f64 - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn surface_directions() {
+        let mut seed: u64 = 90374384094798327;
+        let source: ExternalSource<f64> = ExternalSource {
+            species: Species::Neutron,
+            strength: 1.0,
+            position: SourcePosition::Surface(BoundaryFace::YMax),
+            angular: AngularDistribution::Cosine,
+            direction: inward_normal(BoundaryFace::YMax),
+            energy: SourceEnergy::Mono(1.0),
+            time: SourceTime {
+                profile: TimeProfile::Constant,
+                start: 0.0,
+                end: f64::MAX,
+                decay_time: 0.0,
+            },
+            extent: MCVector {
+                x: 10.0,
+                y: 20.0,
+                z: 30.0,
+            },
+        };
+        let n_samples = 100000;
+        let mut mu_sum = 0.0;
+        (0..n_samples).for_each(|_| {
+            let rr = source.sample_position(&mut seed);
+            assert!((rr.y - 20.0).abs() < 1e-6);
+            let dir = source.sample_direction(&mut seed);
+            assert!((dir.length() - 1.0).abs() < 1e-12);
+            assert!(dir.y <= 0.0);
+            mu_sum -= dir.y;
+        });
+        // mean cosine of a cosine distribution is 2/3
+        assert!((mu_sum / n_samples as f64 - 2.0 / 3.0).abs() < 0.01);
+    }
+}
@@ -3,6 +3,8 @@
 //! This module contains code used to store material-related data. It is mainly used when
 //! initializing the simulation, in order to build the environment of the problem.
 
+use num::zero;
+
 use crate::constants::CustomFloat;
 
 /// Structure used to represent an isotope of a material.
@@ -13,6 +15,9 @@ pub struct Isotope<T: CustomFloat> {
     /// Atomic fraction of the isotope in the material. Not to be confused with
     /// the related quantity _atomic ratio_.
     pub atom_fraction: T,
+    /// Atomic mass of the isotope, in neutron masses. This value is used
+    /// to compute the energy loss of scattered particles.
+    pub mass: T,
 }
 
 /// Structure used to store a material's information.
@@ -20,8 +25,11 @@ pub struct Isotope<T: CustomFloat> {
 pub struct Material<T: CustomFloat> {
     /// Name of the material.
     pub name: String,
-    /// Mass of the material in grams.
+    /// Mass of the material in grams. For materials listing explicit
+    /// isotopes, this is the atom fraction weighted mass of the isotopes.
     pub mass: T,
+    /// Rate of particle sourcing, per unit of volume.
+    pub source_rate: T,
     /// List of the isotopes making up the material.
     pub iso: Vec<Isotope<T>>,
 }
@@ -33,11 +41,27 @@ impl<T: CustomFloat> Material<T> {
     }
 }
 
+/// Share of a [Material] in a mixture.
+#[derive(Debug, Clone, Copy)]
+pub struct MixtureComponent<T: CustomFloat> {
+    /// Index of the material in the database.
+    pub material: usize,
+    /// Fraction of the mixture volume occupied by the material.
+    pub volume_fraction: T,
+    /// Number density of the material averaged over the whole mixture volume,
+    /// i.e. weighted by its volume fraction.
+    pub number_density: T,
+}
+
 /// Top level structure used to store each material's information.
 #[derive(Debug, Default)]
 pub struct MaterialDatabase<T: CustomFloat> {
     /// List of materials.
     pub mat: Vec<Material<T>>,
+    /// Material and number density of each cell of the mesh, indexed by
+    /// global cell index. Only filled when cells are homogenized by volume
+    /// sampling, in which case partially filled cells refer to mixtures.
+    pub cell_materials: Vec<(usize, T)>,
 }
 
 impl<T: CustomFloat> MaterialDatabase<T> {
@@ -52,4 +76,48 @@ impl<T: CustomFloat> MaterialDatabase<T> {
     pub fn find_material(&self, name: &str) -> Option<usize> {
         self.mat.iter().position(|m| m.name == name)
     }
+
+    /// Adds a mixture of the materials passed as argument and returns its
+    /// index along with its number density. The isotopes of each component
+    /// are weighted by its share of the total number density so that no mass
+    /// is lost. The source rate is weighted by the volume fractions.
+    pub fn add_mixture(&mut self, components: &[MixtureComponent<T>]) -> (usize, T) {
+        let number_density: T = components.iter().map(|cpnt| cpnt.number_density).sum();
+        let mut mixture = Material {
+            name: components
+                .iter()
+                .map(|cpnt| self.mat[cpnt.material].name.as_str())
+                .collect::<Vec<_>>()
+                .join("+"),
+            mass: zero(),
+            source_rate: zero(),
+            iso: Vec::new(),
+        };
+        for cpnt in components {
+            let material = &self.mat[cpnt.material];
+            mixture.source_rate += cpnt.volume_fraction * material.source_rate;
+            if cpnt.number_density == zero() {
+                continue;
+            }
+            let weight = cpnt.number_density / number_density;
+            for isotope in &material.iso {
+                // isotopes may be shared by several components
+                match mixture.iso.iter_mut().find(|iso| iso.gid == isotope.gid) {
+                    Some(iso) => iso.atom_fraction += isotope.atom_fraction * weight,
+                    None => mixture.add_isotope(Isotope {
+                        gid: isotope.gid,
+                        atom_fraction: isotope.atom_fraction * weight,
+                        mass: isotope.mass,
+                    }),
+                }
+            }
+        }
+        mixture.mass = mixture
+            .iso
+            .iter()
+            .map(|iso| iso.atom_fraction * iso.mass)
+            .sum();
+        self.add_material(mixture);
+        (self.mat.len() - 1, number_density)
+    }
 }
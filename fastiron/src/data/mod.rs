@@ -4,7 +4,10 @@
 //! beside particles.
 
 pub mod energy_spectrum;
+pub mod external_source;
 pub mod material_database;
 pub mod mc_vector;
 pub mod nuclear_data;
 pub mod tallies;
+pub mod tracks;
+pub mod weight_windows;
@@ -2,10 +2,18 @@
 //!
 //! This module contains code used to store, model and compute nuclear data and quantities.
 
-use num::{zero, FromPrimitive};
+use num::{one, zero, FromPrimitive};
 use rustc_hash::FxHashMap;
 
-use crate::{constants::CustomFloat, parameters::MaterialParameters};
+use crate::{
+    constants::CustomFloat,
+    parameters::{
+        CrossSectionAveraging, CrossSectionParameters, FissionSpectrumType, IsotopeParameters,
+        ScatteringModel,
+    },
+    particles::mc_particle::Species,
+    utils::mc_rng_state::rng_sample,
+};
 
 /// Enum used to represent a reaction type.
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -22,7 +30,7 @@ pub enum ReactionType {
 ///
 /// Private fields represent the coefficients, `aa` corresponding to the leading
 /// coefficient , `ee` to the product of all roots.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Polynomial<T: CustomFloat> {
     /// Leading coefficient of the polynomial function.
     pub aa: T,
@@ -43,11 +51,406 @@ impl<T: CustomFloat> Polynomial<T> {
     }
 }
 
+/// Returns the value in `xx` of the function linearly interpolating the `(xs, ys)`
+/// points. The function is constant outside of the `xs` range.
+fn interpolate<T: CustomFloat>(xs: &[T], ys: &[T], xx: T) -> T {
+    let idx = xs.partition_point(|x| *x <= xx);
+    if idx == 0 {
+        return ys[0];
+    }
+    if idx == xs.len() {
+        return ys[idx - 1];
+    }
+    let (x_low, x_high) = (xs[idx - 1], xs[idx]);
+    let (y_low, y_high) = (ys[idx - 1], ys[idx]);
+    y_low + (y_high - y_low) * (xx - x_low) / (x_high - x_low)
+}
+
+/// Structure used to represent a tabulated cross-section.
+///
+/// Values are linearly interpolated between the points of the energy grid
+/// and held constant outside of it.
+#[derive(Debug)]
+pub struct TabulatedCrossSection<T: CustomFloat> {
+    /// Energy grid of the data, in MeV. Values are strictly increasing.
+    pub energies: Vec<T>,
+    /// Values of the data on the energy grid.
+    pub values: Vec<T>,
+    /// Method used to collapse the data onto energy groups.
+    pub averaging: CrossSectionAveraging,
+}
+
+impl<T: CustomFloat> TabulatedCrossSection<T> {
+    /// Returns the linearly-interpolated value of the data in `energy`.
+    pub fn val(&self, energy: T) -> T {
+        interpolate(&self.energies, &self.values, energy)
+    }
+
+    /// Returns the average value of the data over the `[e_low; e_high]` energy
+    /// range. The integration is exact since the interpolated data is linear
+    /// between the points of the grid and the bounds of the range.
+    pub fn group_average(&self, e_low: T, e_high: T) -> T {
+        let mut nodes: Vec<T> = vec![e_low];
+        nodes.extend(
+            self.energies
+                .iter()
+                .filter(|e| (**e > e_low) & (**e < e_high)),
+        );
+        nodes.push(e_high);
+
+        let mut integral: T = zero();
+        let mut weight: T = zero();
+        nodes.windows(2).for_each(|pair| {
+            let (aa, bb) = (pair[0], pair[1]);
+            let (v_aa, v_bb) = (self.val(aa), self.val(bb));
+            match self.averaging {
+                CrossSectionAveraging::FluxWeighted => {
+                    // v(E) = alpha + beta * E weighted by 1/E
+                    let beta = (v_bb - v_aa) / (bb - aa);
+                    let alpha = v_aa - beta * aa;
+                    let log_ratio = (bb / aa).ln();
+                    integral += alpha * log_ratio + beta * (bb - aa);
+                    weight += log_ratio;
+                }
+                CrossSectionAveraging::Interpolated => {
+                    let two: T = FromPrimitive::from_f64(2.0).unwrap();
+                    integral += (v_aa + v_bb) * (bb - aa) / two;
+                    weight += bb - aa;
+                }
+            }
+        });
+        integral / weight
+    }
+}
+
+/// Enum used to represent the data a cross-section is built from.
+#[derive(Debug)]
+pub enum CrossSectionData<T: CustomFloat> {
+    /// The cross-section is a polynomial function of `log10(energy)`,
+    /// evaluated as `10^(Poly(log10(energy)))`.
+    Polynomial(Polynomial<T>),
+    /// The cross-section is tabulated.
+    Tabulated(TabulatedCrossSection<T>),
+}
+
+impl<T: CustomFloat> CrossSectionData<T> {
+    /// Returns the value of the cross-section over the `[e_low; e_high]` energy
+    /// group. The value is not normalized.
+    pub fn group_value(&self, e_low: T, e_high: T) -> f64 {
+        match self {
+            Self::Polynomial(polynomial) => {
+                let two: T = FromPrimitive::from_f64(2.0).unwrap();
+                let energy: T = (e_low + e_high) / two;
+                // this here gives a value too big for f32
+                10.0_f64.powf(polynomial.val(energy.log10()).to_f64().unwrap())
+            }
+            Self::Tabulated(table) => table.group_average(e_low, e_high).to_f64().unwrap(),
+        }
+    }
+}
+
+impl<T: CustomFloat> From<&CrossSectionParameters<T>> for CrossSectionData<T> {
+    fn from(xs_params: &CrossSectionParameters<T>) -> Self {
+        if xs_params.is_tabulated() {
+            Self::Tabulated(TabulatedCrossSection {
+                energies: xs_params.table_energies.clone(),
+                values: xs_params.table_values.clone(),
+                averaging: xs_params.averaging,
+            })
+        } else {
+            Self::Polynomial(Polynomial {
+                aa: xs_params.aa,
+                bb: xs_params.bb,
+                cc: xs_params.cc,
+                dd: xs_params.dd,
+                ee: xs_params.ee,
+            })
+        }
+    }
+}
+
+/// Enum used to represent the average number of particles produced by a fission.
+#[derive(Debug, Clone)]
+pub enum NuBar<T: CustomFloat> {
+    /// The value does not depend on the incident energy.
+    Constant(T),
+    /// The value is a polynomial function of the incident energy.
+    Polynomial(Polynomial<T>),
+    /// The value is linearly interpolated from tabulated data.
+    Tabulated {
+        /// Energy grid of the data, in MeV.
+        energies: Vec<T>,
+        /// Values of the data on the energy grid.
+        values: Vec<T>,
+    },
+}
+
+impl<T: CustomFloat> NuBar<T> {
+    /// Returns the value of nu bar for a given incident energy.
+    pub fn val(&self, energy: T) -> T {
+        match self {
+            Self::Constant(nu_bar) => *nu_bar,
+            Self::Polynomial(polynomial) => polynomial.val(energy),
+            Self::Tabulated { energies, values } => interpolate(energies, values, energy),
+        }
+    }
+
+    /// Returns an estimate of the maximum value of nu bar over the
+    /// `[energy_low; energy_high]` range.
+    pub fn max_val(&self, energy_low: T, energy_high: T) -> T {
+        match self {
+            Self::Constant(nu_bar) => *nu_bar,
+            Self::Tabulated { values, .. } => values.iter().copied().fold(zero(), T::max),
+            Self::Polynomial(polynomial) => {
+                // the function is sampled since there is no closed form
+                let n_samples: usize = 100;
+                let step = (energy_high - energy_low) / T::from_usize(n_samples).unwrap();
+                (0..=n_samples)
+                    .map(|ii| polynomial.val(energy_low + step * T::from_usize(ii).unwrap()))
+                    .fold(zero(), T::max)
+            }
+        }
+    }
+}
+
+/// Enum used to represent the energy distribution of particles produced by a
+/// fission.
+#[derive(Debug, Clone, Copy)]
+pub enum FissionSpectrum<T: CustomFloat> {
+    /// Legacy distribution of Quicksilver: `20 * ((r + 1) / 2)^2` MeV.
+    Legacy,
+    /// Maxwellian distribution of a given temperature (MeV).
+    Maxwellian {
+        /// Temperature of the distribution, in MeV.
+        temperature: T,
+    },
+    /// Watt distribution of parameters `a` (MeV) and `b` (MeV⁻¹).
+    Watt {
+        /// `a` parameter of the distribution, in MeV.
+        a: T,
+        /// `b` parameter of the distribution, in MeV⁻¹.
+        b: T,
+    },
+}
+
+impl<T: CustomFloat> FissionSpectrum<T> {
+    /// Samples an outgoing energy (MeV) using the random number seed passed
+    /// as argument.
+    pub fn sample(&self, seed: &mut u64) -> T {
+        let one: T = one();
+        let two: T = FromPrimitive::from_f64(2.0).unwrap();
+        match self {
+            Self::Legacy => {
+                let twenty: T = FromPrimitive::from_f64(20.0).unwrap();
+                let rand_f = (rng_sample::<T>(seed) + one) / two;
+                twenty * rand_f * rand_f
+            }
+            Self::Maxwellian { temperature } => sample_maxwellian(*temperature, seed),
+            Self::Watt { a, b } => {
+                // sample a Maxwellian energy and shift it
+                let four: T = FromPrimitive::from_f64(4.0).unwrap();
+                let ww = sample_maxwellian(*a, seed);
+                let rr: T = rng_sample(seed);
+                ww + *a * *a * *b / four + (two * rr - one) * (*a * *a * *b * ww).sqrt()
+            }
+        }
+    }
+}
+
+/// Samples an energy from a Maxwellian distribution of temperature `temperature`.
+/// Random numbers are used as `1 - r` so that logarithms are computed on `]0; 1]`.
+fn sample_maxwellian<T: CustomFloat>(temperature: T, seed: &mut u64) -> T {
+    let one: T = one();
+    let two: T = FromPrimitive::from_f64(2.0).unwrap();
+    let r1: T = rng_sample(seed);
+    let r2: T = rng_sample(seed);
+    let r3: T = rng_sample(seed);
+    let pi: T = T::pi();
+    let cc = (pi * r3 / two).cos();
+    -temperature * ((one - r1).ln() + (one - r2).ln() * cc * cc)
+}
+
+/// Structure used to describe the fission-related properties of a reaction,
+/// i.e. its multiplicity and the energy distribution of produced particles.
+#[derive(Debug, Clone)]
+pub struct FissionData<T: CustomFloat> {
+    /// Average number of particles produced.
+    pub nu_bar: NuBar<T>,
+    /// Energy distribution of produced particles.
+    pub spectrum: FissionSpectrum<T>,
+}
+
+impl<T: CustomFloat> Default for FissionData<T> {
+    fn default() -> Self {
+        Self {
+            nu_bar: NuBar::Constant(zero()),
+            spectrum: FissionSpectrum::Legacy,
+        }
+    }
+}
+
+impl<T: CustomFloat> From<&CrossSectionParameters<T>> for ScatteringLaw<T> {
+    fn from(xs_params: &CrossSectionParameters<T>) -> Self {
+        match xs_params.scattering_model {
+            ScatteringModel::Legacy => Self::Legacy,
+            ScatteringModel::Elastic => Self::Elastic {
+                legendre: xs_params.legendre_coefficients.clone(),
+            },
+        }
+    }
+}
+
+impl<T: CustomFloat> From<&CrossSectionParameters<T>> for FissionData<T> {
+    fn from(xs_params: &CrossSectionParameters<T>) -> Self {
+        let nu_bar = if !xs_params.nu_bar_energies.is_empty() {
+            NuBar::Tabulated {
+                energies: xs_params.nu_bar_energies.clone(),
+                values: xs_params.nu_bar_values.clone(),
+            }
+        } else if !xs_params.nu_bar_polynomial.is_empty() {
+            // coefficients are given by increasing degree
+            let coefficient = |degree: usize| {
+                xs_params
+                    .nu_bar_polynomial
+                    .get(degree)
+                    .copied()
+                    .unwrap_or_default()
+            };
+            NuBar::Polynomial(Polynomial {
+                aa: coefficient(4),
+                bb: coefficient(3),
+                cc: coefficient(2),
+                dd: coefficient(1),
+                ee: coefficient(0),
+            })
+        } else {
+            NuBar::Constant(xs_params.nu_bar)
+        };
+        let spectrum = match xs_params.fission_spectrum {
+            FissionSpectrumType::Legacy => FissionSpectrum::Legacy,
+            FissionSpectrumType::Maxwellian => FissionSpectrum::Maxwellian {
+                temperature: xs_params.maxwell_temperature,
+            },
+            FissionSpectrumType::Watt => FissionSpectrum::Watt {
+                a: xs_params.watt_a,
+                b: xs_params.watt_b,
+            },
+        };
+        Self { nu_bar, spectrum }
+    }
+}
+
+/// Enum used to represent the way scattered particles are sampled.
+#[derive(Debug, Clone, Default)]
+pub enum ScatteringLaw<T: CustomFloat> {
+    /// Legacy model of Quicksilver: the energy is reduced by a random fraction
+    /// of `1/A` and the direction is sampled isotropically in the laboratory frame.
+    #[default]
+    Legacy,
+    /// Elastic scattering on a target at rest, sampled in the centre-of-mass frame.
+    Elastic {
+        /// Legendre moments `a_1, a_2, ...` of the centre-of-mass cosine
+        /// distribution. The scattering is isotropic if the list is empty.
+        legendre: Vec<T>,
+    },
+    /// Compton scattering of a photon on a free electron at rest, sampled from
+    /// the Klein-Nishina distribution.
+    Compton,
+}
+
+impl<T: CustomFloat> ScatteringLaw<T> {
+    /// Samples the outgoing energy and the laboratory-frame scattering cosine
+    /// of a particle of energy `energy` scattered by a target of mass `mass`,
+    /// expressed in neutron masses. The target mass is ignored by the Compton law.
+    pub fn sample(&self, energy: T, mass: T, seed: &mut u64) -> (T, T) {
+        let one: T = one();
+        let two: T = FromPrimitive::from_f64(2.0).unwrap();
+        match self {
+            Self::Legacy => {
+                let energy_out = energy * (one - rng_sample::<T>(seed) * (one / mass));
+                let angle = rng_sample::<T>(seed) * two - one;
+                (energy_out, angle)
+            }
+            Self::Elastic { legendre } => {
+                let mu_cm = sample_legendre(legendre, seed);
+                // kinematics of a two-body collision with a target at rest
+                let mass_term = mass * mass + two * mass * mu_cm + one;
+                let energy_out = energy * mass_term / ((mass + one) * (mass + one));
+                let angle = ((one + mass * mu_cm) / mass_term.sqrt()).max(-one).min(one);
+                (energy_out, angle)
+            }
+            Self::Compton => sample_klein_nishina(energy, seed),
+        }
+    }
+}
+
+/// Samples the outgoing energy and scattering cosine of a Compton-scattered
+/// photon using Kahn's rejection method.
+fn sample_klein_nishina<T: CustomFloat>(energy: T, seed: &mut u64) -> (T, T) {
+    let one: T = one();
+    let two: T = FromPrimitive::from_f64(2.0).unwrap();
+    let four: T = FromPrimitive::from_f64(4.0).unwrap();
+    let nine: T = FromPrimitive::from_f64(9.0).unwrap();
+    let half: T = FromPrimitive::from_f64(0.5).unwrap();
+    // incident energy in electron rest mass units
+    let kk: T = energy / T::electron_mass_energy();
+    loop {
+        let (r1, r2, r3): (T, T, T) = (rng_sample(seed), rng_sample(seed), rng_sample(seed));
+        // eta is the ratio of the incident and outgoing energies
+        let (eta, accept) = if r1 <= (one + two * kk) / (nine + two * kk) {
+            let eta = one + two * kk * r2;
+            (eta, r3 <= four * (one / eta - one / (eta * eta)))
+        } else {
+            let eta = (one + two * kk) / (one + two * kk * r2);
+            let mu = one - (eta - one) / kk;
+            (eta, r3 <= half * (mu * mu + one / eta))
+        };
+        if accept {
+            let mu = (one - (eta - one) / kk).max(-one).min(one);
+            return (energy / eta, mu);
+        }
+    }
+}
+
+/// Samples a cosine from the distribution `f(mu) = 1/2 * sum((2l+1) a_l P_l(mu))`
+/// with `a_0 = 1`, using rejection sampling. The envelope is constant since
+/// Legendre polynomials are bounded by one on `[-1; 1]`.
+fn sample_legendre<T: CustomFloat>(legendre: &[T], seed: &mut u64) -> T {
+    let one: T = one();
+    let two: T = FromPrimitive::from_f64(2.0).unwrap();
+    let weight = |ll: usize| T::from_usize(2 * ll + 1).unwrap();
+    let envelope: T = legendre
+        .iter()
+        .enumerate()
+        .map(|(idx, a_l)| weight(idx + 1) * a_l.abs())
+        .sum::<T>()
+        + one;
+    loop {
+        let mu: T = rng_sample::<T>(seed) * two - one;
+        if legendre.is_empty() {
+            return mu;
+        }
+        // P_l(mu) using Bonnet's recursion
+        let (mut p_prev, mut p_curr) = (one, mu);
+        let mut density = one;
+        for (idx, a_l) in legendre.iter().enumerate() {
+            let ll = idx + 1;
+            density += weight(ll) * *a_l * p_curr;
+            let l_t: T = T::from_usize(ll).unwrap();
+            let p_next = (weight(ll) * mu * p_curr - l_t * p_prev) / (l_t + one);
+            (p_prev, p_curr) = (p_curr, p_next);
+        }
+        if rng_sample::<T>(seed) * envelope <= density {
+            return mu;
+        }
+    }
+}
+
 /// Lowest-level structure to represent a reaction.
 ///
-/// A reaction is characterized by three elements: a probability density
-/// (cross-section), the type of the reaction and a statistical offset
-/// (nu bar).
+/// A reaction is characterized by four elements: a probability density
+/// (cross-section), the type of the reaction, a statistical offset
+/// (nu bar) and the energy distribution of particles it produces.
 #[derive(Debug)]
 pub struct NuclearDataReaction<T: CustomFloat> {
     /// Cross-section of the reaction, i.e. the discretization of its
@@ -60,34 +463,57 @@ pub struct NuclearDataReaction<T: CustomFloat> {
     /// of offspring particles created by a reaction. See the
     /// [`sample_collision()`][crate::particles::mc_particle::MCParticle::sample_collision()] method
     /// for more information.
-    pub nu_bar: T,
+    pub nu_bar: NuBar<T>,
+    /// Energy distribution of the offspring particles created by a reaction.
+    pub spectrum: FissionSpectrum<T>,
+    /// Law used to sample the particle's energy and direction after a
+    /// scattering reaction.
+    pub scattering: ScatteringLaw<T>,
+    /// Average number of photons emitted when the reaction is a neutron
+    /// capture. No photon is produced if the value is zero.
+    pub photon_yield: T,
+    /// Energy of the photons emitted on a capture, in MeV.
+    pub photon_energy: T,
 }
 
 impl<T: CustomFloat> NuclearDataReaction<T> {
     /// Constructor.
     pub fn new(
         rtype: ReactionType,
-        nu_bar: T,
+        fission: &FissionData<T>,
+        scattering: &ScatteringLaw<T>,
         energies: &[T],
-        polynomial: &Polynomial<T>,
+        data: &CrossSectionData<T>,
         reaction_cross_section: T,
     ) -> Self {
         let n_groups = energies.len() - 1;
         let mut xsection: Vec<f64> = vec![zero(); n_groups];
         let mut normal_value: f64 = 0.0;
-        let two: T = FromPrimitive::from_f64(2.0).unwrap();
 
         (0..n_groups).for_each(|ii| {
-            let energy: T = (energies[ii] + energies[ii + 1]) / two;
-            // 10^(Poly(log10(energy)))
-            // this here gives a value too big for f32
-            xsection[ii] = 10.0_f64.powf(polynomial.val(energy.log10()).to_f64().unwrap());
+            xsection[ii] = data.group_value(energies[ii], energies[ii + 1]);
 
+            // the reference group is the first non-zero one at or above 1 MeV;
+            // this handles threshold reactions
             if (energies[ii + 1] >= T::one()) & (normal_value == 0.0) {
                 normal_value = xsection[ii];
             }
         });
-        let scale = reaction_cross_section.to_f64().unwrap() / normal_value;
+        if normal_value == 0.0 {
+            // fall back on the highest non-zero group
+            normal_value = xsection
+                .iter()
+                .rev()
+                .copied()
+                .find(|xs| *xs != 0.0)
+                .unwrap_or_default();
+        }
+        // a cross-section that is zero everywhere stays so
+        let scale = if normal_value == 0.0 {
+            0.0
+        } else {
+            reaction_cross_section.to_f64().unwrap() / normal_value
+        };
         (0..n_groups).for_each(|ii| {
             xsection[ii] *= scale;
         });
@@ -100,7 +526,11 @@ impl<T: CustomFloat> NuclearDataReaction<T> {
         Self {
             cross_section: xsection_t,
             reaction_type: rtype,
-            nu_bar,
+            nu_bar: fission.nu_bar.clone(),
+            spectrum: fission.spectrum,
+            scattering: scattering.clone(),
+            photon_yield: zero(),
+            photon_energy: zero(),
         }
     }
 }
@@ -108,7 +538,6 @@ impl<T: CustomFloat> NuclearDataReaction<T> {
 /// Structure used to hold a list of reactions.
 ///
 /// The list of reaction held by the structure is specific to a particle species.
-/// At the current time there is only one type of particle in the simulation.
 #[derive(Debug, Default)]
 pub struct NuclearDataSpecies<T: CustomFloat> {
     /// List of reactions.
@@ -120,16 +549,18 @@ impl<T: CustomFloat> NuclearDataSpecies<T> {
     pub fn add_reaction(
         &mut self,
         rtype: ReactionType,
-        nu_bar: T,
+        fission: &FissionData<T>,
+        scattering: &ScatteringLaw<T>,
         energies: &[T],
-        polynomial: &Polynomial<T>,
+        data: &CrossSectionData<T>,
         reaction_cross_section: T,
     ) {
         self.reactions.push(NuclearDataReaction::new(
             rtype,
-            nu_bar,
+            fission,
+            scattering,
             energies,
-            polynomial,
+            data,
             reaction_cross_section,
         ))
     }
@@ -137,10 +568,35 @@ impl<T: CustomFloat> NuclearDataSpecies<T> {
 
 /// Structure used to store reactions for a given isotope.
 ///
-/// At the current time there is only one type of particle in the simulation,
-/// meaning that there is only one species per isotope.
+/// The list is indexed using [`Species::index()`], i.e. it holds one
+/// reaction set per particle species.
 pub type NuclearDataIsotope<T> = Vec<NuclearDataSpecies<T>>;
 
+/// Returns the boundaries of `num_groups` logarithmically spaced energy groups
+/// between `energy_low` and `energy_high`.
+pub fn log_uniform_boundaries<T: CustomFloat>(
+    num_groups: usize,
+    energy_low: T,
+    energy_high: T,
+) -> Vec<T> {
+    let mut energies = vec![zero(); num_groups + 1];
+    let length: T = FromPrimitive::from_usize(num_groups + 1).unwrap();
+    // complete energy levels
+    energies[0] = energy_low;
+    energies[num_groups] = energy_high;
+    let log_low: T = energy_low.ln();
+    let log_high: T = energy_high.ln();
+    let delta = (log_high - log_low) / length;
+
+    (1..num_groups).for_each(|ii| {
+        let step = FromPrimitive::from_usize(ii).unwrap();
+        let log_value: T = log_low + delta * step;
+        energies[ii] = log_value.exp();
+    });
+
+    energies
+}
+
 /// Top level structure used to handle all things related to
 /// nuclear data.
 #[derive(Debug, Default)]
@@ -154,25 +610,11 @@ pub struct NuclearData<T: CustomFloat> {
 }
 
 impl<T: CustomFloat> NuclearData<T> {
-    /// Constructor.
-    pub fn new(num_groups: usize, energy_low: T, energy_high: T) -> Self {
-        let mut energies = vec![zero(); num_groups + 1];
-        let length: T = FromPrimitive::from_usize(num_groups + 1).unwrap();
-        // complete energy levels
-        energies[0] = energy_low;
-        energies[num_groups] = energy_high;
-        let log_low: T = energy_low.ln();
-        let log_high: T = energy_high.ln();
-        let delta = (log_high - log_low) / length;
-
-        (1..num_groups).for_each(|ii| {
-            let step = FromPrimitive::from_usize(ii).unwrap();
-            let log_value: T = log_low + delta * step;
-            energies[ii] = log_value.exp();
-        });
-
+    /// Constructor. The energy groups are defined by the boundaries passed
+    /// as argument, which are expected to be strictly increasing.
+    pub fn new(energies: Vec<T>) -> Self {
         Self {
-            num_energy_groups: num_groups,
+            num_energy_groups: energies.len() - 1,
             isotopes: Vec::new(),
             energies,
         }
@@ -180,80 +622,126 @@ impl<T: CustomFloat> NuclearData<T> {
 
     /// Adds an isotope to the internal list.
     ///
-    /// The isotope is built from the material data and cross-sections
-    /// specified in the material.
+    /// The isotope is built from its parameters and the cross-sections they
+    /// refer to. Neutron reactions are distributed in a round-robin fashion
+    /// over the available reaction types, in the following order: scattering,
+    /// fission, absorption. A reaction type is available if its cross-section
+    /// is specified. Photons get one reaction per available type, among
+    /// Compton scattering and absorption.
     pub fn add_isotope(
         &mut self,
-        cross_section: &FxHashMap<String, Polynomial<T>>,
-        mp: &MaterialParameters<T>,
-        nu_bar: T,
+        cross_section: &FxHashMap<String, CrossSectionData<T>>,
+        ip: &IsotopeParameters<T>,
+        fission: &FissionData<T>,
+        scattering: &ScatteringLaw<T>,
     ) -> usize {
-        // for readability purposes
-        let n_reactions: usize = mp.n_reactions;
-        let fission_function: &Polynomial<T> = &cross_section[&mp.fission_cross_section];
-        let scatter_function: &Polynomial<T> = &cross_section[&mp.scattering_cross_section];
-        let absorption_function: &Polynomial<T> = &cross_section[&mp.absorption_cross_section];
-        let total_cross_section: T = mp.total_cross_section;
-        let fission_weight: T = mp.fission_cross_section_ratio;
-        let scatter_weight: T = mp.scattering_cross_section_ratio;
-        let absorption_weight: T = mp.absorbtion_cross_section_ratio;
-
-        self.isotopes.push(vec![NuclearDataSpecies::default()]);
-        let total_weight = fission_weight + scatter_weight + absorption_weight;
-
-        let mut n_fission = n_reactions / 3;
-        let mut n_scatter = n_reactions / 3;
-        let n_absorption = n_reactions / 3;
-
-        // set reaction distribution
-        match n_reactions % 3 {
-            2 => {
-                n_fission += 1;
-                n_scatter += 1;
-            }
-            1 => {
-                n_scatter += 1;
-            }
-            0 => (),
-            _ => unreachable!(),
-        }
-        let mut f: T = FromPrimitive::from_usize(n_fission).unwrap();
-        let fission_xsection: T = (total_cross_section * fission_weight) / (f * total_weight);
-        f = FromPrimitive::from_usize(n_scatter).unwrap();
-        let scatter_xsection: T = (total_cross_section * scatter_weight) / (f * total_weight);
-        f = FromPrimitive::from_usize(n_absorption).unwrap();
-        let absorption_xsection: T = (total_cross_section * absorption_weight) / (f * total_weight);
-
-        let n = self.isotopes.len();
-        self.isotopes[n - 1][0].reactions.reserve(n_reactions);
-
-        (0..n_reactions).for_each(|ii| match ii % 3 {
-            0 => self.isotopes[n - 1][0].add_reaction(
+        let available = |types: &[(ReactionType, &String, T)]| {
+            types
+                .iter()
+                .filter(|(_, name, _)| !name.is_empty())
+                .map(|(rtype, name, weight)| (*rtype, &cross_section[*name], *weight))
+                .collect::<Vec<_>>()
+        };
+
+        let mut neutron = self.build_species(
+            &available(&[
+                (
+                    ReactionType::Scatter,
+                    &ip.scattering_cross_section,
+                    ip.scattering_cross_section_ratio,
+                ),
+                (
+                    ReactionType::Fission,
+                    &ip.fission_cross_section,
+                    ip.fission_cross_section_ratio,
+                ),
+                (
+                    ReactionType::Absorption,
+                    &ip.absorption_cross_section,
+                    ip.absorption_cross_section_ratio,
+                ),
+            ]),
+            ip.n_reactions,
+            ip.total_cross_section,
+            fission,
+            scattering,
+        );
+        neutron
+            .reactions
+            .iter_mut()
+            .filter(|reaction| reaction.reaction_type == ReactionType::Absorption)
+            .for_each(|reaction| {
+                reaction.photon_yield = ip.capture_photon_yield;
+                reaction.photon_energy = ip.capture_photon_energy;
+            });
+
+        let photon_types = available(&[
+            (
                 ReactionType::Scatter,
-                nu_bar,
-                &self.energies,
-                scatter_function,
-                scatter_xsection,
+                &ip.photon_scattering_cross_section,
+                ip.photon_scattering_cross_section_ratio,
             ),
-            1 => self.isotopes[n - 1][0].add_reaction(
-                ReactionType::Fission,
-                nu_bar,
-                &self.energies,
-                fission_function,
-                fission_xsection,
-            ),
-            2 => self.isotopes[n - 1][0].add_reaction(
+            (
                 ReactionType::Absorption,
-                nu_bar,
-                &self.energies,
-                absorption_function,
-                absorption_xsection,
+                &ip.photon_absorption_cross_section,
+                ip.photon_absorption_cross_section_ratio,
             ),
-            _ => unreachable!(),
-        });
+        ]);
+        let photon = self.build_species(
+            &photon_types,
+            photon_types.len(),
+            ip.photon_cross_section,
+            &FissionData::default(),
+            &ScatteringLaw::Compton,
+        );
+
+        self.isotopes.push(vec![neutron, photon]);
         self.isotopes.len() - 1
     }
 
+    /// Builds the reaction set of a species. `n_reactions` reactions are
+    /// distributed in a round-robin fashion over the available types, the
+    /// total cross-section being split according to the types' relative weights.
+    fn build_species(
+        &self,
+        available: &[(ReactionType, &CrossSectionData<T>, T)],
+        n_reactions: usize,
+        total_cross_section: T,
+        fission: &FissionData<T>,
+        scattering: &ScatteringLaw<T>,
+    ) -> NuclearDataSpecies<T> {
+        let mut species = NuclearDataSpecies::default();
+        let n_types = available.len();
+        if n_types == 0 {
+            return species;
+        }
+        let total_weight: T = available.iter().map(|(_, _, weight)| *weight).sum();
+
+        // set reaction distribution & per-reaction cross-section value
+        let xsections: Vec<T> = (0..n_types)
+            .map(|type_idx| {
+                let n_type = n_reactions / n_types + usize::from(type_idx < n_reactions % n_types);
+                let f: T = FromPrimitive::from_usize(n_type).unwrap();
+                (total_cross_section * available[type_idx].2) / (f * total_weight)
+            })
+            .collect();
+
+        species.reactions.reserve(n_reactions);
+
+        (0..n_reactions).for_each(|ii| {
+            let (rtype, function, _) = available[ii % n_types];
+            species.add_reaction(
+                rtype,
+                fission,
+                scattering,
+                &self.energies,
+                function,
+                xsections[ii % n_types],
+            )
+        });
+        species
+    }
+
     /// Returns the energy group a specific energy belongs to by using a dichotomy search.
     pub fn get_energy_groups(&self, energy: T) -> usize {
         let num_energies = self.energies.len();
@@ -262,9 +750,9 @@ impl<T: CustomFloat> NuclearData<T> {
         if energy <= self.energies[0] {
             return 0;
         }
-        // extreme high
-        if energy > self.energies[num_energies - 1] {
-            return num_energies - 1;
+        // extreme high; energies above the upper bound belong to the last group
+        if energy >= self.energies[num_energies - 1] {
+            return num_energies - 2;
         }
 
         // dichotomy search
@@ -283,13 +771,174 @@ impl<T: CustomFloat> NuclearData<T> {
         low
     }
 
-    /// Returns the total cross-section for a given energy group.
-    pub fn get_total_cross_section(&self, isotope_index: usize, group: usize) -> T {
+    /// Returns the total cross-section of a species for a given energy group.
+    pub fn get_total_cross_section(
+        &self,
+        isotope_index: usize,
+        species: Species,
+        group: usize,
+    ) -> T {
         // sum all reaction's xsection for a given isotope at a given energy level
-        self.isotopes[isotope_index][0]
+        self.isotopes[isotope_index][species.index()]
             .reactions
             .iter()
             .map(|reaction| reaction.cross_section[group])
             .sum()
     }
 }
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(averaging: CrossSectionAveraging) -> TabulatedCrossSection<f64> {
+        TabulatedCrossSection {
+            energies: vec![1.0, 2.0, 4.0],
+            values: vec![1.0, 3.0, 3.0],
+            averaging,
+        }
+    }
+
+    #[test]
+    fn tabulated_interpolation() {
+        let xs = table(CrossSectionAveraging::Interpolated);
+        assert_eq!(xs.val(0.5), 1.0);
+        assert_eq!(xs.val(1.5), 2.0);
+        assert_eq!(xs.val(3.0), 3.0);
+        assert_eq!(xs.val(10.0), 3.0);
+    }
+
+    #[test]
+    fn tabulated_group_average() {
+        // plain average over [1; 4]: (2 * 1 + 3 * 2) / 3
+        let xs = table(CrossSectionAveraging::Interpolated);
+        assert!((xs.group_average(1.0, 4.0) - 8.0 / 3.0).abs() < 1e-12);
+        // 1/E weighted average over [1; 2], where v(E) = 2E - 1
+        let xs = table(CrossSectionAveraging::FluxWeighted);
+        let expected = (2.0 - 2.0_f64.ln()) / 2.0_f64.ln();
+        assert!((xs.group_average(1.0, 2.0) - expected).abs() < 1e-12);
+        // constant data is left unchanged
+        assert!((xs.group_average(2.5, 3.5) - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn threshold_reaction() {
+        // zero below 10 MeV: the 1 MeV group cannot be used as reference
+        let data: CrossSectionData<f64> = CrossSectionData::Tabulated(TabulatedCrossSection {
+            energies: vec![1e-9, 10.0, 20.0],
+            values: vec![0.0, 0.0, 2.0],
+            averaging: CrossSectionAveraging::Interpolated,
+        });
+        let energies = [1e-9, 1.0, 5.0, 10.0, 15.0, 20.0];
+        let reaction = NuclearDataReaction::new(
+            ReactionType::Absorption,
+            &FissionData::default(),
+            &ScatteringLaw::default(),
+            &energies,
+            &data,
+            0.5,
+        );
+        assert!(reaction.cross_section.iter().all(|xs| xs.is_finite()));
+        assert_eq!(&reaction.cross_section[..3], &[0.0, 0.0, 0.0]);
+        // the first non-zero group is the reference
+        assert!((reaction.cross_section[3] - 0.5).abs() < 1e-12);
+        assert!(reaction.cross_section[4] > reaction.cross_section[3]);
+    }
+
+    #[test]
+    fn energy_dependent_nu_bar() {
+        let constant: NuBar<f64> = NuBar::Constant(2.4);
+        assert_eq!(constant.val(1.0), 2.4);
+        let polynomial: NuBar<f64> = NuBar::Polynomial(Polynomial {
+            aa: 0.0,
+            bb: 0.0,
+            cc: 0.0,
+            dd: 0.1,
+            ee: 2.4,
+        });
+        assert!((polynomial.val(2.0) - 2.6).abs() < 1e-12);
+        assert!((polynomial.max_val(0.0, 20.0) - 4.4).abs() < 1e-12);
+        let tabulated: NuBar<f64> = NuBar::Tabulated {
+            energies: vec![0.0, 10.0],
+            values: vec![2.0, 4.0],
+        };
+        assert!((tabulated.val(5.0) - 3.0).abs() < 1e-12);
+        assert_eq!(tabulated.max_val(0.0, 20.0), 4.0);
+    }
+
+    #[test]
+    fn fission_spectra() {
+        let n_samples = 100000;
+        let mut seed: u64 = 90374384094798327;
+        let mut mean = |spectrum: FissionSpectrum<f64>| {
+            (0..n_samples)
+                .map(|_| {
+                    let energy = spectrum.sample(&mut seed);
+                    assert!(energy >= 0.0);
+                    energy
+                })
+                .sum::<f64>()
+                / n_samples as f64
+        };
+        // mean of a Maxwellian distribution: 3T/2
+        let maxwellian = mean(FissionSpectrum::Maxwellian { temperature: 1.3 });
+        assert!((maxwellian - 1.95).abs() < 0.02);
+        // mean of a Watt distribution: 3a/2 + a^2 b/4
+        let (a, b) = (0.988, 2.249);
+        let watt = mean(FissionSpectrum::Watt { a, b });
+        assert!((watt - (1.5 * a + a * a * b / 4.0)).abs() < 0.02);
+        // mean of the legacy distribution: 20 * 7/12
+        let legacy = mean(FissionSpectrum::Legacy);
+        assert!((legacy - 35.0 / 3.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn elastic_scattering() {
+        let n_samples = 100000;
+        let mut seed: u64 = 90374384094798327;
+        let mass = 12.0;
+        let isotropic: ScatteringLaw<f64> = ScatteringLaw::Elastic { legendre: vec![] };
+        let (mut energy_sum, mut cosine_sum) = (0.0, 0.0);
+        (0..n_samples).for_each(|_| {
+            let (energy, cosine) = isotropic.sample(1.0, mass, &mut seed);
+            energy_sum += energy;
+            cosine_sum += cosine;
+        });
+        // isotropic in the CM frame: <E'> = E (1 + alpha) / 2, <mu_lab> = 2 / 3A
+        let alpha: f64 = ((mass - 1.0) / (mass + 1.0)).powi(2);
+        assert!((energy_sum / n_samples as f64 - (1.0 + alpha) / 2.0).abs() < 0.01);
+        assert!((cosine_sum / n_samples as f64 - 2.0 / (3.0 * mass)).abs() < 0.01);
+
+        // the first Legendre moment is the mean CM cosine
+        let mu_mean = (0..n_samples)
+            .map(|_| sample_legendre(&[0.3, 0.1], &mut seed))
+            .sum::<f64>()
+            / n_samples as f64;
+        assert!((mu_mean - 0.3).abs() < 0.01);
+    }
+
+    #[test]
+    fn compton_scattering() {
+        let mut seed: u64 = 90374384094798327;
+        let energy = 1.0;
+        let kk = energy / 0.510999;
+        let law: ScatteringLaw<f64> = ScatteringLaw::Compton;
+        let n_samples = 100000;
+        let mut cosine_sum = 0.0;
+        (0..n_samples).for_each(|_| {
+            let (energy_out, cosine) = law.sample(energy, 1.0, &mut seed);
+            // outgoing energy is bounded by the backscatter and forward cases
+            assert!(energy_out <= energy);
+            assert!(energy_out >= energy / (1.0 + 2.0 * kk) - 1e-12);
+            // Compton relation between energy and angle
+            assert!((energy / energy_out - 1.0 - kk * (1.0 - cosine)).abs() < 1e-9);
+            cosine_sum += cosine;
+        });
+        // forward-peaked at high energy
+        assert!(cosine_sum / n_samples as f64 > 0.2);
+    }
+}
@@ -8,8 +8,7 @@
 
 use std::{
     fmt::Debug,
-    fs::OpenOptions,
-    io::Write,
+    io::{Result, Write},
     iter::zip,
     ops::{Index, IndexMut},
     sync::atomic::Ordering,
@@ -19,8 +18,13 @@ use atomic::Atomic;
 use num::zero;
 
 use crate::{
-    constants::CustomFloat,
-    utils::mc_fast_timer::{self, MCFastTimerContainer, Section},
+    constants::{sim::N_SPECIES, CustomFloat},
+    parameters::SimulationParameters,
+    particles::mc_particle::Species,
+    utils::{
+        mc_fast_timer::{self, MCFastTimerContainer, Section},
+        output::{self, TALLIES_REPORT},
+    },
 };
 
 /// Enum representing a tally event.
@@ -37,9 +41,15 @@ pub enum MCTallyEvent {
     FacetCrossingEscape,
     /// Value for a facet crossing event resulting in a reflection on the facet.
     FacetCrossingReflection,
+    /// Value for a facet crossing event resulting in a diffuse reflection on
+    /// the facet, i.e. a re-emission following a cosine distribution.
+    FacetCrossingWhiteReflection,
     /// Value for a facet crossing event resulting in a cell exit to an
     /// off-processor cell.
     FacetCrossingCommunication,
+    /// Value for a facet crossing event resulting in the particle being killed
+    /// by russian roulette when entering a less important cell.
+    FacetCrossingRoulette,
 }
 
 //========
@@ -71,9 +81,9 @@ impl<T: CustomFloat> FluenceDomain<T> {
 // Balance
 //========
 
-pub const N_TALLIED_EVENT: usize = 14;
+pub const N_TALLIED_EVENT: usize = 16;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum TalliedEvent {
     Absorb,
     Census,
@@ -88,24 +98,83 @@ pub enum TalliedEvent {
     OverRr,
     WeightRr,
     Split,
+    ImportanceRr,
+    ImportanceSplit,
     NumSegments,
 }
 
+impl TalliedEvent {
+    /// All tallied events, in index order.
+    pub const ALL: [TalliedEvent; N_TALLIED_EVENT] = [
+        TalliedEvent::Absorb,
+        TalliedEvent::Census,
+        TalliedEvent::Escape,
+        TalliedEvent::Collision,
+        TalliedEvent::End,
+        TalliedEvent::Fission,
+        TalliedEvent::Produce,
+        TalliedEvent::Scatter,
+        TalliedEvent::Start,
+        TalliedEvent::Source,
+        TalliedEvent::OverRr,
+        TalliedEvent::WeightRr,
+        TalliedEvent::Split,
+        TalliedEvent::ImportanceRr,
+        TalliedEvent::ImportanceSplit,
+        TalliedEvent::NumSegments,
+    ];
+
+    /// Returns the name of the event, in snake case.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TalliedEvent::Absorb => "absorb",
+            TalliedEvent::Census => "census",
+            TalliedEvent::Escape => "escape",
+            TalliedEvent::Collision => "collision",
+            TalliedEvent::End => "end",
+            TalliedEvent::Fission => "fission",
+            TalliedEvent::Produce => "produce",
+            TalliedEvent::Scatter => "scatter",
+            TalliedEvent::Start => "start",
+            TalliedEvent::Source => "source",
+            TalliedEvent::OverRr => "over_rr",
+            TalliedEvent::WeightRr => "weight_rr",
+            TalliedEvent::Split => "split",
+            TalliedEvent::ImportanceRr => "importance_rr",
+            TalliedEvent::ImportanceSplit => "importance_split",
+            TalliedEvent::NumSegments => "num_segments",
+        }
+    }
+}
+
 /// Structure used to keep track of the number of event in the simulation.
 ///
 /// During the simulation, each time an event of interest occurs, the counters
 /// are incremented accordingly. In a parallel context, this structure should be
 /// operated on using atomic operations.
+///
+/// Events occurring during tracking are also counted for each particle species;
+/// population control events are only counted in the total.
+///
+/// The statistical weight lost to absorption is tallied alongside the events:
+/// with implicit capture, particles are never absorbed, so the number of
+/// absorptions does not reflect the physics of the problem.
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Balance {
     /// Array used to store tallied event. See [TalliedEvent] for more information.
     pub data: [u64; N_TALLIED_EVENT],
+    /// Species-sorted arrays of tallied events. See [`Species::index()`].
+    pub species: [[u64; N_TALLIED_EVENT]; N_SPECIES],
+    /// Species-sorted absorbed statistical weight.
+    pub absorbed_weight: [f64; N_SPECIES],
 }
 
 impl Balance {
     /// Reset fields to their default value i.e. `0`.
     pub fn reset(&mut self) {
         self.data.fill(0_u64);
+        self.species.iter_mut().for_each(|data| data.fill(0_u64));
+        self.absorbed_weight.fill(0.0);
     }
 
     /// Add another [Balance]'s value to its own.
@@ -114,6 +183,73 @@ impl Balance {
             .iter_mut()
             .zip(bal.data.iter())
             .for_each(|(lhs, rhs)| *lhs += *rhs);
+        self.species
+            .iter_mut()
+            .flatten()
+            .zip(bal.species.iter().flatten())
+            .for_each(|(lhs, rhs)| *lhs += *rhs);
+        self.absorbed_weight
+            .iter_mut()
+            .zip(bal.absorbed_weight.iter())
+            .for_each(|(lhs, rhs)| *lhs += *rhs);
+    }
+
+    /// Count `n` events of a given type undergone by particles of a given species.
+    pub fn tally(&mut self, species: Species, event: TalliedEvent, n: u64) {
+        self[event] += n;
+        self.species[species.index()][event as usize] += n;
+    }
+
+    /// Tally statistical weight absorbed from particles of a given species.
+    pub fn absorb<T: CustomFloat>(&mut self, species: Species, weight: T) {
+        self.absorbed_weight[species.index()] += weight.to_f64().unwrap();
+    }
+
+    /// Returns the total absorbed statistical weight.
+    pub fn total_absorbed_weight(&self) -> f64 {
+        self.absorbed_weight.iter().sum()
+    }
+
+    /// Prints the events tallied for each species. The output looks like
+    /// the following:
+    ///
+    /// ```shell
+    /// [Species Summary]
+    /// species |     absorb |   abs_weight |    scatter |    fission |    produce |  collision |     escape |     census |    num_seg |   scalar_flux
+    /// neutron |      75269 |   6.318211e9 |    1169199 |          0 |          0 |    1244468 |      33281 |     878971 |    2625225 |   1.542698e10
+    ///  photon |      23275 |   1.953858e9 |     244290 |          0 |      73779 |     267565 |      49997 |      12976 |     878857 |   1.595132e10
+    /// ```
+    ///
+    /// The `produce` column counts particles created by fission for neutrons,
+    /// and by radiative capture for photons.
+    pub fn print_species_summary<T: CustomFloat>(&self, scalar_flux: &[T; N_SPECIES]) {
+        println!("[Species Summary]");
+        println!(
+            "{:<7} | {:>10} | {:>12} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} | {:>13}",
+            "species", "absorb", "abs_weight", "scatter", "fission", "produce", "collision", "escape", "census", "num_seg", "scalar_flux"
+        );
+        [Species::Neutron, Species::Photon]
+            .into_iter()
+            .for_each(|species| {
+                let name = match species {
+                    Species::Photon => "photon",
+                    _ => "neutron",
+                };
+                println!(
+                    "{:>7} | {:>10} | {:>12.6e} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} | {:>13.6e}",
+                    name,
+                    self[(species, TalliedEvent::Absorb)],
+                    self.absorbed_weight[species.index()],
+                    self[(species, TalliedEvent::Scatter)],
+                    self[(species, TalliedEvent::Fission)],
+                    self[(species, TalliedEvent::Produce)],
+                    self[(species, TalliedEvent::Collision)],
+                    self[(species, TalliedEvent::Escape)],
+                    self[(species, TalliedEvent::Census)],
+                    self[(species, TalliedEvent::NumSegments)],
+                    scalar_flux[species.index()],
+                );
+            });
     }
 }
 
@@ -137,6 +273,14 @@ impl IndexMut<TalliedEvent> for Balance {
     }
 }
 
+impl Index<(Species, TalliedEvent)> for Balance {
+    type Output = u64;
+
+    fn index(&self, index: (Species, TalliedEvent)) -> &Self::Output {
+        &self.species[index.0.index()][index.1 as usize]
+    }
+}
+
 // Add op (useful when folding)
 
 impl std::ops::Add<Balance> for Balance {
@@ -209,14 +353,25 @@ impl<T: CustomFloat> IndexMut<(usize, usize)> for ScalarFluxDomain<T> {
 pub struct Tallies<T: CustomFloat> {
     /// Cyclic balances.
     pub balance_cycle: Balance,
-    /// Top-level structure holding scalar flux data.
+    /// Top-level structure holding neutron scalar flux data.
     pub scalar_flux_domain: ScalarFluxDomain<T>,
+    /// Top-level structure holding photon scalar flux data.
+    pub photon_flux_domain: ScalarFluxDomain<T>,
 }
 
 impl<T: CustomFloat> Tallies<T> {
     /// Prepare the tallies for use.
     pub fn initialize_tallies(&mut self, n_cells: usize, num_energy_groups: usize) {
         self.scalar_flux_domain = ScalarFluxDomain::new(n_cells, num_energy_groups);
+        self.photon_flux_domain = ScalarFluxDomain::new(n_cells, num_energy_groups);
+    }
+
+    /// Returns the scalar flux structure of a given species.
+    pub fn flux_domain(&self, species: Species) -> &ScalarFluxDomain<T> {
+        match species {
+            Species::Photon => &self.photon_flux_domain,
+            _ => &self.scalar_flux_domain,
+        }
     }
 
     /// Prints summarized data recorded by the tallies.
@@ -226,49 +381,51 @@ impl<T: CustomFloat> Tallies<T> {
     /// would look like the following:
     ///
     /// ```shell
-    /// cycle   |  start |   source |       rr |      split |     absorb |    scatter |    fission |    produce |  collision |     escape |     census |    num_seg |   scalar_flux | ppControl (s) | cycleTracking (s) | cycleSync (s)
-    ///       0 |      0 |    10000 |        0 |      90000 |      97237 |     711673 |      86904 |      86904 |     895814 |          0 |       2763 |    2245733 |   8.984036e11 |  1.757e-2     |     1.01038e0     |  2.200e-4
-    ///       1 |   2763 |    10000 |        0 |      87202 |      97576 |     715193 |      86951 |      86951 |     899720 |          0 |       2389 |    2250433 |   9.191721e11 |  1.337e-2     |     1.02491e0     |  7.382e-4
-    ///       2 |   2389 |    10000 |        0 |      87625 |      97569 |     717781 |      87733 |      87733 |     903083 |          0 |       2445 |    2262159 |   9.303649e11 |  9.174e-3     |     1.03161e0     |  7.335e-4
-    ///       3 |   2445 |    10000 |     1468 |      87569 |      96180 |     704095 |      85839 |      85839 |     886114 |          0 |       2366 |    2217454 |   9.227719e11 |  1.119e-2     |     1.01220e0     |  6.674e-4
-    ///       4 |   2366 |    10000 |      331 |      87599 |      97132 |     716577 |      87708 |      87708 |     901417 |          0 |       2502 |    2256889 |   9.255832e11 |  1.124e-2     |     1.02922e0     |  6.787e-4
+    /// cycle   |  start |   source |       rr |      split |     absorb |   abs_weight |    scatter |    fission |    produce |  collision |     escape |     census |    num_seg |   scalar_flux | ppControl (s) | cycleTracking (s) | cycleSync (s)
+    ///       0 |      0 |    10000 |        0 |      90000 |      97237 |   9.732811e9 |     711673 |      86904 |      86904 |     895814 |          0 |       2763 |    2245733 |   8.984036e11 |  1.757e-2     |     1.01038e0     |  2.200e-4
+    ///       1 |   2763 |    10000 |        0 |      87202 |      97576 |   9.766741e9 |     715193 |      86951 |      86951 |     899720 |          0 |       2389 |    2250433 |   9.191721e11 |  1.337e-2     |     1.02491e0     |  7.382e-4
+    ///       2 |   2389 |    10000 |        0 |      87625 |      97569 |   9.760130e9 |     717781 |      87733 |      87733 |     903083 |          0 |       2445 |    2262159 |   9.303649e11 |  9.174e-3     |     1.03161e0     |  7.335e-4
+    ///       3 |   2445 |    10000 |     1468 |      87569 |      96180 |   9.624035e9 |     704095 |      85839 |      85839 |     886114 |          0 |       2366 |    2217454 |   9.227719e11 |  1.119e-2     |     1.01220e0     |  6.674e-4
+    ///       4 |   2366 |    10000 |      331 |      87599 |      97132 |   9.716307e9 |     716577 |      87708 |      87708 |     901417 |          0 |       2502 |    2256889 |   9.255832e11 |  1.124e-2     |     1.02922e0     |  6.787e-4
     /// ```
     ///
     /// - `cycle` column gives the cycle number.
     /// - `start` column gives the number of particle at the start of the cycle,
     ///   before population control algorithms.
     /// - `source`, `rr`, `split` columns count
-    ///   [`population_control`][crate::simulation::population_control] events.
+    ///   [`population_control`][crate::simulation::population_control] events,
+    ///   as well as splitting & roulette from variance reduction during tracking.
     /// - `absorb`, `scatter`, `fission`, `produce`, `collision` columns count
     ///   [collision_event][crate::simulation::collision_event] events.
+    /// - `abs_weight` column gives the statistical weight lost to absorption. It
+    ///   is written last in the `csv` file to keep the indices of other columns.
     /// - `escape`, `census` columns count the remaining possible
     ///   [`outcomes`][crate::simulation::mc_segment_outcome].
     /// - `num_seg` column counts the total number of computed segments.
-    /// - `scalar_flux` is the total scalar flux of the problem.
+    /// - `scalar_flux` is the total neutron scalar flux of the problem.
     /// - The last three columns indicate the time spent in each section.
+    ///
+    /// If the `csv` switch of the parameters is set, the data is also written
+    /// to the [`TALLIES_REPORT`] output file.
     pub fn print_summary(
         &self,
         timer_container: &mut MCFastTimerContainer,
         step: usize,
-        csv: bool,
-    ) {
+        sim_params: &SimulationParameters<T>,
+    ) -> Result<()> {
+        let csv = sim_params.csv;
         if step == 0 {
             // print header
             println!("[Tally Summary]");
             println!(
-                "{:<7} | {:>8} {:>10} {:>10} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>15} {:>13} {:>19} {:>13}",
-                "cycle", "start |", "source |", "rr |", "split |", "absorb |", "scatter |", "fission |", "produce |", "collision |", 
+                "{:<7} | {:>8} {:>10} {:>10} {:>12} {:>12} {:>14} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>15} {:>13} {:>19} {:>13}",
+                "cycle", "start |", "source |", "rr |", "split |", "absorb |", "abs_weight |", "scatter |", "fission |", "produce |", "collision |", 
                 "escape |", "census |", "num_seg |", "scalar_flux |", "ppControl (s) |", "cycleTracking (s) |", "cycleSync (s)"
             );
             if csv {
                 // write column name
-                let mut file = OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open("tallies_report.csv")
-                    .unwrap();
-                writeln!(file, "cycle;start;source;rr;split;absorb;scatter;fission;produce;collision;escape;census;num_seg;scalar_flux;ppControl(s);cycleTracking(s);cycleSync(s)").unwrap();
+                let mut file = output::create_output(sim_params, TALLIES_REPORT)?;
+                writeln!(file, "cycle;start;source;rr;split;absorb;scatter;fission;produce;collision;escape;census;num_seg;scalar_flux;ppControl(s);cycleTracking(s);cycleSync(s);abs_weight")?;
             }
         }
         let cy_init = mc_fast_timer::get_last_cycle(timer_container, Section::PopulationControl);
@@ -276,13 +433,14 @@ impl<T: CustomFloat> Tallies<T> {
         let cy_fin = mc_fast_timer::get_last_cycle(timer_container, Section::CycleSync);
         let sf_sum = self.scalar_flux_sum();
         let bal = &self.balance_cycle;
-        println!("{:>7} |{:>7} |{:>9} |{:>9} |{:>11} |{:>11} |{:>11} |{:>11} |{:>11} |{:>11} |{:>11} |{:>11} |{:>11} |{:>14.6e} |{:>10.3e}     |{:>14.5e}     |{:>10.3e}",
+        println!("{:>7} |{:>7} |{:>9} |{:>9} |{:>11} |{:>11} |{:>13.6e} |{:>11} |{:>11} |{:>11} |{:>11} |{:>11} |{:>11} |{:>11} |{:>14.6e} |{:>10.3e}     |{:>14.5e}     |{:>10.3e}",
             step,
             bal[TalliedEvent::Start],
             bal[TalliedEvent::Source],
-            bal[TalliedEvent::OverRr] + bal[TalliedEvent::WeightRr],
-            bal[TalliedEvent::Split],
+            bal[TalliedEvent::OverRr] + bal[TalliedEvent::WeightRr] + bal[TalliedEvent::ImportanceRr],
+            bal[TalliedEvent::Split] + bal[TalliedEvent::ImportanceSplit],
             bal[TalliedEvent::Absorb],
+            bal.total_absorbed_weight(),
             bal[TalliedEvent::Scatter],
             bal[TalliedEvent::Fission],
             bal[TalliedEvent::Produce],
@@ -297,18 +455,17 @@ impl<T: CustomFloat> Tallies<T> {
         );
 
         if csv {
-            let mut file = OpenOptions::new()
-                .append(true)
-                .open("tallies_report.csv")
-                .unwrap();
+            let mut file = output::append_output(sim_params, TALLIES_REPORT)?;
             writeln!(
                 file,
-                "{};{};{};{};{};{};{};{};{};{};{};{};{};{:e};{:e};{:e};{:e}",
+                "{};{};{};{};{};{};{};{};{};{};{};{};{};{:e};{:e};{:e};{:e};{:e}",
                 step,
                 bal[TalliedEvent::Start],
                 bal[TalliedEvent::Source],
-                bal[TalliedEvent::OverRr] + bal[TalliedEvent::WeightRr],
-                bal[TalliedEvent::Split],
+                bal[TalliedEvent::OverRr]
+                    + bal[TalliedEvent::WeightRr]
+                    + bal[TalliedEvent::ImportanceRr],
+                bal[TalliedEvent::Split] + bal[TalliedEvent::ImportanceSplit],
                 bal[TalliedEvent::Absorb],
                 bal[TalliedEvent::Scatter],
                 bal[TalliedEvent::Fission],
@@ -321,14 +478,20 @@ impl<T: CustomFloat> Tallies<T> {
                 cy_init,
                 cy_track,
                 cy_fin,
-            )
-            .unwrap();
+                bal.total_absorbed_weight(),
+            )?;
         }
+        Ok(())
     }
 
-    /// Computes the global scalar flux value of the problem.
+    /// Computes the global neutron scalar flux value of the problem.
     pub fn scalar_flux_sum(&self) -> T {
-        self.scalar_flux_domain
+        self.species_flux_sum(Species::Neutron)
+    }
+
+    /// Computes the global scalar flux value of a given species.
+    pub fn species_flux_sum(&self, species: Species) -> T {
+        self.flux_domain(species)
             .cell
             .iter()
             .map(|sf_cell| sf_cell.load(Ordering::Relaxed))
@@ -342,5 +505,6 @@ impl<T: CustomFloat> Tallies<T> {
         self.balance_cycle[TalliedEvent::Start] = new_start;
 
         self.scalar_flux_domain.reset();
+        self.photon_flux_domain.reset();
     }
 }
@@ -0,0 +1,295 @@
+//! Particle track recording
+//!
+//! This module contains the structure used to record the histories of a
+//! subset of particles during tracking, and to write them to files. This is
+//! intended as a debugging tool: recording is done under a lock and all
+//! recorded tracks are kept in memory until the end of the simulation.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Result, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{
+    constants::CustomFloat,
+    data::{mc_vector::MCVector, tallies::MCTallyEvent},
+    parameters::SimulationParameters,
+    particles::mc_particle::{MCParticle, Species},
+    utils::output,
+};
+
+/// Structure used to represent a point of a particle track, i.e. the end of
+/// a segment. Particles crossing a periodic boundary are translated after
+/// the point is recorded, which shows as a jump in the track.
+#[derive(Debug, Clone, Copy)]
+pub struct TrackPoint<T: CustomFloat> {
+    /// Coordinates of the end of the segment.
+    pub coordinate: MCVector<T>,
+    /// Event ending the segment. `None` for the first point of a track.
+    pub event: Option<MCTallyEvent>,
+    /// Global index of the cell the particle is in after the event.
+    pub cell: usize,
+    /// Kinetic energy of the particle after the event.
+    pub energy: T,
+    /// Statistical weight of the particle after the event.
+    pub weight: T,
+}
+
+/// Structure used to represent the track of a particle over a single cycle.
+#[derive(Debug, Clone)]
+pub struct ParticleTrack<T: CustomFloat> {
+    /// Identifier of the particle.
+    pub identifier: u64,
+    /// Species of the particle.
+    pub species: Species,
+    /// Cycle during which the track was recorded.
+    pub cycle: usize,
+    /// Points of the track, in chronological order.
+    pub points: Vec<TrackPoint<T>>,
+}
+
+impl<T: CustomFloat> ParticleTrack<T> {
+    /// Adds a point to the track, using the current energy & weight of the
+    /// particle.
+    pub fn push(
+        &mut self,
+        particle: &MCParticle<T>,
+        coordinate: MCVector<T>,
+        event: Option<MCTallyEvent>,
+        cell: usize,
+    ) {
+        self.points.push(TrackPoint {
+            coordinate,
+            event,
+            cell,
+            energy: particle.kinetic_energy,
+            weight: particle.weight,
+        });
+    }
+}
+
+/// Structure used to select particles & store their tracks.
+///
+/// A particle is recorded if its identifier is part of the user-specified
+/// list, or if it is one of the first histories tracked during the cycle.
+/// Recording is disabled if no output file is specified.
+#[derive(Debug, Default)]
+pub struct ParticleTracks<T: CustomFloat> {
+    /// Sorted identifiers of the particles to record.
+    identifiers: Vec<u64>,
+    /// Number of histories to record at each cycle, regardless of identifiers.
+    max_histories: usize,
+    /// Number of histories selected during the current cycle.
+    n_histories: AtomicUsize,
+    /// Index of the current cycle.
+    cycle: usize,
+    /// Recorded tracks.
+    pub tracks: Mutex<Vec<ParticleTrack<T>>>,
+}
+
+impl<T: CustomFloat> ParticleTracks<T> {
+    /// Constructor.
+    pub fn new(sim_params: &SimulationParameters<T>) -> Self {
+        if sim_params.track_out.is_empty() {
+            return Self::default();
+        }
+        let mut identifiers = sim_params.track_particles.clone();
+        identifiers.sort_unstable();
+        Self {
+            identifiers,
+            max_histories: sim_params.track_histories,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if particles may be recorded.
+    pub fn is_enabled(&self) -> bool {
+        !self.identifiers.is_empty() | (self.max_histories != 0)
+    }
+
+    /// Prepares the structure for the given cycle.
+    pub fn start_cycle(&mut self, cycle: usize) {
+        self.cycle = cycle;
+        *self.n_histories.get_mut() = 0;
+    }
+
+    /// Returns an empty track if the particle should be recorded.
+    pub fn select(&self, particle: &MCParticle<T>) -> Option<ParticleTrack<T>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let selected = self.identifiers.binary_search(&particle.identifier).is_ok()
+            || (self.n_histories.fetch_add(1, Ordering::Relaxed) < self.max_histories);
+        selected.then(|| ParticleTrack {
+            identifier: particle.identifier,
+            species: particle.species,
+            cycle: self.cycle,
+            points: Vec::new(),
+        })
+    }
+
+    /// Stores a completed track.
+    pub fn record(&self, track: ParticleTrack<T>) {
+        self.tracks.lock().unwrap().push(track);
+    }
+
+    /// Writes the recorded tracks to the `<track_out>.csv` output file and, if
+    /// the `track_vtk` switch is set, as polylines to `<track_out>.vtk`. Tracks
+    /// are sorted by cycle and particle identifier.
+    ///
+    /// In the VTK file, the event ending each segment is stored as the index
+    /// of the [MCTallyEvent] variant, `-1` marking the first point of a track.
+    pub fn write(&self, sim_params: &SimulationParameters<T>) -> Result<()> {
+        let mut tracks = self.tracks.lock().unwrap();
+        tracks.sort_by_key(|track| (track.cycle, track.identifier));
+
+        let file_name = &sim_params.track_out;
+        let mut file = BufWriter::new(output::create_output(
+            sim_params,
+            &(file_name.to_owned() + ".csv"),
+        )?);
+        writeln!(
+            file,
+            "cycle;identifier;species;point;x;y;z;event;cell;energy;weight"
+        )?;
+        for track in tracks.iter() {
+            for (point_idx, point) in track.points.iter().enumerate() {
+                let event = point
+                    .event
+                    .map_or("Start".to_string(), |e| format!("{e:?}"));
+                writeln!(
+                    file,
+                    "{};{};{:?};{};{};{};{};{};{};{};{}",
+                    track.cycle,
+                    track.identifier,
+                    track.species,
+                    point_idx,
+                    point.coordinate.x,
+                    point.coordinate.y,
+                    point.coordinate.z,
+                    event,
+                    point.cell,
+                    point.energy,
+                    point.weight,
+                )?;
+            }
+        }
+        file.flush()?;
+
+        if sim_params.track_vtk {
+            let file = output::create_output(sim_params, &(file_name.to_owned() + ".vtk"))?;
+            write_polylines(file, &tracks)?;
+        }
+        Ok(())
+    }
+}
+
+//==================
+// Private functions
+//==================
+
+fn write_polylines<T: CustomFloat>(file: File, tracks: &[ParticleTrack<T>]) -> Result<()> {
+    let mut file = BufWriter::new(file);
+    let n_points: usize = tracks.iter().map(|track| track.points.len()).sum();
+
+    writeln!(file, "# vtk DataFile Version 3.0")?;
+    writeln!(file, "fastiron particle tracks")?;
+    writeln!(file, "ASCII")?;
+    writeln!(file, "DATASET POLYDATA")?;
+    writeln!(file, "POINTS {n_points} double")?;
+    for point in tracks.iter().flat_map(|track| &track.points) {
+        let rr = point.coordinate;
+        writeln!(file, "{} {} {}", rr.x, rr.y, rr.z)?;
+    }
+    writeln!(file, "LINES {} {}", tracks.len(), tracks.len() + n_points)?;
+    let mut offset: usize = 0;
+    for track in tracks {
+        write!(file, "{}", track.points.len())?;
+        for point_idx in offset..offset + track.points.len() {
+            write!(file, " {point_idx}")?;
+        }
+        writeln!(file)?;
+        offset += track.points.len();
+    }
+
+    // track data
+    writeln!(file, "CELL_DATA {}", tracks.len())?;
+    writeln!(file, "SCALARS identifier unsigned_long 1")?;
+    writeln!(file, "LOOKUP_TABLE default")?;
+    for track in tracks {
+        writeln!(file, "{}", track.identifier)?;
+    }
+    writeln!(file, "SCALARS cycle int 1")?;
+    writeln!(file, "LOOKUP_TABLE default")?;
+    for track in tracks {
+        writeln!(file, "{}", track.cycle)?;
+    }
+
+    // point data
+    writeln!(file, "POINT_DATA {n_points}")?;
+    writeln!(file, "SCALARS event int 1")?;
+    writeln!(file, "LOOKUP_TABLE default")?;
+    for point in tracks.iter().flat_map(|track| &track.points) {
+        writeln!(file, "{}", point.event.map_or(-1, |e| e as i32))?;
+    }
+    writeln!(file, "SCALARS cell int 1")?;
+    writeln!(file, "LOOKUP_TABLE default")?;
+    for point in tracks.iter().flat_map(|track| &track.points) {
+        writeln!(file, "{}", point.cell)?;
+    }
+    writeln!(file, "SCALARS energy double 1")?;
+    writeln!(file, "LOOKUP_TABLE default")?;
+    for point in tracks.iter().flat_map(|track| &track.points) {
+        writeln!(file, "{}", point.energy)?;
+    }
+    writeln!(file, "SCALARS weight double 1")?;
+    writeln!(file, "LOOKUP_TABLE default")?;
+    for point in tracks.iter().flat_map(|track| &track.points) {
+        writeln!(file, "{}", point.weight)?;
+    }
+
+    file.flush()
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection() {
+        let mut sim_params = SimulationParameters::<f64> {
+            track_particles: vec![42, 7],
+            track_histories: 2,
+            ..Default::default()
+        };
+        // no output file, no recording
+        let tracks = ParticleTracks::new(&sim_params);
+        assert!(!tracks.is_enabled());
+
+        sim_params.track_out = "tracks".to_string();
+        let mut tracks = ParticleTracks::new(&sim_params);
+        tracks.start_cycle(3);
+        let particle = |identifier: u64| MCParticle::<f64> {
+            identifier,
+            ..Default::default()
+        };
+        // selected by identifier
+        let track = tracks.select(&particle(7)).unwrap();
+        assert_eq!((track.identifier, track.cycle), (7, 3));
+        // first histories of the cycle
+        assert!(tracks.select(&particle(1)).is_some());
+        assert!(tracks.select(&particle(2)).is_some());
+        assert!(tracks.select(&particle(3)).is_none());
+        assert!(tracks.select(&particle(42)).is_some());
+        tracks.start_cycle(4);
+        assert!(tracks.select(&particle(3)).is_some());
+    }
+}
@@ -0,0 +1,80 @@
+//! Weight windows used for variance reduction
+//!
+//! This module contains the structure holding the weight windows of the
+//! problem, indexed by cell and energy group. See
+//! [`MCParticle::weight_window()`][crate::particles::mc_particle::MCParticle::weight_window]
+//! for the way they are applied.
+
+use num::zero;
+
+use crate::{constants::CustomFloat, parameters::WeightWindowParameters};
+
+/// Structure used to store the bounds of the weight windows of the problem.
+#[derive(Debug, Default)]
+pub struct WeightWindows<T: CustomFloat> {
+    /// Number of energy groups of the problem.
+    pub n_groups: usize,
+    /// Flattened list of bounds, relative to the source particle weight,
+    /// indexed using the global cell index & energy group.
+    pub bounds: Vec<(T, T)>,
+}
+
+impl<T: CustomFloat> WeightWindows<T> {
+    /// Constructor. Windows are applied in the order of the list, the last
+    /// matching window defining the bounds of a cell & group. Cells and groups
+    /// without windows have unbounded weights.
+    pub fn new(windows: &[WeightWindowParameters<T>], n_cells: usize, n_groups: usize) -> Self {
+        let mut bounds = vec![(zero(), T::huge_float()); n_cells * n_groups];
+        windows.iter().for_each(|ww| {
+            let cells = ww.cell.map_or(0..n_cells, |cell| cell..cell + 1);
+            cells.for_each(|cell| {
+                let groups = ww.group.map_or(0..n_groups, |group| group..group + 1);
+                groups.for_each(|group| bounds[cell * n_groups + group] = (ww.lower, ww.upper));
+            });
+        });
+        Self { n_groups, bounds }
+    }
+
+    /// Returns the bounds of the window in the given cell and energy group.
+    pub fn get(&self, cell_gid: usize, group: usize) -> (T, T) {
+        self.bounds[cell_gid * self.n_groups + group]
+    }
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_priority() {
+        let windows: Vec<WeightWindowParameters<f64>> = vec![
+            WeightWindowParameters {
+                cell: None,
+                group: None,
+                lower: 0.5,
+                upper: 2.0,
+            },
+            WeightWindowParameters {
+                cell: Some(3),
+                group: None,
+                lower: 0.1,
+                upper: 0.4,
+            },
+            WeightWindowParameters {
+                cell: Some(3),
+                group: Some(1),
+                lower: 0.01,
+                upper: 0.05,
+            },
+        ];
+        let ww = WeightWindows::new(&windows, 4, 2);
+        assert_eq!(ww.get(0, 0), (0.5, 2.0));
+        assert_eq!(ww.get(2, 1), (0.5, 2.0));
+        assert_eq!(ww.get(3, 0), (0.1, 0.4));
+        assert_eq!(ww.get(3, 1), (0.01, 0.05));
+    }
+}
@@ -0,0 +1,208 @@
+//! Constructive solid geometry
+//!
+//! This module contains the code used to combine geometries into regions
+//! using boolean operators. Expressions refer to geometries by name and
+//! use the following operators, by decreasing order of precedence:
+//! - `~a`: complement of `a`
+//! - `a & b`: intersection of `a` and `b`
+//! - `a | b`: union of `a` and `b`, and `a - b`: difference of `a` and `b`,
+//!   i.e. `a & ~b`; both have the same precedence
+//!
+//! Parentheses can be used to group terms. Operators of equal precedence are
+//! evaluated from left to right, e.g. `a | b - c` is `(a | b) - c` while
+//! `a - b | c` is `(a - b) | c`.
+
+/// Enum used to represent a boolean expression over named geometries.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsgExpression {
+    /// Reference to a named geometry.
+    Geometry(String),
+    /// Space outside of the operand.
+    Complement(Box<CsgExpression>),
+    /// Space inside both operands.
+    Intersection(Box<CsgExpression>, Box<CsgExpression>),
+    /// Space inside either operand.
+    Union(Box<CsgExpression>, Box<CsgExpression>),
+}
+
+impl CsgExpression {
+    /// Parses an expression. Returns `None` if the expression is empty or
+    /// ill-formed.
+    pub fn parse(expression: &str) -> Option<Self> {
+        let tokens = tokenize(expression)?;
+        let mut pos: usize = 0;
+        let res = parse_union(&tokens, &mut pos)?;
+        // all tokens should be consumed
+        if pos != tokens.len() {
+            return None;
+        }
+        Some(res)
+    }
+
+    /// Evaluates the expression, using the passed closure to determine
+    /// whether a point lies inside a given named geometry.
+    pub fn evaluate<F: FnMut(&str) -> bool>(&self, inside: &mut F) -> bool {
+        match self {
+            CsgExpression::Geometry(name) => inside(name),
+            CsgExpression::Complement(expr) => !expr.evaluate(inside),
+            CsgExpression::Intersection(lhs, rhs) => lhs.evaluate(inside) && rhs.evaluate(inside),
+            CsgExpression::Union(lhs, rhs) => lhs.evaluate(inside) || rhs.evaluate(inside),
+        }
+    }
+
+    /// Returns the names of the geometries referenced by the expression.
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            CsgExpression::Geometry(name) => vec![name.as_str()],
+            CsgExpression::Complement(expr) => expr.names(),
+            CsgExpression::Intersection(lhs, rhs) | CsgExpression::Union(lhs, rhs) => {
+                let mut names = lhs.names();
+                names.extend(rhs.names());
+                names
+            }
+        }
+    }
+}
+
+//==================
+// Private functions
+//==================
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Complement,
+    Intersection,
+    Union,
+    Difference,
+    Open,
+    Close,
+}
+
+fn tokenize(expression: &str) -> Option<Vec<Token>> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '~' | '!' => Token::Complement,
+            '&' => Token::Intersection,
+            '|' => Token::Union,
+            '-' => Token::Difference,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c if c.is_whitespace() => continue,
+            c if c.is_alphanumeric() | (c == '_') => {
+                let mut name = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() | (c == '_')) {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                Token::Name(name)
+            }
+            _ => return None,
+        };
+        tokens.push(token);
+    }
+    Some(tokens)
+}
+
+// unions & differences share the lowest precedence level
+fn parse_union(tokens: &[Token], pos: &mut usize) -> Option<CsgExpression> {
+    let mut lhs = parse_intersection(tokens, pos)?;
+    while let Some(token @ (Token::Union | Token::Difference)) = tokens.get(*pos) {
+        *pos += 1;
+        let rhs = parse_intersection(tokens, pos)?;
+        lhs = match token {
+            Token::Union => CsgExpression::Union(Box::new(lhs), Box::new(rhs)),
+            _ => CsgExpression::Intersection(
+                Box::new(lhs),
+                Box::new(CsgExpression::Complement(Box::new(rhs))),
+            ),
+        };
+    }
+    Some(lhs)
+}
+
+fn parse_intersection(tokens: &[Token], pos: &mut usize) -> Option<CsgExpression> {
+    let mut lhs = parse_unary(tokens, pos)?;
+    while let Some(Token::Intersection) = tokens.get(*pos) {
+        *pos += 1;
+        let rhs = parse_unary(tokens, pos)?;
+        lhs = CsgExpression::Intersection(Box::new(lhs), Box::new(rhs));
+    }
+    Some(lhs)
+}
+
+fn parse_unary(tokens: &[Token], pos: &mut usize) -> Option<CsgExpression> {
+    let token = tokens.get(*pos)?;
+    *pos += 1;
+    match token {
+        Token::Name(name) => Some(CsgExpression::Geometry(name.to_owned())),
+        Token::Complement => Some(CsgExpression::Complement(Box::new(parse_unary(
+            tokens, pos,
+        )?))),
+        Token::Open => {
+            let expr = parse_union(tokens, pos)?;
+            match tokens.get(*pos) {
+                Some(Token::Close) => {
+                    *pos += 1;
+                    Some(expr)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_evaluate() {
+        let expr = CsgExpression::parse("outer - inner | ~(a & b)").unwrap();
+        assert_eq!(expr.names(), vec!["outer", "inner", "a", "b"]);
+
+        // (outer & ~inner) | ~(a & b)
+        let eval = |inside: &[&str]| expr.evaluate(&mut |name: &str| inside.contains(&name));
+        assert!(eval(&["outer", "a", "b"]));
+        assert!(!eval(&["outer", "inner", "a", "b"]));
+        assert!(eval(&["outer", "inner", "a"]));
+        assert!(!eval(&["a", "b"]));
+
+        // unions & differences are evaluated from left to right
+        let expr = CsgExpression::parse("a | b - c").unwrap();
+        let eval = |inside: &[&str]| expr.evaluate(&mut |name: &str| inside.contains(&name));
+        assert!(eval(&["a"]));
+        assert!(!eval(&["a", "c"]));
+        assert!(!eval(&["b", "c"]));
+        let expr = CsgExpression::parse("a - b | c").unwrap();
+        let eval = |inside: &[&str]| expr.evaluate(&mut |name: &str| inside.contains(&name));
+        assert!(eval(&["a", "b", "c"]));
+        assert!(eval(&["c"]));
+        assert!(!eval(&["a", "b"]));
+        assert_eq!(
+            CsgExpression::parse("a - b | c"),
+            CsgExpression::parse("(a - b) | c")
+        );
+        assert_ne!(
+            CsgExpression::parse("a - b | c"),
+            CsgExpression::parse("a - (b | c)")
+        );
+
+        // ill-formed expressions
+        assert!(CsgExpression::parse("").is_none());
+        assert!(CsgExpression::parse("a &").is_none());
+        assert!(CsgExpression::parse("(a | b").is_none());
+        assert!(CsgExpression::parse("a b").is_none());
+        assert!(CsgExpression::parse("a + b").is_none());
+    }
+}
@@ -94,7 +94,7 @@ impl<T: CustomFloat> Default for MCNearestFacet<T> {
 /// undergo when reaching a given facet.
 ///
 /// This value essentially depends on the nature of what is on the other side.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub enum MCSubfacetAdjacencyEvent {
     /// Default value. This will generate error at runtime if not initialized
     /// correctly.
@@ -106,6 +106,14 @@ pub enum MCSubfacetAdjacencyEvent {
     /// Value corresponding to a reflection event. The facet is located at the
     /// edge of the problem and the behavior of the facet is set to reflect.
     BoundaryReflection,
+    /// Value corresponding to a diffuse reflection event. The facet is located
+    /// at the edge of the problem and re-emits particles following a cosine
+    /// distribution.
+    BoundaryWhite,
+    /// Value corresponding to a periodic crossing event. The facet is located
+    /// at the edge of the problem and particles are translated to the opposite
+    /// face of the problem.
+    BoundaryPeriodic,
     /// Value corresponding to an intra-problem crossing event. The neighboring
     /// facet belongs to a cell managed by the same processor.
     TransitOnProcessor,
@@ -5,6 +5,7 @@ use super::{CORNER_OFFSET, FACE_OFFSET, N_FACES, N_POINTS_INTERSEC};
 use crate::{
     constants::{CustomFloat, Tuple3, Tuple4},
     data::mc_vector::MCVector,
+    parameters::SimulationParameters,
 };
 
 /// Structure representing the spatial grid of the problem.
@@ -24,43 +25,104 @@ pub struct GlobalFccGrid<T: CustomFloat> {
     /// Size of the problem along the z-axis (cm).
     pub lz: T,
 
-    /// Size of a mesh cell along the x-axis (cm).
-    pub dx: T,
-    /// Size of a mesh cell along the y-axis (cm).
-    pub dy: T,
-    /// Size of a mesh cell along the z-axis (cm).
-    pub dz: T,
+    /// Positions of the mesh planes along the x-axis (cm), from `0` to `lx`.
+    pub x_planes: Vec<T>,
+    /// Positions of the mesh planes along the y-axis (cm), from `0` to `ly`.
+    pub y_planes: Vec<T>,
+    /// Positions of the mesh planes along the z-axis (cm), from `0` to `lz`.
+    pub z_planes: Vec<T>,
+
+    /// Sizes of the mesh cells along the x-axis (cm).
+    pub dx: Vec<T>,
+    /// Sizes of the mesh cells along the y-axis (cm).
+    pub dy: Vec<T>,
+    /// Sizes of the mesh cells along the z-axis (cm).
+    pub dz: Vec<T>,
 }
 
 impl<T: CustomFloat> GlobalFccGrid<T> {
-    /// Constructor.
+    /// Constructor. Builds a uniform grid.
     pub fn new(nx: usize, ny: usize, nz: usize, lx: T, ly: T, lz: T) -> Self {
-        let tmpx: T = FromPrimitive::from_usize(nx).unwrap();
-        let tmpy: T = FromPrimitive::from_usize(ny).unwrap();
-        let tmpz: T = FromPrimitive::from_usize(nz).unwrap();
+        let uniform = |n: usize, l: T| -> Vec<T> {
+            let tmp: T = FromPrimitive::from_usize(n).unwrap();
+            let d = l / tmp;
+            (0..=n)
+                .map(|ii| <T as FromPrimitive>::from_usize(ii).unwrap() * d)
+                .collect()
+        };
+        let mut grid = Self::rectilinear(uniform(nx, lx), uniform(ny, ly), uniform(nz, lz));
+        // keep exact sizes for uniform grids
+        grid.dx = vec![lx / FromPrimitive::from_usize(nx).unwrap(); nx];
+        grid.dy = vec![ly / FromPrimitive::from_usize(ny).unwrap(); ny];
+        grid.dz = vec![lz / FromPrimitive::from_usize(nz).unwrap(); nz];
+        grid
+    }
 
+    /// Constructor. Builds a rectilinear grid from the positions of its planes
+    /// along each axis. Plane lists should start at `0` and be strictly increasing.
+    pub fn rectilinear(x_planes: Vec<T>, y_planes: Vec<T>, z_planes: Vec<T>) -> Self {
+        let sizes = |planes: &[T]| -> Vec<T> { planes.windows(2).map(|w| w[1] - w[0]).collect() };
         Self {
-            nx,
-            ny,
-            nz,
-            lx,
-            ly,
-            lz,
-            dx: lx / tmpx,
-            dy: ly / tmpy,
-            dz: lz / tmpz,
+            nx: x_planes.len() - 1,
+            ny: y_planes.len() - 1,
+            nz: z_planes.len() - 1,
+            lx: x_planes[x_planes.len() - 1],
+            ly: y_planes[y_planes.len() - 1],
+            lz: z_planes[z_planes.len() - 1],
+            dx: sizes(&x_planes),
+            dy: sizes(&y_planes),
+            dz: sizes(&z_planes),
+            x_planes,
+            y_planes,
+            z_planes,
         }
     }
 
-    /// Returns the index of the cell the coordinates belong to.
+    /// Constructor. Builds the grid described by the simulation parameters,
+    /// using explicit mesh planes along the axes they are specified for.
+    pub fn from_params(sim_params: &SimulationParameters<T>) -> Self {
+        let mut grid = Self::new(
+            sim_params.nx,
+            sim_params.ny,
+            sim_params.nz,
+            sim_params.lx,
+            sim_params.ly,
+            sim_params.lz,
+        );
+        let explicit = |planes: &Vec<T>, uniform: &Vec<T>| {
+            if planes.is_empty() {
+                uniform.clone()
+            } else {
+                planes.clone()
+            }
+        };
+        if !(sim_params.x_planes.is_empty()
+            & sim_params.y_planes.is_empty()
+            & sim_params.z_planes.is_empty())
+        {
+            grid = Self::rectilinear(
+                explicit(&sim_params.x_planes, &grid.x_planes),
+                explicit(&sim_params.y_planes, &grid.y_planes),
+                explicit(&sim_params.z_planes, &grid.z_planes),
+            );
+        }
+        grid
+    }
+
+    /// Returns the index of the cell the coordinates belong to. Coordinates
+    /// on the upper boundaries of the problem are assigned to the last cell.
     pub fn which_cell(&self, r: &MCVector<T>) -> usize {
-        let ix = r.x / self.dx;
-        let iy = r.y / self.dy;
-        let iz = r.z / self.dz;
+        // index of the last plane lower or equal to the coordinate
+        let locate = |planes: &[T], x: T| -> usize {
+            planes
+                .partition_point(|plane| *plane <= x)
+                .saturating_sub(1)
+                .min(planes.len() - 2)
+        };
         self.cell_tuple_to_idx(&(
-            ix.to_usize().unwrap(),
-            iy.to_usize().unwrap(),
-            iz.to_usize().unwrap(),
+            locate(&self.x_planes, r.x),
+            locate(&self.y_planes, r.y),
+            locate(&self.z_planes, r.z),
         ))
     }
 
@@ -70,9 +132,9 @@ impl<T: CustomFloat> GlobalFccGrid<T> {
         let tt: Tuple3 = self.cell_idx_to_tuple(idx_cell);
         let r: MCVector<T> = self.node_coord_from_tuple(&(tt.0, tt.1, tt.2, 0));
         r + MCVector {
-            x: self.dx / two,
-            y: self.dy / two,
-            z: self.dz / two,
+            x: self.dx[tt.0] / two,
+            y: self.dy[tt.1] / two,
+            z: self.dz[tt.2] / two,
         }
     }
 
@@ -148,6 +210,20 @@ impl<T: CustomFloat> GlobalFccGrid<T> {
         nbr_cell_gid
     }
 
+    /// Returns the cell located on the opposite side of the problem when
+    /// crossing the specified face of a boundary cell.
+    pub fn get_periodic_nbr_gid(&self, cell_gid: usize, face: usize) -> usize {
+        let mut cell_tt = self.cell_idx_to_tuple(cell_gid);
+        // even faces have a positive offset, see FACE_OFFSET
+        let upper = face.is_multiple_of(2);
+        match face / 2 {
+            0 => cell_tt.0 = if upper { 0 } else { self.nx - 1 },
+            1 => cell_tt.1 = if upper { 0 } else { self.ny - 1 },
+            _ => cell_tt.2 = if upper { 0 } else { self.nz - 1 },
+        }
+        self.cell_tuple_to_idx(&cell_tt)
+    }
+
     /// Returns a node's coordinate from its index.
     pub fn node_coord_from_idx(&self, idx: usize) -> MCVector<T> {
         self.node_coord_from_tuple(&self.node_idx_to_tuple(idx))
@@ -156,32 +232,34 @@ impl<T: CustomFloat> GlobalFccGrid<T> {
     /// Returns a node's coordinate from its tuple.
     pub fn node_coord_from_tuple(&self, tt: &Tuple4) -> MCVector<T> {
         let two: T = FromPrimitive::from_f64(2.0).unwrap();
+        // half-sizes of the cell the node is the corner of; face centers
+        // are never located on the upper boundaries of the problem
+        let hx = self.dx[tt.0.min(self.nx - 1)] / two;
+        let hy = self.dy[tt.1.min(self.ny - 1)] / two;
+        let hz = self.dz[tt.2.min(self.nz - 1)] / two;
         let basis_offset: [MCVector<T>; 4] = [
             MCVector::default(),
             MCVector {
                 x: zero(),
-                y: self.dy / two,
-                z: self.dz / two,
+                y: hy,
+                z: hz,
             },
             MCVector {
-                x: self.dx / two,
+                x: hx,
                 y: zero(),
-                z: self.dz / two,
+                z: hz,
             },
             MCVector {
-                x: self.dx / two,
-                y: self.dy / two,
+                x: hx,
+                y: hy,
                 z: zero(),
             },
         ];
-        let tx: T = FromPrimitive::from_usize(tt.0).unwrap();
-        let ty: T = FromPrimitive::from_usize(tt.1).unwrap();
-        let tz: T = FromPrimitive::from_usize(tt.2).unwrap();
 
         MCVector {
-            x: tx * self.dx,
-            y: ty * self.dy,
-            z: tz * self.dz,
+            x: self.x_planes[tt.0],
+            y: self.y_planes[tt.1],
+            z: self.z_planes[tt.2],
         } + basis_offset[tt.3]
     }
 
@@ -203,6 +281,7 @@ impl<T: CustomFloat> GlobalFccGrid<T> {
 #[cfg(test)]
 mod tests {
     use super::GlobalFccGrid;
+    use crate::data::mc_vector::MCVector;
 
     #[test]
     fn snap_turtle() {
@@ -212,4 +291,48 @@ mod tests {
         assert_eq!(grid.snap_turtle(t0), (0, 2, 1));
         assert_eq!(grid.snap_turtle(t1), (2, 0, 0));
     }
+
+    #[test]
+    fn rectilinear() {
+        let grid = GlobalFccGrid::rectilinear(
+            vec![0.0, 1.0, 3.0, 7.0],
+            vec![0.0, 2.0],
+            vec![0.0, 0.5, 1.0],
+        );
+        assert_eq!((grid.nx, grid.ny, grid.nz), (3, 1, 2));
+        assert_eq!((grid.lx, grid.ly, grid.lz), (7.0, 2.0, 1.0));
+        assert_eq!(grid.dx, vec![1.0, 2.0, 4.0]);
+
+        let rr = MCVector {
+            x: 5.0,
+            y: 1.0,
+            z: 0.75,
+        };
+        let cell = grid.which_cell(&rr);
+        assert_eq!(grid.cell_idx_to_tuple(cell), (2, 0, 1));
+        let center = grid.cell_center(cell);
+        assert_eq!((center.x, center.y, center.z), (5.0, 1.0, 0.75));
+        // upper boundaries belong to the last cell
+        let rr = MCVector {
+            x: 7.0,
+            y: 2.0,
+            z: 1.0,
+        };
+        assert_eq!(grid.cell_idx_to_tuple(grid.which_cell(&rr)), (2, 0, 1));
+    }
+
+    #[test]
+    fn periodic_nbr() {
+        let grid = GlobalFccGrid::new(3, 3, 3, 9.0, 9.0, 9.0);
+        let cell = grid.cell_tuple_to_idx(&(2, 0, 1));
+        // +x face wraps to the first cell, -y face to the last one
+        assert_eq!(
+            grid.get_periodic_nbr_gid(cell, 0),
+            grid.cell_tuple_to_idx(&(0, 0, 1))
+        );
+        assert_eq!(
+            grid.get_periodic_nbr_gid(cell, 3),
+            grid.cell_tuple_to_idx(&(2, 2, 1))
+        );
+    }
 }
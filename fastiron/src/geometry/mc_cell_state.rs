@@ -13,8 +13,12 @@ pub struct MCCellState<T: CustomFloat> {
     pub material: usize,
     /// Cell volume in cm³.
     pub volume: T,
-    /// Density of the material in the cell?
+    /// Number density of the material in the cell, relative to its nominal
+    /// value. It is set using the density of the enclosing geometry.
     pub cell_number_density: T,
+    /// Importance of the cell, used for geometry splitting and russian roulette.
+    /// It is set using the importance of the enclosing geometry.
+    pub importance: T,
     /// Cell identifier.
     pub id: usize,
     /// Local tally counting particles spawned in this cell.
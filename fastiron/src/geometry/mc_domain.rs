@@ -2,12 +2,15 @@
 //!
 //!
 
-use num::{one, FromPrimitive};
+use num::{zero, FromPrimitive};
 use rustc_hash::FxHashMap;
 
 use crate::{
     constants::CustomFloat,
-    data::{material_database::MaterialDatabase, mc_vector::MCVector},
+    data::{
+        material_database::{MaterialDatabase, MixtureComponent},
+        mc_vector::MCVector,
+    },
     parameters::{GeometryParameters, Parameters, Shape},
     simulation::mct::cell_position_3dg,
     utils::decomposition_object::DecompositionObject,
@@ -154,6 +157,8 @@ pub struct MCDomain<T: CustomFloat> {
     pub cell_state: Vec<MCCellState<T>>,
     /// Mesh of the domain.
     pub mesh: MCMeshDomain<T>,
+    /// Map from the global index of a cell in the grid to its local index.
+    pub cell_idx_map: FxHashMap<usize, usize>,
 }
 
 impl<T: CustomFloat> MCDomain<T> {
@@ -169,36 +174,80 @@ impl<T: CustomFloat> MCDomain<T> {
         let cell_state: Vec<MCCellState<T>> = (0..mesh.cell_geometry.len())
             .map(|cell_idx| {
                 let rr = cell_position_3dg(&mesh, cell_idx);
-                let mat_name = Self::find_material(&params.geometry_params, &rr);
+                let geometry = Self::find_geometry(&params.geometry_params, &rr).unwrap();
                 let cell_center: MCVector<T> = Self::cell_center(&mesh, cell_idx);
+                let cell_gid = grid.which_cell(&cell_center);
+                let volume = Self::cell_volume(&mesh, cell_idx);
+                // cells are homogenized beforehand when using volume sampling
+                let (material, cell_number_density) = if mat_db.cell_materials.is_empty() {
+                    (
+                        mat_db.find_material(&geometry.material_name).unwrap(),
+                        geometry.density_at(&rr),
+                    )
+                } else {
+                    mat_db.cell_materials[cell_gid]
+                };
                 MCCellState {
-                    material: mat_db.find_material(&mat_name).unwrap(),
-                    volume: Self::cell_volume(&mesh, cell_idx),
-                    cell_number_density: one(),
-                    id: grid.which_cell(&cell_center) * 0x0100000000,
+                    material,
+                    volume,
+                    cell_number_density,
+                    importance: geometry.importance,
+                    id: cell_gid * 0x0100000000,
                     source_tally: 0,
                 }
             })
             .collect();
+        let cell_idx_map: FxHashMap<usize, usize> = cell_state
+            .iter()
+            .enumerate()
+            .map(|(cell_idx, cell)| (cell.id / 0x0100000000, cell_idx))
+            .collect();
 
         MCDomain {
             global_domain: mesh.domain_gid,
             cell_state,
             mesh,
+            cell_idx_map,
         }
     }
 
-    fn find_material(geometry_params: &[GeometryParameters<T>], rr: &MCVector<T>) -> String {
-        let mut mat_name = String::default();
+    /// Returns the local index of the cell the coordinates belong to, if
+    /// it is part of the domain.
+    pub fn which_cell(&self, grid: &GlobalFccGrid<T>, rr: &MCVector<T>) -> Option<usize> {
+        self.cell_idx_map.get(&grid.which_cell(rr)).copied()
+    }
 
-        geometry_params.iter().rev().for_each(|geom| {
-            if Self::is_inside(geom, rr) {
-                // cant return directly because of the behavior of original function
-                mat_name.clone_from(&geom.material_name);
-            }
-        });
+    /// Returns the geometry the coordinates belong to. If multiple geometries
+    /// overlap, the first one of the list takes priority, which is equivalent to
+    /// the reverse iteration of the original function.
+    fn find_geometry<'a>(
+        geometry_params: &'a [GeometryParameters<T>],
+        rr: &MCVector<T>,
+    ) -> Option<&'a GeometryParameters<T>> {
+        geometry_params
+            .iter()
+            .filter(|geom| !geom.material_name.is_empty())
+            .find(|geom| Self::contains(geometry_params, geom, rr))
+    }
 
-        mat_name
+    /// Returns true if the coordinates lie inside the geometry, taking into
+    /// account its transformation and, for regions, the geometries it is
+    /// built from.
+    fn contains(
+        geometry_params: &[GeometryParameters<T>],
+        geom: &GeometryParameters<T>,
+        rr: &MCVector<T>,
+    ) -> bool {
+        let local = geom.to_local(rr);
+        match &geom.region {
+            Some(region) => region.evaluate(&mut |name| {
+                geometry_params
+                    .iter()
+                    .find(|other| other.name == name)
+                    .is_some_and(|other| Self::contains(geometry_params, other, &local))
+            }),
+            None => Self::is_inside(geom, &local),
+        }
     }
 
     fn is_inside(geom: &GeometryParameters<T>, rr: &MCVector<T>) -> bool {
@@ -217,8 +266,99 @@ impl<T: CustomFloat> MCDomain<T> {
                 };
                 (*rr - center).length() <= geom.radius
             }
-            Shape::Undefined => unreachable!(),
+            Shape::Cylinder => {
+                let two: T = FromPrimitive::from_f64(2.0).unwrap();
+                let axis = geom.axis();
+                let axis = axis * (T::one() / axis.length());
+                let dd = *rr - geom.center();
+                // coordinate along the axis & distance to the axis
+                let tt = dd.dot(&axis);
+                let radial = (dd - axis * tt).length();
+                (tt.abs() <= geom.height / two) & (radial <= geom.radius)
+            }
+            Shape::Cone => {
+                let axis = geom.axis();
+                let axis = axis * (T::one() / axis.length());
+                let dd = *rr - geom.center();
+                let tt = dd.dot(&axis);
+                let radial = (dd - axis * tt).length();
+                (tt >= T::zero()) & (tt <= geom.height) & (radial <= geom.radius * tt / geom.height)
+            }
+            Shape::Plane => (*rr - geom.center()).dot(&geom.axis()) <= T::zero(),
+            Shape::Ellipsoid => {
+                let dd = *rr - geom.center();
+                let (xx, yy, zz) = (
+                    dd.x / geom.x_radius,
+                    dd.y / geom.y_radius,
+                    dd.z / geom.z_radius,
+                );
+                xx * xx + yy * yy + zz * zz <= T::one()
+            }
+            // regions are evaluated by the caller
+            Shape::Region | Shape::Undefined => unreachable!(),
+        }
+    }
+
+    /// Estimates the material volume fractions of the specified cell of the
+    /// grid using a regular sub-grid of sample points. Returns the materials
+    /// found in the cell, in order of appearance, with their volume fraction
+    /// and their density averaged over the whole cell. The sampled volume of
+    /// each geometry is added to `geometry_volume`, regardless of overlaps.
+    pub fn sample_cell(
+        grid: &GlobalFccGrid<T>,
+        cell_gid: usize,
+        params: &Parameters<T>,
+        mat_db: &MaterialDatabase<T>,
+        geometry_volume: &mut [T],
+    ) -> Vec<MixtureComponent<T>> {
+        let n_samples = params.simulation_params.volume_samples;
+        let (ix, iy, iz) = grid.cell_idx_to_tuple(cell_gid);
+        let nn: T = FromPrimitive::from_usize(n_samples).unwrap();
+        let n_total: T = nn * nn * nn;
+        let sample_volume = grid.dx[ix] * grid.dy[iy] * grid.dz[iz] / n_total;
+        // samples are taken at the center of each sub-cell
+        let offset =
+            |ii: usize| -> T { (T::from_usize(ii).unwrap() + T::from_f64(0.5).unwrap()) / nn };
+
+        let mut components: Vec<MixtureComponent<T>> = Vec::new();
+        for kk in 0..n_samples {
+            for jj in 0..n_samples {
+                for ii in 0..n_samples {
+                    let rr = MCVector {
+                        x: grid.x_planes[ix] + grid.dx[ix] * offset(ii),
+                        y: grid.y_planes[iy] + grid.dy[iy] * offset(jj),
+                        z: grid.z_planes[iz] + grid.dz[iz] * offset(kk),
+                    };
+                    params
+                        .geometry_params
+                        .iter()
+                        .zip(geometry_volume.iter_mut())
+                        .filter(|(geom, _)| Self::contains(&params.geometry_params, geom, &rr))
+                        .for_each(|(_, geom_volume)| *geom_volume += sample_volume);
+                    if let Some(geom) = Self::find_geometry(&params.geometry_params, &rr) {
+                        let mat_idx = mat_db.find_material(&geom.material_name).unwrap();
+                        let cpnt = match components.iter_mut().find(|c| c.material == mat_idx) {
+                            Some(cpnt) => cpnt,
+                            None => {
+                                components.push(MixtureComponent {
+                                    material: mat_idx,
+                                    volume_fraction: zero(),
+                                    number_density: zero(),
+                                });
+                                components.last_mut().unwrap()
+                            }
+                        };
+                        cpnt.volume_fraction += T::one();
+                        cpnt.number_density += geom.density_at(&rr);
+                    }
+                }
+            }
         }
+        components.iter_mut().for_each(|cpnt| {
+            cpnt.volume_fraction /= n_total;
+            cpnt.number_density /= n_total;
+        });
+        components
     }
 
     /// Returns the coordinates of the center of
@@ -333,6 +473,20 @@ fn build_cells<T: CustomFloat>(
                 face_info[ii].nbr_idx = nbr_domain_idx[&face_cell_info.domain_gid.unwrap()];
                 if face_nbr[ii] == *cell_gid {
                     face_info[ii].event = boundary_cond[ii];
+                    if boundary_cond[ii] == MCSubfacetAdjacencyEvent::BoundaryPeriodic {
+                        // neighbor is the cell on the opposite side of the problem
+                        let periodic_gid = grid.get_periodic_nbr_gid(*cell_gid, ii);
+                        match partition.cell_info_map.get(&periodic_gid) {
+                            Some(nbr_cell_info) if nbr_cell_info.foreman == cell_info.foreman => {
+                                face_info[ii].cell_info = *nbr_cell_info;
+                                face_info[ii].nbr_idx =
+                                    nbr_domain_idx[&nbr_cell_info.domain_gid.unwrap()];
+                            }
+                            _ => {
+                                face_info[ii].event = MCSubfacetAdjacencyEvent::TransitOffProcessor
+                            }
+                        }
+                    }
                 } else if face_cell_info.foreman == cell_info.foreman {
                     face_info[ii].event = MCSubfacetAdjacencyEvent::TransitOnProcessor;
                 } else {
@@ -385,7 +539,9 @@ fn make_facet(
     facet.subfacet.neighbor_foreman = face_info[face_id].cell_info.foreman;
 
     match facet.subfacet.event {
-        MCSubfacetAdjacencyEvent::BoundaryReflection | MCSubfacetAdjacencyEvent::BoundaryEscape => {
+        MCSubfacetAdjacencyEvent::BoundaryReflection
+        | MCSubfacetAdjacencyEvent::BoundaryWhite
+        | MCSubfacetAdjacencyEvent::BoundaryEscape => {
             facet.subfacet.adjacent.facet = facet.subfacet.current.facet;
         }
         _ => (),
@@ -393,22 +549,24 @@ fn make_facet(
 }
 
 /// Match the boundary conditions of Parameters to its Enum representation.
+/// The returned array is ordered like the faces of the grid, i.e. according
+/// to [FACE_OFFSET][super::FACE_OFFSET].
 fn get_boundary_conditions<T: CustomFloat>(
     params: &Parameters<T>,
 ) -> [MCSubfacetAdjacencyEvent; 6] {
-    match params.simulation_params.boundary_condition.as_ref() {
-        "reflect" => [MCSubfacetAdjacencyEvent::BoundaryReflection; 6],
-        "escape" => [MCSubfacetAdjacencyEvent::BoundaryEscape; 6],
-        "octant" => [
-            MCSubfacetAdjacencyEvent::BoundaryEscape,
-            MCSubfacetAdjacencyEvent::BoundaryReflection,
-            MCSubfacetAdjacencyEvent::BoundaryEscape,
-            MCSubfacetAdjacencyEvent::BoundaryReflection,
-            MCSubfacetAdjacencyEvent::BoundaryEscape,
-            MCSubfacetAdjacencyEvent::BoundaryReflection,
-        ],
-        _ => unreachable!(),
-    }
+    let conditions = params.simulation_params.boundary_conditions();
+    // face offsets go (+x, -x, +y, -y, +z, -z) while conditions go (xMin, xMax, ...)
+    let mut events = [MCSubfacetAdjacencyEvent::AdjacencyUndefined; 6];
+    (0..6).for_each(|ii| {
+        events[ii ^ 1] = match conditions[ii] {
+            "reflect" => MCSubfacetAdjacencyEvent::BoundaryReflection,
+            "escape" => MCSubfacetAdjacencyEvent::BoundaryEscape,
+            "white" => MCSubfacetAdjacencyEvent::BoundaryWhite,
+            "periodic" => MCSubfacetAdjacencyEvent::BoundaryPeriodic,
+            _ => unreachable!(),
+        }
+    });
+    events
 }
 
 //=============
@@ -418,6 +576,7 @@ fn get_boundary_conditions<T: CustomFloat>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{geometry::csg::CsgExpression, parameters::DensityProfile};
 
     #[test]
     fn inside_material() {
@@ -510,10 +669,181 @@ mod tests {
 
         // find_material
         let geoms = vec![geom_a, geom_b, geom_c, geom_d];
-        assert_eq!(MCDomain::find_material(&geoms, &r1), String::from("mat_b"));
-        assert_eq!(MCDomain::find_material(&geoms, &r2), String::from("mat_d"));
-        assert_eq!(MCDomain::find_material(&geoms, &r3), String::from("mat_a"));
-        assert_eq!(MCDomain::find_material(&geoms, &r5), String::from("mat_a")); // first of the list takes priority
-        assert_eq!(MCDomain::find_material(&geoms, &r6), String::from("mat_c"));
+        assert_eq!(
+            MCDomain::find_geometry(&geoms, &r1).unwrap().material_name,
+            "mat_b"
+        );
+        assert_eq!(
+            MCDomain::find_geometry(&geoms, &r2).unwrap().material_name,
+            "mat_d"
+        );
+        assert_eq!(
+            MCDomain::find_geometry(&geoms, &r3).unwrap().material_name,
+            "mat_a"
+        );
+        assert_eq!(
+            MCDomain::find_geometry(&geoms, &r5).unwrap().material_name,
+            "mat_a"
+        ); // first of the list takes priority
+        assert_eq!(
+            MCDomain::find_geometry(&geoms, &r6).unwrap().material_name,
+            "mat_c"
+        );
+    }
+
+    #[test]
+    fn inside_shapes() {
+        let point = |x: f64, y: f64, z: f64| MCVector { x, y, z };
+        // tilted cylinder of radius 1.0 & height 4.0 centered on (5.0, 5.0, 5.0)
+        let cylinder = GeometryParameters {
+            shape: Shape::Cylinder,
+            radius: 1.0,
+            height: 4.0,
+            x_center: 5.0,
+            y_center: 5.0,
+            z_center: 5.0,
+            x_axis: 1.0,
+            y_axis: 1.0,
+            z_axis: 0.0,
+            ..Default::default()
+        };
+        assert!(MCDomain::is_inside(&cylinder, &point(6.0, 6.0, 5.0)));
+        assert!(MCDomain::is_inside(&cylinder, &point(5.5, 4.5, 5.0)));
+        assert!(!MCDomain::is_inside(&cylinder, &point(6.0, 4.0, 5.0)));
+        assert!(!MCDomain::is_inside(&cylinder, &point(7.0, 7.0, 5.0)));
+
+        // cone with apex at origin, opening along z
+        let cone = GeometryParameters {
+            shape: Shape::Cone,
+            radius: 2.0,
+            height: 4.0,
+            ..Default::default()
+        };
+        assert!(MCDomain::is_inside(&cone, &point(0.4, 0.0, 1.0)));
+        assert!(!MCDomain::is_inside(&cone, &point(0.6, 0.0, 1.0)));
+        assert!(!MCDomain::is_inside(&cone, &point(0.0, 0.0, -1.0)));
+        assert!(!MCDomain::is_inside(&cone, &point(0.0, 0.0, 4.5)));
+
+        // half-space x <= 3.0
+        let plane = GeometryParameters {
+            shape: Shape::Plane,
+            x_center: 3.0,
+            x_axis: 2.0,
+            z_axis: 0.0,
+            ..Default::default()
+        };
+        assert!(MCDomain::is_inside(&plane, &point(2.0, 10.0, -4.0)));
+        assert!(!MCDomain::is_inside(&plane, &point(3.5, 0.0, 0.0)));
+
+        // ellipsoid of semi-axes (4.0, 1.0, 1.0) centered on the origin
+        let ellipsoid = GeometryParameters {
+            shape: Shape::Ellipsoid,
+            x_radius: 4.0,
+            y_radius: 1.0,
+            z_radius: 1.0,
+            ..Default::default()
+        };
+        assert!(MCDomain::is_inside(&ellipsoid, &point(3.5, 0.0, 0.0)));
+        assert!(!MCDomain::is_inside(&ellipsoid, &point(0.0, 1.5, 0.0)));
+    }
+
+    #[test]
+    fn inside_region() {
+        let point = |x: f64, y: f64, z: f64| MCVector { x, y, z };
+        // spherical shell of radii 1.0 & 2.0, cut by the half-space z <= 0.0
+        let outer = GeometryParameters {
+            name: String::from("outer"),
+            shape: Shape::Sphere,
+            radius: 2.0,
+            ..Default::default()
+        };
+        let inner = GeometryParameters {
+            name: String::from("inner"),
+            radius: 1.0,
+            ..outer.clone()
+        };
+        let lower = GeometryParameters {
+            name: String::from("lower"),
+            shape: Shape::Plane,
+            ..Default::default()
+        };
+        // shell is moved to (5.0, 0.0, 0.0) & flipped upside down
+        let shell = GeometryParameters {
+            material_name: String::from("mat_a"),
+            shape: Shape::Region,
+            region: CsgExpression::parse("outer - inner - lower"),
+            x_translate: 5.0,
+            x_rotate: 180.0,
+            ..Default::default()
+        };
+        let geoms = vec![shell, outer, inner, lower];
+        let contains = |rr: MCVector<f64>| MCDomain::contains(&geoms, &geoms[0], &rr);
+        assert!(contains(point(5.0, 0.0, -1.5)));
+        assert!(!contains(point(5.0, 0.0, 1.5)));
+        assert!(!contains(point(5.0, 0.0, -0.5)));
+        assert!(!contains(point(0.0, 0.0, -1.5)));
+
+        // primitives without material are ignored for material assignment
+        assert_eq!(
+            MCDomain::find_geometry(&geoms, &point(5.0, 0.0, -1.5))
+                .unwrap()
+                .material_name,
+            "mat_a"
+        );
+        assert!(MCDomain::find_geometry(&geoms, &point(5.0, 0.0, 1.5)).is_none());
+
+        // scaled geometry
+        let scaled = GeometryParameters {
+            scale: 2.0,
+            ..geoms[2].clone()
+        };
+        assert!(MCDomain::contains(&geoms, &scaled, &point(1.5, 0.0, 0.0)));
+        assert!(!MCDomain::contains(&geoms, &scaled, &point(2.5, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn geometry_density() {
+        let geom_uniform = GeometryParameters {
+            material_name: String::from("mat_a"),
+            shape: Shape::Brick,
+            density: 0.5,
+            x_max: 4.0,
+            y_max: 4.0,
+            z_max: 4.0,
+            ..Default::default()
+        };
+        let geom_linear = GeometryParameters {
+            density: 1.0,
+            density_profile: DensityProfile::Linear,
+            density_gradient_x: -0.5,
+            ..Default::default()
+        };
+        let geom_exp = GeometryParameters {
+            density: 2.0,
+            density_profile: DensityProfile::Exponential,
+            density_gradient_z: 0.5f64.ln(),
+            ..Default::default()
+        };
+
+        let r1 = MCVector {
+            x: 1.0,
+            y: 3.0,
+            z: 2.0,
+        };
+        let r2 = MCVector {
+            x: 3.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        assert_eq!(geom_uniform.density_at(&r1), 0.5);
+        assert_eq!(geom_linear.density_at(&r1), 0.5);
+        // clamped to zero
+        assert_eq!(geom_linear.density_at(&r2), 0.0);
+        assert!((geom_exp.density_at(&r1) - 0.5).abs() < 1.0e-12);
+
+        let geoms = vec![geom_uniform];
+        let found = MCDomain::find_geometry(&geoms, &r1).unwrap();
+        assert_eq!(found.material_name, "mat_a");
+        assert!(MCDomain::find_geometry(&geoms, &(r1 * 2.0)).is_none());
     }
 }
@@ -11,6 +11,7 @@
 // Internal modules
 //=================
 
+pub mod csg;
 pub mod facets;
 pub mod global_fcc_grid;
 pub mod grid_assignment_object;
@@ -1,28 +1,40 @@
 //! Initialization code for the problem
 
-use std::{fmt::Debug, fs::File, io::Write};
+use std::{
+    fmt::Debug,
+    io::{BufWriter, Result, Write},
+};
 
 use crate::{
-    constants::{CustomFloat, Tuple3},
+    constants::{
+        sim::{N_SOURCE_SHARE_SAMPLES, N_SPECIES},
+        CustomFloat, Tuple3,
+    },
     data::{
         material_database::{Isotope, Material},
         mc_vector::MCVector,
-        nuclear_data::{NuclearData, Polynomial, ReactionType},
+        nuclear_data::{CrossSectionData, FissionData, NuclearData, ReactionType, ScatteringLaw},
+        tracks::ParticleTracks,
     },
     geometry::{
         global_fcc_grid::GlobalFccGrid, mc_domain::MCDomain, mesh_partition::MeshPartition,
     },
     montecarlo::{MonteCarloData, MonteCarloResults, MonteCarloUnit},
-    parameters::Parameters,
-    particles::particle_container::ParticleContainer,
+    parameters::{IsotopeParameters, Parameters},
+    particles::{mc_particle::Species, particle_container::ParticleContainer},
     utils::{
         comm_object::CommObject,
         decomposition_object::DecompositionObject,
+        mc_fast_timer::MCFastTimerContainer,
         mc_processor_info::{ExecPolicy, MCProcessorInfo},
-        mc_rng_state::rng_sample,
+        mc_rng_state::{rng_sample, spawn_rn_seed},
+        output,
+        progress::Progress,
+        trace::Tracer,
     },
 };
 use atomic::Atomic;
+use log::{debug, error, info};
 use num::{one, zero, Float, FromPrimitive};
 use rustc_hash::FxHashMap;
 
@@ -30,7 +42,7 @@ use rustc_hash::FxHashMap;
 pub fn init_mcdata<T: CustomFloat>(params: Parameters<T>) -> MonteCarloData<T> {
     let mut mcdata: MonteCarloData<T> = MonteCarloData::new(params);
 
-    println!("  [MonteCarloData Initialization]: Start");
+    debug!("  [MonteCarloData Initialization]: Start");
     init_nuclear_data(&mut mcdata);
 
     if !mcdata
@@ -39,9 +51,15 @@ pub fn init_mcdata<T: CustomFloat>(params: Parameters<T>) -> MonteCarloData<T> {
         .cross_sections_out
         .is_empty()
     {
-        check_cross_sections(&mcdata);
+        if let Err(e) = check_cross_sections(&mcdata) {
+            error!("Could not write cross-sections: {e}");
+        }
+    }
+    if mcdata.params.simulation_params.volume_samples > 0 {
+        init_cell_materials(&mut mcdata);
+        report_geometry_volumes(&mcdata);
     }
-    println!("  [MonteCarloData Initialization]: Done");
+    debug!("  [MonteCarloData Initialization]: Done");
 
     mcdata
 }
@@ -53,25 +71,40 @@ pub fn init_particle_containers<T: CustomFloat>(
     params: &Parameters<T>,
     proc_info: &MCProcessorInfo, // may be removed if we add it to parameters
 ) -> Vec<ParticleContainer<T>> {
-    println!("  [ParticleContainer Initialization]: Start");
+    debug!("  [ParticleContainer Initialization]: Start");
     // compute the capacities using number of threads, target number of particles & fission statistical offset
     let target_n_particles = params.simulation_params.n_particles as usize;
 
     let regular_capacity_per_container = target_n_particles / proc_info.n_units; // equivalent of batch size
     let regular_capacity = regular_capacity_per_container + regular_capacity_per_container / 10; // approximate 10% margin
 
-    let max_nu_bar: usize = params
+    // materials without explicit isotopes use their own fission cross-section
+    let fission_cross_sections = params
         .material_params
         .values()
-        .map(|mp| {
-            params.cross_section_params[&mp.fission_cross_section]
+        .filter(|mp| mp.isotopes.is_empty())
+        .map(|mp| &mp.fission_cross_section)
+        .chain(
+            params
+                .isotope_params
+                .values()
+                .map(|ip| &ip.fission_cross_section),
+        );
+    let max_nu_bar: usize = fission_cross_sections
+        .filter_map(|xs_name| params.cross_section_params.get(xs_name))
+        .map(|xs| {
+            FissionData::from(xs)
                 .nu_bar
+                .max_val(
+                    params.simulation_params.e_min,
+                    params.simulation_params.e_max,
+                )
                 .ceil()
                 .to_usize()
                 .unwrap()
         })
         .max()
-        .unwrap();
+        .unwrap_or_default();
 
     let extra_capacity = regular_capacity_per_container * max_nu_bar.max(2);
 
@@ -81,7 +114,7 @@ pub fn init_particle_containers<T: CustomFloat>(
         ExecPolicy::Distributed => todo!(),
         ExecPolicy::Hybrid => todo!(),
     };
-    println!("  [ParticleContainer Initialization]: Done");
+    debug!("  [ParticleContainer Initialization]: Done");
     vec![container; n_container]
 }
 
@@ -90,21 +123,34 @@ pub fn init_particle_containers<T: CustomFloat>(
 /// The correct number is determined according to simulation parameters & execution policy.
 pub fn init_mcunits<T: CustomFloat>(mcdata: &MonteCarloData<T>) -> Vec<MonteCarloUnit<T>> {
     let mut units: Vec<MonteCarloUnit<T>> = (0..mcdata.params.simulation_params.n_units)
-        .map(|_| MonteCarloUnit::default())
+        .map(|_| MonteCarloUnit {
+            source_tallies: vec![0; mcdata.sources.len()],
+            tracks: ParticleTracks::new(&mcdata.params.simulation_params),
+            progress: Progress::new(
+                mcdata.params.simulation_params.progress_interval,
+                mcdata.params.simulation_params.n_steps,
+            ),
+            fast_timer: MCFastTimerContainer {
+                tracer: Tracer::new(!mcdata.params.simulation_params.trace_out.is_empty()),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
         .collect();
 
     // inits
-    println!("  [MonteCarloUnit Initialization]: Start");
+    debug!("  [MonteCarloUnit Initialization]: Start");
     init_mesh(&mut units, mcdata);
     init_tallies(&mut units, &mcdata.params);
     init_xs_cache(&mut units, mcdata.params.simulation_params.n_groups);
-    println!("  [MonteCarloUnit Initialization]: Done");
+    init_source_shares(&mut units, mcdata);
+    debug!("  [MonteCarloUnit Initialization]: Done");
 
     // checks
-    println!("  [Consistency Check]: Start");
+    debug!("  [Consistency Check]: Start");
     // TODO: implement the check correctly according to new init
     consistency_check(&units);
-    println!("  [Consistency Check]: Done");
+    debug!("  [Consistency Check]: Done");
 
     units
 }
@@ -115,6 +161,8 @@ pub fn init_results<T: CustomFloat>(params: &Parameters<T>) -> MonteCarloResults
         params.simulation_params.energy_spectrum.to_owned(),
         params.simulation_params.n_groups,
         params.simulation_params.coral_benchmark,
+        !params.simulation_params.vtk_out.is_empty(),
+        !params.simulation_params.json_out.is_empty(),
     )
 }
 
@@ -124,54 +172,98 @@ pub fn init_results<T: CustomFloat>(params: &Parameters<T>) -> MonteCarloResults
 
 fn init_nuclear_data<T: CustomFloat>(mcdata: &mut MonteCarloData<T>) {
     let params = &mcdata.params;
-    let energy_low: T = params.simulation_params.e_min;
-    let energy_high: T = params.simulation_params.e_max;
-    mcdata.nuclear_data =
-        NuclearData::new(params.simulation_params.n_groups, energy_low, energy_high);
-
-    let mut cross_section: FxHashMap<String, Polynomial<T>> = Default::default();
-    for xs_params in params.cross_section_params.values() {
-        cross_section.insert(
-            xs_params.name.to_owned(),
-            Polynomial {
-                aa: xs_params.aa,
-                bb: xs_params.bb,
-                cc: xs_params.cc,
-                dd: xs_params.dd,
-                ee: xs_params.ee,
-            },
-        );
-    }
+    mcdata.nuclear_data = NuclearData::new(params.simulation_params.energy_boundaries());
 
-    let mut n_isotopes: usize = 0;
-    let n_materials: usize = params.material_params.len();
+    let cross_section: FxHashMap<String, CrossSectionData<T>> = params
+        .cross_section_params
+        .values()
+        .map(|xs_params| (xs_params.name.to_owned(), CrossSectionData::from(xs_params)))
+        .collect();
 
-    for mat_params in params.material_params.values() {
-        n_isotopes += mat_params.n_isotopes;
-    }
+    let n_materials: usize = params.material_params.len();
+    let n_isotopes: usize = params
+        .material_params
+        .values()
+        .map(|mp| {
+            if mp.isotopes.is_empty() {
+                mp.n_isotopes
+            } else {
+                0
+            }
+        })
+        .sum::<usize>()
+        + params.isotope_params.len();
 
     mcdata.nuclear_data.isotopes.reserve(n_isotopes);
     mcdata.material_database.mat.reserve(n_materials);
 
+    // explicit isotopes are shared between the materials referring to them
+    let mut isotope_gids: FxHashMap<String, usize> = Default::default();
+
     for mp in params.material_params.values() {
         let mut material: Material<T> = Material {
             name: mp.name.to_owned(),
             mass: mp.mass,
-            iso: Vec::with_capacity(mp.n_isotopes),
+            source_rate: mp.source_rate,
+            iso: Vec::with_capacity(mp.n_isotopes.max(mp.isotopes.len())),
         };
 
-        (0..mp.n_isotopes).for_each(|_| {
-            let isotope_gid = mcdata.nuclear_data.add_isotope(
-                &cross_section,
-                mp,
-                params.cross_section_params[&mp.fission_cross_section].nu_bar,
-            );
-            // All isotopes are equally prevalent => each weights 1/n_isotopes
-            material.add_isotope(Isotope {
-                gid: isotope_gid,
-                atom_fraction: one::<T>() / FromPrimitive::from_usize(mp.n_isotopes).unwrap(),
-            })
-        });
+        if mp.isotopes.is_empty() {
+            let ip = IsotopeParameters::from(mp);
+            (0..mp.n_isotopes).for_each(|_| {
+                let isotope_gid = mcdata.nuclear_data.add_isotope(
+                    &cross_section,
+                    &ip,
+                    &FissionData::from(&params.cross_section_params[&mp.fission_cross_section]),
+                    &ScatteringLaw::from(
+                        &params.cross_section_params[&mp.scattering_cross_section],
+                    ),
+                );
+                // All isotopes are equally prevalent => each weights 1/n_isotopes
+                material.add_isotope(Isotope {
+                    gid: isotope_gid,
+                    atom_fraction: one::<T>() / FromPrimitive::from_usize(mp.n_isotopes).unwrap(),
+                    mass: mp.mass,
+                })
+            });
+        } else {
+            // missing fractions => all isotopes are equally prevalent
+            let fractions: Vec<T> = if mp.atom_fractions.is_empty() {
+                vec![one(); mp.isotopes.len()]
+            } else {
+                mp.atom_fractions.clone()
+            };
+            let total_fraction: T = fractions.iter().copied().sum();
+
+            for (iso_name, fraction) in mp.isotopes.iter().zip(fractions) {
+                let ip = &params.isotope_params[iso_name];
+                let isotope_gid = *isotope_gids.entry(iso_name.to_owned()).or_insert_with(|| {
+                    let fission = params
+                        .cross_section_params
+                        .get(&ip.fission_cross_section)
+                        .map(FissionData::from)
+                        .unwrap_or_default();
+                    let scattering = params
+                        .cross_section_params
+                        .get(&ip.scattering_cross_section)
+                        .map(ScatteringLaw::from)
+                        .unwrap_or_default();
+                    mcdata
+                        .nuclear_data
+                        .add_isotope(&cross_section, ip, &fission, &scattering)
+                });
+                material.add_isotope(Isotope {
+                    gid: isotope_gid,
+                    atom_fraction: fraction / total_fraction,
+                    mass: ip.mass,
+                })
+            }
+            material.mass = material
+                .iso
+                .iter()
+                .map(|iso| iso.atom_fraction * iso.mass)
+                .sum();
+        }
         mcdata.material_database.add_material(material);
     }
 }
@@ -196,13 +288,9 @@ pub fn consistency_check<T: CustomFloat>(units: &[MonteCarloUnit<T>]) {
                         assert_eq!(current.cell.unwrap(), cell_idx);
                         let adjacent = ff.subfacet.adjacent;
                         // These can hold none as a correct value e.g. if the current cell is on the border of the problem
-                        if adjacent.domain.is_some()
-                            & adjacent.cell.is_some()
-                            & adjacent.facet.is_some()
+                        if let (Some(domain_idx_adj), Some(cell_idx_adj), Some(facet_idx_adj)) =
+                            (adjacent.domain, adjacent.cell, adjacent.facet)
                         {
-                            let domain_idx_adj = adjacent.domain.unwrap();
-                            let cell_idx_adj = adjacent.cell.unwrap();
-                            let facet_idx_adj = adjacent.facet.unwrap();
                             let backside = &units[domain_idx_adj].domain.mesh.cell_connectivity
                                 [cell_idx_adj]
                                 .facet[facet_idx_adj]
@@ -251,14 +339,6 @@ fn init_mesh<T: CustomFloat>(mcunits: &mut [MonteCarloUnit<T>], mcdata: &MonteCa
     let params = &mcdata.params;
     let mat_db = &mcdata.material_database;
 
-    let nx: usize = params.simulation_params.nx;
-    let ny: usize = params.simulation_params.ny;
-    let nz: usize = params.simulation_params.nz;
-
-    let lx: T = params.simulation_params.lx;
-    let ly: T = params.simulation_params.ly;
-    let lz: T = params.simulation_params.lz;
-
     let n_units: usize = params.simulation_params.n_units as usize;
 
     let n_ranks: usize = 1;
@@ -266,7 +346,7 @@ fn init_mesh<T: CustomFloat>(mcunits: &mut [MonteCarloUnit<T>], mcdata: &MonteCa
     let my_rank = 0;
 
     let ddc = DecompositionObject::new(my_rank, n_ranks, n_domains_per_rank);
-    let global_grid: GlobalFccGrid<T> = GlobalFccGrid::new(nx, ny, nz, lx, ly, lz);
+    let global_grid: GlobalFccGrid<T> = GlobalFccGrid::from_params(&params.simulation_params);
 
     let mut s = params.simulation_params.seed + 1; // use a seed dependant on sim seed
     let domain_centers = initialize_centers_rand(n_units, &global_grid, &mut s);
@@ -308,6 +388,66 @@ fn init_mesh<T: CustomFloat>(mcunits: &mut [MonteCarloUnit<T>], mcdata: &MonteCa
     });
 }
 
+/// Homogenizes the cells of the mesh using volume sampling. Cells filled by
+/// a single material use it with its density averaged over the cell. Other
+/// cells, i.e. cells overlapping several materials or partially void, are
+/// filled with a mixture of the materials they contain so that no mass is lost.
+fn init_cell_materials<T: CustomFloat>(mcdata: &mut MonteCarloData<T>) {
+    let params = &mcdata.params;
+    let mat_db = &mut mcdata.material_database;
+    let grid: GlobalFccGrid<T> = GlobalFccGrid::from_params(&params.simulation_params);
+    let n_cells = grid.nx * grid.ny * grid.nz;
+
+    mcdata.geometry_volume = vec![zero(); params.geometry_params.len()];
+    mat_db.cell_materials = Vec::with_capacity(n_cells);
+    for cell_gid in 0..n_cells {
+        let components =
+            MCDomain::sample_cell(&grid, cell_gid, params, mat_db, &mut mcdata.geometry_volume);
+        let cell_material = match components[..] {
+            [cpnt] if cpnt.volume_fraction == one() => (cpnt.material, cpnt.number_density),
+            _ => mat_db.add_mixture(&components),
+        };
+        mat_db.cell_materials.push(cell_material);
+    }
+}
+
+/// Prints the volume of each geometry as represented by the mesh, compared
+/// to its analytic value when there is one. Geometries are listed in the
+/// order of the input file; unnamed ones are referred to by their position.
+fn report_geometry_volumes<T: CustomFloat>(mcdata: &MonteCarloData<T>) {
+    let params = &mcdata.params;
+    let n_geometries = params.geometry_params.len();
+    info!("  [Geometry Volumes]");
+    info!(
+        "    {:<16} | {:>14} | {:>14} | {:>10}",
+        "geometry", "analytic", "sampled", "error (%)"
+    );
+    // geometries are stored in reverse order
+    params
+        .geometry_params
+        .iter()
+        .zip(mcdata.geometry_volume.iter())
+        .enumerate()
+        .rev()
+        .for_each(|(geom_idx, (geom, &sampled))| {
+            let label = if geom.name.is_empty() {
+                format!("#{}", n_geometries - 1 - geom_idx)
+            } else {
+                geom.name.to_owned()
+            };
+            match geom.volume() {
+                Some(analytic) => {
+                    let error = T::from_f64(100.0).unwrap() * (sampled - analytic) / analytic;
+                    info!("    {label:<16} | {analytic:>14.6e} | {sampled:>14.6e} | {error:>10.3}");
+                }
+                None => info!(
+                    "    {label:<16} | {:>14} | {sampled:>14.6e} | {:>10}",
+                    "-", "-"
+                ),
+            }
+        });
+}
+
 fn init_tallies<T: CustomFloat>(mcunits: &mut [MonteCarloUnit<T>], params: &Parameters<T>) {
     mcunits.iter_mut().for_each(|mcunit| {
         mcunit.tallies.initialize_tallies(
@@ -324,7 +464,38 @@ fn init_xs_cache<T: CustomFloat>(mcunits: &mut [MonteCarloUnit<T>], n_energy_gro
             .domain
             .cell_state
             .iter()
-            .flat_map(|_| (0..n_energy_groups).map(|_| Atomic::new(zero())))
+            .flat_map(|_| (0..N_SPECIES * n_energy_groups).map(|_| Atomic::new(zero())))
+            .collect();
+    })
+}
+
+fn init_source_shares<T: CustomFloat>(
+    mcunits: &mut [MonteCarloUnit<T>],
+    mcdata: &MonteCarloData<T>,
+) {
+    let sim_params = &mcdata.params.simulation_params;
+    let grid: GlobalFccGrid<T> = GlobalFccGrid::from_params(sim_params);
+    let n_cells = (sim_params.nx * sim_params.ny * sim_params.nz) as u64;
+    let n_samples: T = FromPrimitive::from_u64(N_SOURCE_SHARE_SAMPLES).unwrap();
+    mcunits.iter_mut().for_each(|mcunit| {
+        // all units sample the same emission points, using the seeds of the
+        // first particles of each source, so that the shares sum to one
+        mcunit.source_shares = mcdata
+            .sources
+            .iter()
+            .enumerate()
+            .map(|(source_idx, source)| {
+                let n_inside = (0..N_SOURCE_SHARE_SAMPLES)
+                    .filter(|sample_idx| {
+                        let mut rand_n_seed =
+                            (n_cells + source_idx as u64) * 0x0100000000 + sample_idx;
+                        let mut seed = spawn_rn_seed::<T>(&mut rand_n_seed);
+                        let coordinate = source.sample_position(&mut seed);
+                        mcunit.domain.which_cell(&grid, &coordinate).is_some()
+                    })
+                    .count();
+                <T as FromPrimitive>::from_usize(n_inside).unwrap() / n_samples
+            })
             .collect();
     })
 }
@@ -355,8 +526,9 @@ struct XSData<T: Float> {
 ///     9 |   0.000000002656 |        0.0108589141086 |         0.0096890310969 |         0.0794520547945
 /// ```
 ///
-/// The energy scale is logarithmic, hence the way it is printed.
-pub fn check_cross_sections<T: CustomFloat>(mcdata: &MonteCarloData<T>) {
+/// The energy scale is the one of the group structure, logarithmic by default,
+/// hence the way it is printed.
+pub fn check_cross_sections<T: CustomFloat>(mcdata: &MonteCarloData<T>) -> Result<()> {
     let nucdb = &mcdata.nuclear_data;
     let matdb = &mcdata.material_database;
 
@@ -373,24 +545,27 @@ pub fn check_cross_sections<T: CustomFloat>(mcdata: &MonteCarloData<T>) {
     matdb.mat.iter().for_each(|material| {
         let mat_name = material.name.to_owned();
         let mut xc_vec: Vec<XSData<T>> = vec![XSData::default(); n_groups];
-        let n_isotopes: T = FromPrimitive::from_usize(material.iso.len()).unwrap();
         // for each isotope
         material.iso.iter().for_each(|isotope| {
+            let atom_fraction = isotope.atom_fraction;
             // for each reaction
-            nucdb.isotopes[isotope.gid][0]
+            nucdb.isotopes[isotope.gid][Species::Neutron.index()]
                 .reactions
                 .iter()
                 .for_each(|reaction| {
                     // for each energy group
                     (0..n_groups).for_each(|group_idx| match reaction.reaction_type {
                         ReactionType::Scatter => {
-                            xc_vec[group_idx].sca += reaction.cross_section[group_idx] / n_isotopes;
+                            xc_vec[group_idx].sca +=
+                                atom_fraction * reaction.cross_section[group_idx];
                         }
                         ReactionType::Absorption => {
-                            xc_vec[group_idx].abs += reaction.cross_section[group_idx] / n_isotopes;
+                            xc_vec[group_idx].abs +=
+                                atom_fraction * reaction.cross_section[group_idx];
                         }
                         ReactionType::Fission => {
-                            xc_vec[group_idx].fis += reaction.cross_section[group_idx] / n_isotopes;
+                            xc_vec[group_idx].fis +=
+                                atom_fraction * reaction.cross_section[group_idx];
                         }
                     });
                 });
@@ -399,27 +574,22 @@ pub fn check_cross_sections<T: CustomFloat>(mcdata: &MonteCarloData<T>) {
     });
 
     // build an output file
-    let file_name = mcdata
-        .params
-        .simulation_params
-        .cross_sections_out
-        .to_owned()
-        + ".dat";
-    let mut file = File::create(file_name).unwrap();
+    let sim_params = &mcdata.params.simulation_params;
+    let file_name = sim_params.cross_sections_out.to_owned() + ".dat";
+    let mut file = BufWriter::new(output::create_output(sim_params, &file_name)?);
     // header
-    write!(file, "group |           energy |  ").unwrap();
-    xc_table.iter().for_each(|(mat_name, _)| {
+    write!(file, "group |           energy |  ")?;
+    for mat_name in xc_table.keys() {
         write!(
             file,
             "{mat_name}_absorb |  {mat_name}_fission |  {mat_name}_scatter"
-        )
-        .unwrap();
-    });
-    writeln!(file).unwrap();
+        )?;
+    }
+    writeln!(file)?;
     // data
-    (0..n_groups).for_each(|ii| {
-        write!(file, "{:>5} |  {:>15.12} |   ", ii, energies[ii]).unwrap();
-        xc_table.values_mut().for_each(|xc_vec| {
+    for ii in 0..n_groups {
+        write!(file, "{:>5} |  {:>15.12} |   ", ii, energies[ii])?;
+        for xc_vec in xc_table.values_mut() {
             if xc_vec[ii].abs < T::tiny_float() {
                 xc_vec[ii].abs = zero();
             }
@@ -433,9 +603,9 @@ pub fn check_cross_sections<T: CustomFloat>(mcdata: &MonteCarloData<T>) {
                 file,
                 "{:>20.13} |  {:>22.13} |  {:>22.13}",
                 xc_vec[ii].abs, xc_vec[ii].fis, xc_vec[ii].sca
-            )
-            .unwrap();
-        });
-        writeln!(file).unwrap()
-    });
+            )?;
+        }
+        writeln!(file)?;
+    }
+    file.flush()
 }
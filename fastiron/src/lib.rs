@@ -29,6 +29,26 @@
 //!           name of energy spectrum output file
 //!   -S, --cross-sections <CROSS_SECTIONS_OUT>
 //!           name of cross-section output file
+//!       --vtk <VTK_OUT>
+//!           name of VTK mesh & tallies output file
+//!       --tracks <TRACK_OUT>
+//!           name of particle tracks output file
+//!       --json <JSON_OUT>
+//!           name of JSON run report output file
+//!       --trace <TRACE_OUT>
+//!           name of per-thread timing trace output file, in the Chrome trace-event format
+//!       --output-dir <OUTPUT_DIR>
+//!           directory output files are written to -- {threads}, {units}, {particles}, {steps}, {seed} & {timestamp} are replaced
+//!       --run-name <RUN_NAME>
+//!           prefix of the output files -- supports the same placeholders as the output directory
+//!       --overwrite
+//!           replace existing output files if present
+//!       --progress <PROGRESS_INTERVAL>
+//!           interval in seconds between two progress reports during tracking -- if absent or set to 0, progress is not reported
+//!       --log-level <LOG_LEVEL>
+//!           verbosity of the log messages [default: info] [possible values: error, warn, info, debug]
+//!       --quiet
+//!           only print errors & reports, overriding the log level
 //!   -D, --dt <DT>
 //!           time step in seconds
 //!   -l, --load-balance
@@ -51,6 +71,8 @@
 //!           size of the chunks when executing in parallel -- if absent or set to 0, use dynamic chunk size
 //!   -r, --rayon <N_RAYON_THREADS>
 //!           number of rayon threads that should be used to run the simulation -- set to 0 for rayon's default config
+//!       --implicit-capture
+//!           use implicit capture instead of analog absorption if present
 //!   -u, --units <N_UNITS>
 //!           number of units that should be used to run the simulation
 //!   -N, --n-steps <N_STEPS>
@@ -79,14 +101,20 @@
 //! $ cargo run --bin=fastiron --release -- -i input_files/QS_originals/Homogeneous/homogeneousProblem_v7_ts.inp -e energy -S section -n 10000
 //! ```
 //!
-//! Fastiron will print the parameters and run the simulation. Two reports will
-//! be printed at run-time and, if file names are specified, two files will be
-//! created. These four outputs contain data such as event counts, timers value,
+//! Fastiron will print the parameters and run the simulation. Three reports will
+//! be printed at run-time and, if file names are specified, up to three files
+//! will be created. These outputs contain data such as event counts, timers value,
 //! or final state of the system. To see more about these reports:
 //! - [`Tallies::print_summary()`][crate::data::tallies::Tallies::print_summary()]
 //! - [`MCFastTimerContainer::cumulative_report()`][crate::utils::mc_fast_timer::MCFastTimerContainer::cumulative_report()]
 //! - [`EnergySpectrum`][crate::data::energy_spectrum::EnergySpectrum]
 //! - [`init::check_cross_sections()`]
+//! - [`vtk::write_vtk()`][crate::utils::vtk::write_vtk()]
+//! - [`MemoryReport`][crate::utils::memory::MemoryReport]
+//! - [`RunReport`][crate::utils::report::RunReport]
+//! - [`Tracer`][crate::utils::trace::Tracer]
+//!
+//! The location & naming of output files is described in [`utils::output`].
 //!
 //! # Useful Links
 //!
@@ -4,6 +4,7 @@ use std::time::Instant;
 
 use clap::Parser;
 use hwlocality::topology::support::{DiscoverySupport, FeatureSupport};
+use log::{error, info, warn};
 use num::{one, zero, FromPrimitive};
 
 use hwlocality::cpu::binding::CpuBindingFlags;
@@ -18,12 +19,18 @@ use fastiron::data::tallies::TalliedEvent;
 use fastiron::init::{init_mcdata, init_mcunits, init_particle_containers, init_results};
 use fastiron::montecarlo::{MonteCarloData, MonteCarloResults, MonteCarloUnit};
 use fastiron::parameters::Parameters;
+use fastiron::particles::mc_particle::Species;
 use fastiron::particles::particle_container::ParticleContainer;
 use fastiron::simulation::population_control;
 use fastiron::utils::coral_benchmark_correctness::coral_benchmark_correctness;
 use fastiron::utils::input::Cli;
+use fastiron::utils::logging;
 use fastiron::utils::mc_fast_timer::{self, Section};
 use fastiron::utils::mc_processor_info::ExecPolicy;
+use fastiron::utils::memory::MemoryReport;
+use fastiron::utils::output;
+use fastiron::utils::report::RunReport;
+use fastiron::utils::vtk::write_vtk;
 
 //=====
 // Main
@@ -31,12 +38,13 @@ use fastiron::utils::mc_processor_info::ExecPolicy;
 
 fn main() {
     let cli = Cli::parse();
+    logging::init(cli.log_level, cli.quiet);
 
     if cli.single_precision {
-        println!("[INFO] Running simulation using `f32`");
+        info!("Running simulation using `f32`");
         run::<f32>(cli);
     } else {
-        println!("[INFO] Running simulation using `f64`");
+        info!("Running simulation using `f64`");
         run::<f64>(cli);
     }
 }
@@ -46,15 +54,21 @@ fn main() {
 //============
 
 pub fn run<T: CustomFloat>(cli: Cli) {
-    let params: Parameters<T> = Parameters::get_parameters(cli).unwrap();
-    println!("[Simulation Parameters]\n{:#?}", params.simulation_params);
+    let params: Parameters<T> = match Parameters::get_parameters(cli) {
+        Ok(params) => params,
+        Err(e) => {
+            error!("Could not set up the simulation: {e:?}");
+            std::process::exit(1);
+        }
+    };
+    info!("[Simulation Parameters]\n{:#?}", params.simulation_params);
 
     let n_cells_tot =
         params.simulation_params.nx * params.simulation_params.ny * params.simulation_params.nz;
 
     if params.simulation_params.n_particles as usize / n_cells_tot < 10 {
-        println!("[ERROR] TOO FEW PARTICLES PER CELL OVERALL");
-        println!("[ERROR] Need at least 10 particles per cell of the mesh overall");
+        error!("TOO FEW PARTICLES PER CELL OVERALL");
+        error!("Need at least 10 particles per cell of the mesh overall");
         return;
     }
 
@@ -63,7 +77,7 @@ pub fn run<T: CustomFloat>(cli: Cli) {
     //===============
 
     let start_init = Instant::now();
-    println!("[Initialization]: Start");
+    info!("[Initialization]: Start");
 
     let n_steps = params.simulation_params.n_steps;
 
@@ -79,13 +93,13 @@ pub fn run<T: CustomFloat>(cli: Cli) {
             let topology = Topology::new().unwrap();
             // these railguards were taken from the bind_threads_cpu of hwlocality
             if !topology.supports(FeatureSupport::discovery, DiscoverySupport::pu_count) {
-                println!("[Warning] cannot bind threads to core -- reporting of PU objects not supported");
+                warn!("cannot bind threads to core -- reporting of PU objects not supported");
             } else if topology.feature_support().cpu_binding().is_none() {
-                println!("[Warning] cannot bind threads to core -- CPU binding not supported");
+                warn!("cannot bind threads to core -- CPU binding not supported");
             } else {
                 let cpu_support = topology.feature_support().cpu_binding().unwrap();
                 if !(cpu_support.get_thread() && cpu_support.set_thread()) {
-                    println!("[Warning] cannot bind threads to core -- CPU binding queries not supported");
+                    warn!("cannot bind threads to core -- CPU binding queries not supported");
                 } else {
                     // we can get to work
                     let topo = Arc::new(Mutex::new(topology));
@@ -100,14 +114,13 @@ pub fn run<T: CustomFloat>(cli: Cli) {
         mcdata.exec_info.n_rayon_threads = rayon::current_num_threads();
     }
 
-    println!("[Initialization]: Done");
-    println!(
+    info!("[Initialization]: Done");
+    info!(
         "[Initialization]: {}ms elapsed",
         start_init.elapsed().as_millis()
     );
 
-    println!("[Execution Info]");
-    println!("{}", mcdata.exec_info);
+    info!("[Execution Info]\n{}", mcdata.exec_info);
 
     //==========
     // Core loop
@@ -119,6 +132,7 @@ pub fn run<T: CustomFloat>(cli: Cli) {
             mc_fast_timer::start(&mut mcunits[0].fast_timer, Section::Main);
 
             for step in 0..n_steps {
+                let cycle_start = mcunits[0].fast_timer.tracer.now();
                 cycle_sync(
                     &mut mcdata,
                     &mut mcunits,
@@ -127,6 +141,12 @@ pub fn run<T: CustomFloat>(cli: Cli) {
                     &mut mcresults,
                 );
                 cycle_process(&mcdata, &mut mcunits[0], &mut containers[0]);
+                mcunits[0].fast_timer.tracer.record(
+                    "cycle",
+                    "cycle",
+                    cycle_start,
+                    &[("cycle", step as u64)],
+                );
             }
             cycle_sync(
                 &mut mcdata,
@@ -148,7 +168,18 @@ pub fn run<T: CustomFloat>(cli: Cli) {
 
     game_over(&mcdata, &mut mcunits, &mcresults);
 
-    coral_benchmark_correctness(&mcresults);
+    let memory = MemoryReport::new(&mcdata, &mcunits, &containers, &mcresults);
+    memory.print();
+
+    let correctness = coral_benchmark_correctness(&mcresults);
+
+    let json_out = &mcdata.params.simulation_params.json_out;
+    if !json_out.is_empty() {
+        if let Err(e) = RunReport::new(&mcdata, &mcunits, &mcresults, &memory, &correctness).write()
+        {
+            error!("Could not write run report: {e}");
+        }
+    }
 }
 
 //==========================
@@ -165,12 +196,46 @@ pub fn game_over<T: CustomFloat>(
             let mcunit = &mut mcunits[0];
             mcunit.fast_timer.update_main_stats();
 
-            mcunit.fast_timer.cumulative_report(
+            if let Err(e) = mcunit.fast_timer.cumulative_report(
                 mcresults.balance_cumulative[TalliedEvent::NumSegments],
-                mcdata.params.simulation_params.csv,
-            );
+                &mcdata.params.simulation_params,
+            ) {
+                error!("Could not write timers report: {e}");
+            }
+
+            let tracer = &mcunit.fast_timer.tracer;
+            if tracer.is_enabled() {
+                tracer.print_thread_report(
+                    mcunit.fast_timer.tots[Section::CycleTrackingProcess as usize],
+                );
+                if let Err(e) = tracer.write(&mcdata.params.simulation_params) {
+                    error!("Could not write trace: {e}");
+                }
+            }
 
-            mcresults.spectrum.print_spectrum(mcdata);
+            if let Err(e) = mcresults.spectrum.print_spectrum(mcdata) {
+                error!("Could not write energy spectrum: {e}");
+            }
+
+            // only worth printing if photons were transported
+            if mcresults.balance_cumulative[(Species::Photon, TalliedEvent::NumSegments)] != 0 {
+                mcresults
+                    .balance_cumulative
+                    .print_species_summary(&mcresults.species_flux);
+            }
+
+            let vtk_out = &mcdata.params.simulation_params.vtk_out;
+            if !vtk_out.is_empty() {
+                if let Err(e) = write_vtk(vtk_out, mcdata, mcunits, mcresults) {
+                    error!("Could not write VTK file: {e}");
+                }
+            }
+
+            if mcunits[0].tracks.is_enabled() {
+                if let Err(e) = mcunits[0].tracks.write(&mcdata.params.simulation_params) {
+                    error!("Could not write particle tracks: {e}");
+                }
+            }
         }
         ExecPolicy::Distributed | ExecPolicy::Hybrid => todo!(),
     }
@@ -205,8 +270,7 @@ pub fn bind_threads(thread_id: usize, topo: &Arc<Mutex<Topology>>) {
     match locked_topo.bind_thread_cpu(pthread_id, cpu_set, CpuBindingFlags::THREAD) {
         Ok(_) => {}
         Err(e) => {
-            println!("[Error]: Could not bind threads to cpu cores:");
-            println!("{e:#?}");
+            error!("Could not bind threads to cpu cores:\n{e:#?}");
         }
     }
 }
@@ -243,14 +307,29 @@ pub fn cycle_sync<T: CustomFloat>(
                 // if sequential/rayon-only, just use the single Monte-Carlo unit
                 mcunits[0].tallies.balance_cycle[TalliedEvent::End] =
                     containers[0].processed_particles.len() as u64;
-                mcunits[0].tallies.print_summary(
+                if let Err(e) = mcunits[0].tallies.print_summary(
                     &mut mcunits[0].fast_timer,
                     step - 1,
-                    mcdata.params.simulation_params.csv,
-                );
+                    &mcdata.params.simulation_params,
+                ) {
+                    error!("Could not write tallies report: {e}");
+                }
                 mcresults.update_stats(mcunits);
                 mcresults.update_spectrum(containers);
+                mcresults.update_memory(containers);
                 mcunits[0].fast_timer.clear_last_cycle_timers();
+
+                // intermediate mesh output
+                let sim = &mcdata.params.simulation_params;
+                if !sim.vtk_out.is_empty()
+                    && (sim.vtk_frequency != 0)
+                    && step.is_multiple_of(sim.vtk_frequency)
+                {
+                    let file_name = output::vtk_step_name(sim, step);
+                    if let Err(e) = write_vtk(&file_name, mcdata, mcunits, mcresults) {
+                        error!("Could not write VTK file: {e}");
+                    }
+                }
             }
             ExecPolicy::Distributed | ExecPolicy::Hybrid => todo!(), // need to reduce
         }
@@ -262,12 +341,15 @@ pub fn cycle_sync<T: CustomFloat>(
     }
 
     // prepare structures for next processing cycle
+    mcdata.time = mcdata.params.simulation_params.dt * FromPrimitive::from_usize(step).unwrap();
     let iter = zip(mcunits.iter_mut(), containers.iter_mut());
     let mut current_n_particles: usize = 0;
     let mut total_problem_weight: T = zero();
     iter.for_each(|(mcunit, container)| {
         mcunit.update_unit_weight(mcdata);
         mcunit.clear_cross_section_cache();
+        mcunit.tracks.start_cycle(step);
+        mcunit.progress.start_cycle(step);
         container.swap_processing_processed();
         let local_n_particles = container.processing_particles.len();
         mcunit.tallies.balance_cycle[TalliedEvent::Start] = local_n_particles as u64;
@@ -305,6 +387,7 @@ pub fn cycle_process<T: CustomFloat>(
         mcdata.exec_info.n_units,
         mcdata.params.simulation_params.load_balance,
     );
+    mcunit.window_weight = mcdata.source_particle_weight / split_rr_factor;
     // regulate accordingly
     if split_rr_factor < one() {
         container.regulate_population(
@@ -324,6 +407,9 @@ pub fn cycle_process<T: CustomFloat>(
 
     mc_fast_timer::stop(&mut mcunit.fast_timer, Section::PopulationControl);
     mc_fast_timer::start(&mut mcunit.fast_timer, Section::CycleTracking);
+    mcunit
+        .progress
+        .start_tracking(container.processing_particles.len());
 
     while !container.is_done_processing() {
         // sort particles
@@ -5,22 +5,30 @@
 //! here in the future.
 
 use std::fmt::Debug;
+use std::iter::zip;
 use std::ops::{Index, IndexMut};
 
 use atomic::{Atomic, Ordering};
 use num::zero;
 
-use crate::constants::CustomFloat;
+use crate::constants::{sim::N_SPECIES, CustomFloat};
 use crate::data::energy_spectrum::EnergySpectrum;
+use crate::data::external_source::ExternalSource;
 use crate::data::material_database::MaterialDatabase;
 use crate::data::nuclear_data::NuclearData;
 use crate::data::tallies::{Balance, FluenceDomain, Tallies};
+use crate::data::tracks::ParticleTracks;
+use crate::data::weight_windows::WeightWindows;
 use crate::geometry::mc_domain::MCDomain;
 use crate::parameters::{BenchType, Parameters};
+use crate::particles::mc_particle::Species;
 use crate::particles::particle_collection::ParticleCollection;
 use crate::particles::particle_container::ParticleContainer;
 use crate::utils::mc_fast_timer::MCFastTimerContainer;
 use crate::utils::mc_processor_info::MCProcessorInfo;
+use crate::utils::memory::CycleMemory;
+use crate::utils::progress::Progress;
+use crate::utils::report::CycleReport;
 
 /// Super-structure used to contain all the problem's data.
 ///
@@ -42,16 +50,36 @@ pub struct MonteCarloData<T: CustomFloat> {
     /// Current total number of particles in the simulation. This value is updated at
     /// each cycle for ease of access by all [MonteCarloUnit].
     pub global_n_particles: usize,
+    /// External sources of the problem.
+    pub sources: Vec<ExternalSource<T>>,
+    /// Time at the start of the current cycle (s).
+    pub time: T,
+    /// Weight windows of the problem, if any.
+    pub weight_windows: Option<WeightWindows<T>>,
+    /// Volume of each geometry of the problem as represented by the mesh,
+    /// estimated by sampling the cells. Empty if volume sampling is disabled.
+    pub geometry_volume: Vec<T>,
 }
 
 impl<T: CustomFloat> MonteCarloData<T> {
     /// Constructor.
     pub fn new(params: Parameters<T>) -> Self {
         let exec_info = MCProcessorInfo::new(&params.simulation_params);
+        let sources = params
+            .source_params
+            .iter()
+            .map(|sp| ExternalSource::new(sp, &params.simulation_params))
+            .collect();
+        let sim = &params.simulation_params;
+        let weight_windows = (!sim.weight_windows.is_empty()).then(|| {
+            WeightWindows::new(&sim.weight_windows, sim.nx * sim.ny * sim.nz, sim.n_groups)
+        });
 
         Self {
             params,
             exec_info,
+            sources,
+            weight_windows,
             ..Default::default()
         }
     }
@@ -72,6 +100,19 @@ pub struct MonteCarloUnit<T: CustomFloat> {
     pub unit_weight: T,
     /// Structure used to lazily compute cross-sections.
     pub xs_cache: XSCache<T>,
+    /// Number of particles spawned by each external source, used to
+    /// generate random number seeds.
+    pub source_tallies: Vec<u64>,
+    /// Fraction of the emissions of each external source that take place
+    /// in the domain of the unit.
+    pub source_shares: Vec<T>,
+    /// Reference weight of the weight windows, i.e. the weight of source
+    /// particles after population control.
+    pub window_weight: T,
+    /// Tracks of the recorded particles.
+    pub tracks: ParticleTracks<T>,
+    /// Progress of the tracking, reported periodically if enabled.
+    pub progress: Progress,
 }
 
 impl<T: CustomFloat> MonteCarloUnit<T> {
@@ -88,20 +129,28 @@ impl<T: CustomFloat> MonteCarloUnit<T> {
             .material_database
             .mat
             .iter()
-            .map(|mat| mcdata.params.material_params[&mat.name].source_rate)
+            .map(|mat| mat.source_rate)
             .collect();
 
-        self.unit_weight = self
+        let dt = mcdata.params.simulation_params.dt;
+        let material_weight = self
             .domain
             .cell_state
             .iter()
             .map(|cell| {
-                // constant because cell volume is constant in our program
-                let cell_weight: T =
-                    cell.volume * source_rate[cell.material] * mcdata.params.simulation_params.dt;
+                // cells may have different volumes on rectilinear meshes
+                let cell_weight: T = cell.volume * source_rate[cell.material] * dt;
                 cell_weight
             })
-            .sum::<T>()
+            .sum::<T>();
+        // each unit only emits its share of the external sources
+        let external_weight = mcdata
+            .sources
+            .iter()
+            .zip(self.source_shares.iter())
+            .map(|(source, share)| source.weight(mcdata.time, dt) * *share)
+            .sum::<T>();
+        self.unit_weight = material_weight + external_weight;
     }
 }
 
@@ -112,22 +161,29 @@ pub struct XSCache<T: CustomFloat> {
     /// index when accessing a cross-section.
     pub num_groups: usize,
     /// Flattened cache for cross-section storage. The structure is indexed using
-    /// cell ID & energy group.
+    /// cell ID, particle species & energy group.
     pub cache: Vec<Atomic<T>>,
 }
 
+impl<T: CustomFloat> XSCache<T> {
+    fn flat_index(&self, index: (usize, Species, usize)) -> usize {
+        (index.0 * N_SPECIES + index.1.index()) * self.num_groups + index.2
+    }
+}
+
 // maybe make these accesses unchecked?
-impl<T: CustomFloat> Index<(usize, usize)> for XSCache<T> {
+impl<T: CustomFloat> Index<(usize, Species, usize)> for XSCache<T> {
     type Output = Atomic<T>;
 
-    fn index(&self, index: (usize, usize)) -> &Self::Output {
-        &self.cache[index.0 * self.num_groups + index.1]
+    fn index(&self, index: (usize, Species, usize)) -> &Self::Output {
+        &self.cache[self.flat_index(index)]
     }
 }
 
-impl<T: CustomFloat> IndexMut<(usize, usize)> for XSCache<T> {
-    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
-        &mut self.cache[index.0 * self.num_groups + index.1]
+impl<T: CustomFloat> IndexMut<(usize, Species, usize)> for XSCache<T> {
+    fn index_mut(&mut self, index: (usize, Species, usize)) -> &mut Self::Output {
+        let idx = self.flat_index(index);
+        &mut self.cache[idx]
     }
 }
 
@@ -138,8 +194,18 @@ impl<T: CustomFloat> IndexMut<(usize, usize)> for XSCache<T> {
 pub struct MonteCarloResults<T: CustomFloat> {
     /// Balance used for cumulative and centralized statistics.
     pub balance_cumulative: Balance,
+    /// Cumulative scalar flux of each species.
+    pub species_flux: [T; N_SPECIES],
     /// Top-level structure used to compute fluence data.
     pub fluence: FluenceDomain<T>,
+    /// Cumulative neutron scalar flux of each cell & energy group, for each
+    /// unit. Only tallied if specified at construction, empty otherwise.
+    pub cell_flux: Option<Vec<Vec<T>>>,
+    /// Per-cycle data used for the run report. Only recorded if specified at
+    /// construction, `None` otherwise.
+    pub cycles: Option<Vec<CycleReport>>,
+    /// Per-cycle memory state of the particle collections.
+    pub memory: Vec<CycleMemory>,
     /// Energy spectrum of the problem.
     pub spectrum: EnergySpectrum,
     /// Enum used to adapt additional checks after simulation.
@@ -147,11 +213,23 @@ pub struct MonteCarloResults<T: CustomFloat> {
 }
 
 impl<T: CustomFloat> MonteCarloResults<T> {
-    /// Constructor.
-    pub fn new(spectrum_name: String, spectrum_size: usize, bench_type: BenchType) -> Self {
+    /// Constructor. Cell fluxes are only tallied if `tally_cell_flux` is true.
+    /// Per-cycle data is only recorded if `record_cycles` is true; the energy
+    /// spectrum is then computed even if no output file is specified.
+    pub fn new(
+        spectrum_name: String,
+        spectrum_size: usize,
+        bench_type: BenchType,
+        tally_cell_flux: bool,
+        record_cycles: bool,
+    ) -> Self {
         Self {
             balance_cumulative: Default::default(),
+            species_flux: [zero(); N_SPECIES],
             fluence: Default::default(),
+            cell_flux: tally_cell_flux.then(Vec::new),
+            cycles: record_cycles.then(Vec::new),
+            memory: Vec::new(),
             spectrum: EnergySpectrum::new(spectrum_name, spectrum_size),
             bench_type,
         }
@@ -159,7 +237,7 @@ impl<T: CustomFloat> MonteCarloResults<T> {
 
     /// Update the energy spectrum by going over all the currently valid particles.
     pub fn update_spectrum(&mut self, containers: &[ParticleContainer<T>]) {
-        if self.spectrum.file_name.is_empty() {
+        if self.spectrum.file_name.is_empty() & self.cycles.is_none() {
             return;
         }
 
@@ -184,11 +262,34 @@ impl<T: CustomFloat> MonteCarloResults<T> {
         })
     }
 
+    /// Record the memory state of the particle collections at the end of the
+    /// cycle. This resets their reallocation counters.
+    pub fn update_memory(&mut self, containers: &mut [ParticleContainer<T>]) {
+        self.memory
+            .push(CycleMemory::new(self.memory.len(), containers));
+    }
+
     /// Update internal structure from data tallied during the cycle.
     pub fn update_stats(&mut self, mcunits: &mut [MonteCarloUnit<T>]) {
+        if let Some(cycles) = &mut self.cycles {
+            cycles.push(CycleReport::new(cycles.len(), mcunits));
+        }
+        if let Some(cell_flux) = &mut self.cell_flux {
+            cell_flux.resize_with(mcunits.len(), Vec::new);
+            zip(cell_flux.iter_mut(), mcunits.iter()).for_each(|(unit_flux, mcunit)| {
+                let flux_domain = &mcunit.tallies.scalar_flux_domain;
+                unit_flux.resize(flux_domain.cell.len(), zero());
+                zip(unit_flux.iter_mut(), flux_domain.cell.iter())
+                    .for_each(|(total, val)| *total += val.load(Ordering::Relaxed));
+            });
+        }
         mcunits.iter_mut().for_each(|mcunit| {
             self.balance_cumulative
                 .add_to_self(&mcunit.tallies.balance_cycle);
+            self.species_flux[Species::Neutron.index()] +=
+                mcunit.tallies.species_flux_sum(Species::Neutron);
+            self.species_flux[Species::Photon.index()] +=
+                mcunit.tallies.species_flux_sum(Species::Photon);
             if self.bench_type != BenchType::Standard {
                 self.fluence.compute(&mcunit.tallies.scalar_flux_domain)
             }
@@ -4,11 +4,20 @@
 //! simulation. Input reading is located in the [`crate::utils`] module,
 //! while parsing is done here.
 
+use std::path::Path;
+
 use rustc_hash::FxHashMap;
+use serde::Serialize;
 
 use crate::{
-    constants::CustomFloat,
-    utils::input::{parse_input_file, Cli, InputError},
+    constants::{groups, CustomFloat},
+    data::{mc_vector::MCVector, nuclear_data::log_uniform_boundaries},
+    geometry::csg::CsgExpression,
+    particles::mc_particle::Species,
+    utils::{
+        input::{parse_input_file, read_table, read_weight_windows, Cli, InputError},
+        output,
+    },
 };
 
 /// Alias for a `<String, String>` [`FxHashMap`]. See here for detailed
@@ -31,10 +40,38 @@ pub enum ParameterError {
     /// contains the name of the aforementioned cross-section and
     /// the material referring to it.
     MissingCrossSection(String),
+    /// There is a missing reference to an isotope; The string
+    /// contains the name of the aforementioned isotope and the
+    /// material referring to it.
+    MissingIsotope(String),
+    /// The isotopic composition of a material is invalid, e.g. the
+    /// number of atom fractions does not match the number of isotopes;
+    /// The string contains the name of the material.
+    BadComposition(String),
+    /// The energy group boundaries are invalid, i.e. there are less than two
+    /// of them, or they are not positive and strictly increasing.
+    BadEnergyGroups,
+    /// An external source is inconsistent with the problem, e.g. it lies
+    /// outside of the problem; The value is the index of the source in the
+    /// list of sources.
+    BadSource(usize),
+    /// A weight window refers to a cell or an energy group that does not
+    /// exist; The value is the index of the window in the list of windows.
+    BadWeightWindow(usize),
+    /// A boundary condition is unknown, or a periodic face is paired with a
+    /// non-periodic one; The string contains the aforementioned condition.
+    BadBoundaryCondition(String),
+    /// A region refers to an unknown geometry, refers to itself directly or
+    /// indirectly, or shares its name with another geometry; The string
+    /// contains the name of the aforementioned geometry.
+    BadRegion(String),
+    /// The mesh planes along an axis are invalid, i.e. there are less than two
+    /// of them, they do not start at zero or are not strictly increasing.
+    BadMeshPlanes,
 }
 
 /// Enum used to run additional tests according to the input benchmark
-#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize)]
 pub enum BenchType {
     /// No additional tests are executed. This is the default mode.
     #[default]
@@ -45,8 +82,23 @@ pub enum BenchType {
     Coral2,
 }
 
+/// Enum used to describe the energy group structure of the problem
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize)]
+pub enum GroupStructure {
+    /// Default value. `nGroups` groups are logarithmically spaced between the
+    /// energy bounds of the problem.
+    #[default]
+    LogUniform,
+    /// CASMO 2-group structure, i.e. a thermal and a fast group.
+    Casmo2,
+    /// CASMO 4-group structure.
+    Casmo4,
+    /// Group boundaries are explicitly listed in the input.
+    Explicit,
+}
+
 /// Enum used to describe a geometry's shape
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub enum Shape {
     /// Default value. Will result in errors if any geometries still
     /// hold this value at the end of initialization.
@@ -56,26 +108,222 @@ pub enum Shape {
     Brick,
     /// Sphere-shaped geometry.
     Sphere,
+    /// Cylinder-shaped geometry, centered on a point of its axis.
+    Cylinder,
+    /// Cone-shaped geometry, with its apex on a point of its axis.
+    Cone,
+    /// Half-space delimited by a plane. The space on the side the axis points
+    /// away from is inside the geometry.
+    Plane,
+    /// Ellipsoid-shaped geometry, aligned with the axes of the problem.
+    Ellipsoid,
+    /// Region defined by a boolean expression over other named geometries.
+    /// See [CsgExpression] for the syntax.
+    Region,
+}
+
+/// Enum used to describe the spatial variation of a geometry's density
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum DensityProfile {
+    /// Default value. The density is constant over the whole geometry.
+    #[default]
+    Uniform,
+    /// The density varies linearly with the position: `density + g.r`.
+    /// Negative values are clamped to zero.
+    Linear,
+    /// The density varies exponentially with the position: `density * exp(g.r)`.
+    Exponential,
+}
+
+/// Enum used to describe how a tabulated cross-section is collapsed onto
+/// the energy groups
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum CrossSectionAveraging {
+    /// Default value. The linearly-interpolated data is averaged over each
+    /// group using a `1/E` flux as the weighting function.
+    #[default]
+    FluxWeighted,
+    /// The linearly-interpolated data is averaged over each group without
+    /// weighting.
+    Interpolated,
+}
+
+/// Enum used to describe the energy distribution of particles produced by fission
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum FissionSpectrumType {
+    /// Default value. Energies are sampled as `20 * ((r + 1) / 2)^2` MeV,
+    /// `r` being a random number in `[0; 1[`.
+    #[default]
+    Legacy,
+    /// Maxwellian spectrum of parameter `maxwellTemperature`.
+    Maxwellian,
+    /// Watt spectrum of parameters `wattA` and `wattB`.
+    Watt,
+}
+
+/// Enum used to describe how scattered particles are sampled
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum ScatteringModel {
+    /// Default value. The outgoing energy is `E * (1 - r / A)` and the outgoing
+    /// direction is isotropic in the laboratory frame.
+    #[default]
+    Legacy,
+    /// Elastic scattering on a target at rest. The scattering cosine is sampled
+    /// in the centre-of-mass frame, optionally using Legendre moments, and
+    /// converted to the laboratory frame.
+    Elastic,
+}
+
+/// Enum used to describe the spatial distribution of an external source
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum SourceShape {
+    /// Default value. Particles are emitted from a single point.
+    #[default]
+    Point,
+    /// Particles are emitted uniformly in a rectangular cuboid.
+    Box,
+    /// Particles are emitted uniformly in a sphere.
+    Sphere,
+    /// Particles are emitted uniformly on a boundary face of the problem.
+    Surface,
+}
+
+/// Enum used to designate a boundary face of the problem
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum BoundaryFace {
+    /// Default value. Face of equation `x = 0`.
+    #[default]
+    XMin,
+    /// Face of equation `x = lx`.
+    XMax,
+    /// Face of equation `y = 0`.
+    YMin,
+    /// Face of equation `y = ly`.
+    YMax,
+    /// Face of equation `z = 0`.
+    ZMin,
+    /// Face of equation `z = lz`.
+    ZMax,
+}
+
+/// Enum used to describe the angular distribution of an external source
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum AngularDistribution {
+    /// Default value. Directions are sampled uniformly over the unit sphere,
+    /// or over the inward half-sphere for a surface source.
+    #[default]
+    Isotropic,
+    /// All particles are emitted along the same direction. A surface source
+    /// emits along the inward normal if no direction is specified.
+    Mono,
+    /// Directions follow a cosine distribution around the inward normal of
+    /// a surface source.
+    Cosine,
+}
+
+/// Enum used to describe the energy distribution of an external source
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum EnergyDistribution {
+    /// Default value. Energies are sampled uniformly between two bounds.
+    #[default]
+    Uniform,
+    /// All particles are emitted with the same energy.
+    Mono,
+    /// Watt spectrum of parameters `wattA` and `wattB`.
+    Watt,
+    /// Histogram defined by bin boundaries and relative weights. Energies
+    /// are uniform within a bin.
+    Histogram,
+}
+
+/// Enum used to describe the time dependence of an external source intensity
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum TimeProfile {
+    /// Default value. The intensity is constant while the source is active.
+    #[default]
+    Constant,
+    /// The intensity decays exponentially from the start of the source,
+    /// with a characteristic time `decayTime`.
+    Exponential,
 }
 
 /// Structure used to describe a geometry, i.e. a physical space of a
 /// certain shape and certain material.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone)]
 pub struct GeometryParameters<T: CustomFloat> {
-    /// Name of the material the geometry is made of.
+    /// Name of the geometry, used to refer to it in regions. Optional.
+    pub name: String,
+    /// Name of the material the geometry is made of. Named geometries without
+    /// material are only used to build regions and are not assigned to cells.
     pub material_name: String,
+    /// Number density of the material relative to its nominal value. A value
+    /// of zero results in a void.
+    pub density: T,
+    /// Spatial variation of the density. See [DensityProfile] for more information.
+    pub density_profile: DensityProfile,
+    /// Importance of the geometry. Particles moving towards more important
+    /// cells are split, particles moving towards less important cells play
+    /// russian roulette. Particles entering a cell of null importance are killed.
+    pub importance: T,
+    /// x-component of the density gradient `g`, in cm⁻¹ (or density units per cm
+    /// for a linear profile).
+    pub density_gradient_x: T,
+    /// y-component of the density gradient `g`.
+    pub density_gradient_y: T,
+    /// z-component of the density gradient `g`.
+    pub density_gradient_z: T,
     /// Shape of the material. Note that this value defines which other fields are used:
     /// - A sphere-shaped geometry will only use radius and coordinates of the center.
     /// - A brick-shaped geometry will only use bounds on the axes.
+    /// - A cylinder-shaped geometry will use radius, height, axis and coordinates
+    ///   of the center of the cylinder.
+    /// - A cone-shaped geometry will use radius of the base, height, axis and
+    ///   coordinates of the apex, stored as the center.
+    /// - A plane will use axis as the normal and coordinates of the center as a
+    ///   point of the plane.
+    /// - An ellipsoid-shaped geometry will use radii along each axis and
+    ///   coordinates of the center.
     pub shape: Shape,
-    /// Radius of a sphere-shaped geometry.
+    /// Radius of a sphere, cylinder or of the base of a cone.
     pub radius: T,
-    /// x-coordinate of the center of a sphere-shaped geometry.
+    /// x-coordinate of the center of the geometry.
     pub x_center: T,
-    /// y-coordinate of the center of a sphere-shaped geometry.
+    /// y-coordinate of the center of the geometry.
     pub y_center: T,
-    /// z-coordinate of the center of a sphere-shaped geometry.
+    /// z-coordinate of the center of the geometry.
     pub z_center: T,
+    /// x-component of the axis of a cylinder, cone or plane.
+    pub x_axis: T,
+    /// y-component of the axis of a cylinder, cone or plane.
+    pub y_axis: T,
+    /// z-component of the axis of a cylinder, cone or plane.
+    pub z_axis: T,
+    /// Length of a cylinder or cone along its axis. Cylinders are unbounded
+    /// by default.
+    pub height: T,
+    /// Semi-axis along the x-axis of an ellipsoid-shaped geometry.
+    pub x_radius: T,
+    /// Semi-axis along the y-axis of an ellipsoid-shaped geometry.
+    pub y_radius: T,
+    /// Semi-axis along the z-axis of an ellipsoid-shaped geometry.
+    pub z_radius: T,
+    /// Boolean expression defining a region-shaped geometry.
+    pub region: Option<CsgExpression>,
+    /// Translation applied to the geometry along the x-axis.
+    pub x_translate: T,
+    /// Translation applied to the geometry along the y-axis.
+    pub y_translate: T,
+    /// Translation applied to the geometry along the z-axis.
+    pub z_translate: T,
+    /// Rotation applied to the geometry around the x-axis, in degrees.
+    pub x_rotate: T,
+    /// Rotation applied to the geometry around the y-axis, in degrees.
+    pub y_rotate: T,
+    /// Rotation applied to the geometry around the z-axis, in degrees.
+    pub z_rotate: T,
+    /// Uniform scaling applied to the geometry. The geometry is scaled, then
+    /// rotated around the x, y and z axes in that order, then translated.
+    pub scale: T,
     /// Lower bound on the x-axis of a brick-shaped geometry.
     pub x_min: T,
     /// Lower bound on the y-axis of a brick-shaped geometry.
@@ -112,11 +360,30 @@ impl<T: CustomFloat> GeometryParameters<T> {
         }
         for (key, val) in block {
             match key.as_ref() {
+                "name" => geometry_params.name = val,
                 "material" => geometry_params.material_name = val,
+                "density" => fetch_data!(density, val),
+                "densityProfile" => {
+                    geometry_params.density_profile = match val.as_ref() {
+                        "uniform" => DensityProfile::Uniform,
+                        "linear" => DensityProfile::Linear,
+                        "exponential" => DensityProfile::Exponential,
+                        _ => return Err(InputError::BadGeometryBlock),
+                    }
+                }
+                "densityGradientX" => fetch_data!(density_gradient_x, val),
+                "densityGradientY" => fetch_data!(density_gradient_y, val),
+                "densityGradientZ" => fetch_data!(density_gradient_z, val),
+                "importance" => fetch_data!(importance, val),
                 "shape" => {
                     geometry_params.shape = match val.as_ref() {
                         "brick" => Shape::Brick,
                         "sphere" => Shape::Sphere,
+                        "cylinder" => Shape::Cylinder,
+                        "cone" => Shape::Cone,
+                        "plane" => Shape::Plane,
+                        "ellipsoid" => Shape::Ellipsoid,
+                        "region" => Shape::Region,
                         _ => return Err(InputError::BadGeometryBlock),
                     }
                 }
@@ -124,6 +391,36 @@ impl<T: CustomFloat> GeometryParameters<T> {
                 "xCenter" => fetch_data!(x_center, val),
                 "yCenter" => fetch_data!(y_center, val),
                 "zCenter" => fetch_data!(z_center, val),
+                "axis" => {
+                    let (zero, one) = (T::zero(), T::one());
+                    let axis = match val.as_ref() {
+                        "x" => (one, zero, zero),
+                        "y" => (zero, one, zero),
+                        "z" => (zero, zero, one),
+                        _ => return Err(InputError::BadGeometryBlock),
+                    };
+                    geometry_params.x_axis = axis.0;
+                    geometry_params.y_axis = axis.1;
+                    geometry_params.z_axis = axis.2;
+                }
+                "xAxis" => fetch_data!(x_axis, val),
+                "yAxis" => fetch_data!(y_axis, val),
+                "zAxis" => fetch_data!(z_axis, val),
+                "height" => fetch_data!(height, val),
+                "xRadius" => fetch_data!(x_radius, val),
+                "yRadius" => fetch_data!(y_radius, val),
+                "zRadius" => fetch_data!(z_radius, val),
+                "region" => match CsgExpression::parse(&val) {
+                    Some(expr) => geometry_params.region = Some(expr),
+                    None => return Err(InputError::BadGeometryBlock),
+                },
+                "xTranslate" => fetch_data!(x_translate, val),
+                "yTranslate" => fetch_data!(y_translate, val),
+                "zTranslate" => fetch_data!(z_translate, val),
+                "xRotate" => fetch_data!(x_rotate, val),
+                "yRotate" => fetch_data!(y_rotate, val),
+                "zRotate" => fetch_data!(z_rotate, val),
+                "scale" => fetch_data!(scale, val),
                 "xMin" => fetch_data!(x_min, val),
                 "yMin" => fetch_data!(y_min, val),
                 "zMin" => fetch_data!(z_min, val),
@@ -133,9 +430,169 @@ impl<T: CustomFloat> GeometryParameters<T> {
                 _ => return Err(InputError::BadGeometryBlock),
             }
         }
+        if (geometry_params.density < T::zero()) | (geometry_params.importance < T::zero()) {
+            return Err(InputError::BadGeometryBlock);
+        }
+        let valid_shape = match geometry_params.shape {
+            Shape::Cylinder => {
+                (geometry_params.axis().length() > T::zero())
+                    & (geometry_params.radius > T::zero())
+                    & (geometry_params.height > T::zero())
+            }
+            Shape::Cone => {
+                (geometry_params.axis().length() > T::zero())
+                    & (geometry_params.radius > T::zero())
+                    & (geometry_params.height > T::zero())
+                    & (geometry_params.height < T::huge_float())
+            }
+            Shape::Plane => geometry_params.axis().length() > T::zero(),
+            Shape::Region => geometry_params.region.is_some(),
+            Shape::Ellipsoid => {
+                (geometry_params.x_radius > T::zero())
+                    & (geometry_params.y_radius > T::zero())
+                    & (geometry_params.z_radius > T::zero())
+            }
+            _ => true,
+        };
+        if !valid_shape | (geometry_params.scale <= T::zero()) {
+            return Err(InputError::BadGeometryBlock);
+        }
 
         Ok(geometry_params)
     }
+
+    /// Returns the center of the geometry.
+    pub fn center(&self) -> MCVector<T> {
+        MCVector {
+            x: self.x_center,
+            y: self.y_center,
+            z: self.z_center,
+        }
+    }
+
+    /// Returns the axis of the geometry, as specified in the input.
+    pub fn axis(&self) -> MCVector<T> {
+        MCVector {
+            x: self.x_axis,
+            y: self.y_axis,
+            z: self.z_axis,
+        }
+    }
+
+    /// Returns the analytic volume of the geometry, taking into account its
+    /// scaling. Returns `None` for unbounded geometries and regions.
+    pub fn volume(&self) -> Option<T> {
+        let pi: T = T::pi();
+        let third: T = T::one() / T::from_f64(3.0).unwrap();
+        let volume = match self.shape {
+            Shape::Brick => {
+                (self.x_max - self.x_min) * (self.y_max - self.y_min) * (self.z_max - self.z_min)
+            }
+            Shape::Sphere => T::from_f64(4.0).unwrap() * third * pi * self.radius.powi(3),
+            Shape::Cylinder if self.height >= T::huge_float() => return None,
+            Shape::Cylinder => pi * self.radius * self.radius * self.height,
+            Shape::Cone => third * pi * self.radius * self.radius * self.height,
+            Shape::Ellipsoid => {
+                T::from_f64(4.0).unwrap()
+                    * third
+                    * pi
+                    * self.x_radius
+                    * self.y_radius
+                    * self.z_radius
+            }
+            Shape::Plane | Shape::Region | Shape::Undefined => return None,
+        };
+        Some(volume * self.scale.powi(3))
+    }
+
+    /// Returns the coordinates in the frame of the untransformed geometry,
+    /// i.e. applies the inverse of the geometry's transformation.
+    pub fn to_local(&self, rr: &MCVector<T>) -> MCVector<T> {
+        let pi: T = T::pi();
+        let to_rad: T = pi / T::from_f64(180.0).unwrap();
+        let translation = MCVector {
+            x: self.x_translate,
+            y: self.y_translate,
+            z: self.z_translate,
+        };
+        let mut local = *rr - translation;
+        // undo rotations in reverse order
+        let (sin, cos) = (-self.z_rotate * to_rad).sin_cos();
+        local = MCVector {
+            x: cos * local.x - sin * local.y,
+            y: sin * local.x + cos * local.y,
+            z: local.z,
+        };
+        let (sin, cos) = (-self.y_rotate * to_rad).sin_cos();
+        local = MCVector {
+            x: cos * local.x + sin * local.z,
+            y: local.y,
+            z: -sin * local.x + cos * local.z,
+        };
+        let (sin, cos) = (-self.x_rotate * to_rad).sin_cos();
+        local = MCVector {
+            x: local.x,
+            y: cos * local.y - sin * local.z,
+            z: sin * local.y + cos * local.z,
+        };
+        local * (T::one() / self.scale)
+    }
+
+    /// Returns the relative number density of the geometry at the given
+    /// coordinates, according to its [DensityProfile].
+    pub fn density_at(&self, rr: &MCVector<T>) -> T {
+        let gradient: MCVector<T> = MCVector {
+            x: self.density_gradient_x,
+            y: self.density_gradient_y,
+            z: self.density_gradient_z,
+        };
+        match self.density_profile {
+            DensityProfile::Uniform => self.density,
+            DensityProfile::Linear => (self.density + gradient.dot(rr)).max(T::zero()),
+            DensityProfile::Exponential => self.density * gradient.dot(rr).exp(),
+        }
+    }
+}
+
+impl<T: CustomFloat> Default for GeometryParameters<T> {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            material_name: Default::default(),
+            density: T::one(),
+            density_profile: Default::default(),
+            importance: T::one(),
+            density_gradient_x: Default::default(),
+            density_gradient_y: Default::default(),
+            density_gradient_z: Default::default(),
+            shape: Default::default(),
+            radius: Default::default(),
+            x_center: Default::default(),
+            y_center: Default::default(),
+            z_center: Default::default(),
+            x_axis: Default::default(),
+            y_axis: Default::default(),
+            z_axis: T::one(),
+            height: T::huge_float(),
+            x_radius: Default::default(),
+            y_radius: Default::default(),
+            z_radius: Default::default(),
+            region: None,
+            x_translate: Default::default(),
+            y_translate: Default::default(),
+            z_translate: Default::default(),
+            x_rotate: Default::default(),
+            y_rotate: Default::default(),
+            z_rotate: Default::default(),
+            scale: T::one(),
+            x_min: Default::default(),
+            y_min: Default::default(),
+            z_min: Default::default(),
+            x_max: Default::default(),
+            y_max: Default::default(),
+            z_max: Default::default(),
+        }
+    }
 }
 
 /// Struct used to describe a material, i.e. its name and relevant physical
@@ -166,6 +623,14 @@ pub struct MaterialParameters<T: CustomFloat> {
     pub absorbtion_cross_section_ratio: T,
     /// Fission reaction cross-section ratio i.e. its relative weight.
     pub fission_cross_section_ratio: T,
+    /// Names of the isotopes making up the material. If this list is empty,
+    /// the material is made of `n_isotopes` identical isotopes built from the
+    /// cross-section fields above.
+    pub isotopes: Vec<String>,
+    /// Atom fractions of the isotopes, in the same order as `isotopes`. The
+    /// values are normalized at initialization. If this list is empty, all
+    /// isotopes are equally prevalent.
+    pub atom_fractions: Vec<T>,
 }
 
 impl<T: CustomFloat> MaterialParameters<T> {
@@ -202,6 +667,17 @@ impl<T: CustomFloat> MaterialParameters<T> {
                 "scatteringCrossSectionRatio" => fetch_data!(scattering_cross_section_ratio, val),
                 "absorptionCrossSectionRatio" => fetch_data!(absorbtion_cross_section_ratio, val),
                 "fissionCrossSectionRatio" => fetch_data!(fission_cross_section_ratio, val),
+                "isotopes" => {
+                    material_params.isotopes = split_list(&val).map(String::from).collect()
+                }
+                "atomFractions" => {
+                    for fraction in split_list(&val) {
+                        match fraction.parse() {
+                            Ok(v) => material_params.atom_fractions.push(v),
+                            Err(_) => return Err(InputError::BadMaterialBlock),
+                        }
+                    }
+                }
                 _ => return Err(InputError::BadMaterialBlock),
             }
         }
@@ -225,6 +701,172 @@ impl<T: CustomFloat> Default for MaterialParameters<T> {
             scattering_cross_section_ratio: T::one(),
             absorbtion_cross_section_ratio: T::one(),
             fission_cross_section_ratio: T::one(),
+            isotopes: Vec::new(),
+            atom_fractions: Vec::new(),
+        }
+    }
+}
+
+/// Structure used to describe an isotope, i.e. its name, atomic mass and
+/// the set of reactions it can undergo.
+///
+/// A reaction type is only available if its cross-section name is specified.
+#[derive(Debug)]
+pub struct IsotopeParameters<T: CustomFloat> {
+    /// Name of the isotope.
+    pub name: String,
+    /// Atomic mass of the isotope, in neutron masses.
+    pub mass: T,
+    /// Total value of the cross-section.
+    pub total_cross_section: T,
+    /// Number of reactions.
+    pub n_reactions: usize,
+    /// Scattering reaction cross-section name.
+    pub scattering_cross_section: String,
+    /// Absorption reaction cross-section name.
+    pub absorption_cross_section: String,
+    /// Fission reaction cross-section name.
+    pub fission_cross_section: String,
+    /// Scattering reaction cross-section ratio i.e. its relative weight.
+    pub scattering_cross_section_ratio: T,
+    /// Absorption reaction cross-section ratio i.e. its relative weight.
+    pub absorption_cross_section_ratio: T,
+    /// Fission reaction cross-section ratio i.e. its relative weight.
+    pub fission_cross_section_ratio: T,
+    /// Total value of the photon cross-section.
+    pub photon_cross_section: T,
+    /// Photon scattering reaction cross-section name. Photons are scattered
+    /// following the Klein-Nishina distribution.
+    pub photon_scattering_cross_section: String,
+    /// Photon absorption reaction cross-section name.
+    pub photon_absorption_cross_section: String,
+    /// Photon scattering reaction cross-section ratio i.e. its relative weight.
+    pub photon_scattering_cross_section_ratio: T,
+    /// Photon absorption reaction cross-section ratio i.e. its relative weight.
+    pub photon_absorption_cross_section_ratio: T,
+    /// Average number of photons emitted on a neutron capture.
+    pub capture_photon_yield: T,
+    /// Energy of the photons emitted on a neutron capture, in MeV.
+    pub capture_photon_energy: T,
+}
+
+impl<T: CustomFloat> IsotopeParameters<T> {
+    /// Creates a [IsotopeParameters] object using the [Block] passed as
+    /// argument. Any field not specified in the block will have its default
+    /// value as defined in the [Default] implementation. May return an error
+    /// if the block isn't a proper Isotope block, i.e.:
+    /// - There is an unknown field.
+    /// - A value associated to a valid field is invalid
+    /// In that case, the [IsotopeParameters] object is scrapped instead of
+    /// being returned as incomplete or potentially erroneous.
+    pub fn from_block(block: Block) -> Result<Self, InputError> {
+        let mut isotope_params = Self::default();
+
+        macro_rules! fetch_data {
+            ($f: ident, $v: expr) => {
+                isotope_params.$f = match $v.parse() {
+                    Ok(v) => v,
+                    Err(_) => return Err(InputError::BadIsotopeBlock),
+                }
+            };
+        }
+        for (key, val) in block {
+            match key.as_ref() {
+                "name" => isotope_params.name = val,
+                "mass" => fetch_data!(mass, val),
+                "totalCrossSection" => fetch_data!(total_cross_section, val),
+                "nReactions" => fetch_data!(n_reactions, val),
+                "scatteringCrossSection" => fetch_data!(scattering_cross_section, val),
+                "absorptionCrossSection" => fetch_data!(absorption_cross_section, val),
+                "fissionCrossSection" => fetch_data!(fission_cross_section, val),
+                "scatteringCrossSectionRatio" => fetch_data!(scattering_cross_section_ratio, val),
+                "absorptionCrossSectionRatio" => fetch_data!(absorption_cross_section_ratio, val),
+                "fissionCrossSectionRatio" => fetch_data!(fission_cross_section_ratio, val),
+                "photonCrossSection" => fetch_data!(photon_cross_section, val),
+                "photonScatteringCrossSection" => {
+                    fetch_data!(photon_scattering_cross_section, val)
+                }
+                "photonAbsorptionCrossSection" => {
+                    fetch_data!(photon_absorption_cross_section, val)
+                }
+                "photonScatteringCrossSectionRatio" => {
+                    fetch_data!(photon_scattering_cross_section_ratio, val)
+                }
+                "photonAbsorptionCrossSectionRatio" => {
+                    fetch_data!(photon_absorption_cross_section_ratio, val)
+                }
+                "capturePhotonYield" => fetch_data!(capture_photon_yield, val),
+                "capturePhotonEnergy" => fetch_data!(capture_photon_energy, val),
+                _ => return Err(InputError::BadIsotopeBlock),
+            }
+        }
+
+        if isotope_params.n_reactions == 0 {
+            return Err(InputError::BadIsotopeBlock);
+        }
+        if (isotope_params.capture_photon_yield < T::zero())
+            | (isotope_params.capture_photon_energy <= T::zero())
+        {
+            return Err(InputError::BadIsotopeBlock);
+        }
+
+        Ok(isotope_params)
+    }
+
+    /// Returns the names of the cross-sections referenced by the isotope.
+    pub fn cross_section_names(&self) -> impl Iterator<Item = &String> {
+        [
+            &self.scattering_cross_section,
+            &self.absorption_cross_section,
+            &self.fission_cross_section,
+            &self.photon_scattering_cross_section,
+            &self.photon_absorption_cross_section,
+        ]
+        .into_iter()
+        .filter(|name| !name.is_empty())
+    }
+}
+
+impl<T: CustomFloat> Default for IsotopeParameters<T> {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            mass: T::from_f64(1000.0).unwrap(),
+            total_cross_section: T::one(),
+            n_reactions: 3,
+            scattering_cross_section: Default::default(),
+            absorption_cross_section: Default::default(),
+            fission_cross_section: Default::default(),
+            scattering_cross_section_ratio: T::one(),
+            absorption_cross_section_ratio: T::one(),
+            fission_cross_section_ratio: T::one(),
+            photon_cross_section: T::one(),
+            photon_scattering_cross_section: Default::default(),
+            photon_absorption_cross_section: Default::default(),
+            photon_scattering_cross_section_ratio: T::one(),
+            photon_absorption_cross_section_ratio: T::one(),
+            capture_photon_yield: T::zero(),
+            capture_photon_energy: T::from_f64(2.0).unwrap(),
+        }
+    }
+}
+
+/// Builds the isotope used by materials that do not list explicit isotopes,
+/// i.e. an isotope carrying the material's mass and reaction set.
+impl<T: CustomFloat> From<&MaterialParameters<T>> for IsotopeParameters<T> {
+    fn from(mp: &MaterialParameters<T>) -> Self {
+        Self {
+            name: mp.name.to_owned(),
+            mass: mp.mass,
+            total_cross_section: mp.total_cross_section,
+            n_reactions: mp.n_reactions,
+            scattering_cross_section: mp.scattering_cross_section.to_owned(),
+            absorption_cross_section: mp.absorption_cross_section.to_owned(),
+            fission_cross_section: mp.fission_cross_section.to_owned(),
+            scattering_cross_section_ratio: mp.scattering_cross_section_ratio,
+            absorption_cross_section_ratio: mp.absorbtion_cross_section_ratio,
+            fission_cross_section_ratio: mp.fission_cross_section_ratio,
+            ..Default::default()
         }
     }
 }
@@ -233,7 +875,9 @@ impl<T: CustomFloat> Default for MaterialParameters<T> {
 /// representation.
 ///
 /// The probability density functions are represented using degree 4 polynomial
-/// functions.
+/// functions, or using pointwise data read from a table file. In both cases,
+/// the resulting group-wise values are normalized so that the reaction's
+/// cross-section value is reached at 1 MeV.
 #[derive(Debug)]
 pub struct CrossSectionParameters<T: CustomFloat> {
     /// Name of the cross-section.
@@ -248,8 +892,43 @@ pub struct CrossSectionParameters<T: CustomFloat> {
     pub dd: T,
     /// Degree 0 coefficient of the polynomial function.
     pub ee: T,
-    /// Normalization value?
+    /// Average number of particles produced by a fission. This value is used
+    /// if the multiplicity isn't energy-dependent.
     pub nu_bar: T,
+    /// Coefficients of an energy-dependent nu bar, by increasing degree. The
+    /// resulting polynomial function is evaluated at the incident energy (MeV).
+    pub nu_bar_polynomial: Vec<T>,
+    /// Path to a file holding a tabulated energy-dependent nu bar. A relative
+    /// path is resolved from the directory of the input file.
+    pub nu_bar_file: String,
+    /// Energy grid of the tabulated nu bar, in MeV.
+    pub nu_bar_energies: Vec<T>,
+    /// Values of the tabulated nu bar on the energy grid.
+    pub nu_bar_values: Vec<T>,
+    /// Energy distribution of the particles produced by fission.
+    pub fission_spectrum: FissionSpectrumType,
+    /// Temperature of the Maxwellian spectrum, in MeV.
+    pub maxwell_temperature: T,
+    /// `a` parameter of the Watt spectrum, in MeV.
+    pub watt_a: T,
+    /// `b` parameter of the Watt spectrum, in MeV⁻¹.
+    pub watt_b: T,
+    /// Model used to sample scattered particles.
+    pub scattering_model: ScatteringModel,
+    /// Legendre moments `a_1, a_2, ...` of the centre-of-mass scattering cosine
+    /// distribution, `a_0` being one. Only used with an elastic scattering model;
+    /// if empty, the scattering is isotropic in the centre-of-mass frame.
+    pub legendre_coefficients: Vec<T>,
+    /// Path to a file holding a tabulated cross-section. If specified, the
+    /// polynomial coefficients are ignored. A relative path is resolved from
+    /// the directory of the input file.
+    pub table_file: String,
+    /// Method used to collapse the tabulated data onto the energy groups.
+    pub averaging: CrossSectionAveraging,
+    /// Energy grid of the tabulated data, in MeV.
+    pub table_energies: Vec<T>,
+    /// Values of the tabulated data on the energy grid.
+    pub table_values: Vec<T>,
 }
 
 impl<T: CustomFloat> CrossSectionParameters<T> {
@@ -282,11 +961,93 @@ impl<T: CustomFloat> CrossSectionParameters<T> {
                 "D" => fetch_data!(dd, val),
                 "E" => fetch_data!(ee, val),
                 "nuBar" => fetch_data!(nu_bar, val),
+                "file" => cross_section.table_file = val,
+                "averaging" => {
+                    cross_section.averaging = match val.as_ref() {
+                        "flux" => CrossSectionAveraging::FluxWeighted,
+                        "interpolation" => CrossSectionAveraging::Interpolated,
+                        _ => return Err(InputError::BadCrossSectionBlock),
+                    }
+                }
+                "nuBarPolynomial" => {
+                    for coefficient in split_list(&val) {
+                        match coefficient.parse() {
+                            Ok(v) => cross_section.nu_bar_polynomial.push(v),
+                            Err(_) => return Err(InputError::BadCrossSectionBlock),
+                        }
+                    }
+                    // same degree as cross-section polynomials
+                    if cross_section.nu_bar_polynomial.len() > 5 {
+                        return Err(InputError::BadCrossSectionBlock);
+                    }
+                }
+                "nuBarFile" => cross_section.nu_bar_file = val,
+                "fissionSpectrum" => {
+                    cross_section.fission_spectrum = match val.as_ref() {
+                        "legacy" => FissionSpectrumType::Legacy,
+                        "maxwell" => FissionSpectrumType::Maxwellian,
+                        "watt" => FissionSpectrumType::Watt,
+                        _ => return Err(InputError::BadCrossSectionBlock),
+                    }
+                }
+                "maxwellTemperature" => fetch_data!(maxwell_temperature, val),
+                "wattA" => fetch_data!(watt_a, val),
+                "wattB" => fetch_data!(watt_b, val),
+                "scatteringModel" => {
+                    cross_section.scattering_model = match val.as_ref() {
+                        "legacy" => ScatteringModel::Legacy,
+                        "elastic" => ScatteringModel::Elastic,
+                        _ => return Err(InputError::BadCrossSectionBlock),
+                    }
+                }
+                "legendreCoefficients" => {
+                    for coefficient in split_list(&val) {
+                        match coefficient.parse() {
+                            Ok(v) => cross_section.legendre_coefficients.push(v),
+                            Err(_) => return Err(InputError::BadCrossSectionBlock),
+                        }
+                    }
+                }
                 _ => return Err(InputError::BadCrossSectionBlock),
             }
         }
+
+        // anisotropy is only modelled by the elastic scattering model
+        if !cross_section.legendre_coefficients.is_empty()
+            & (cross_section.scattering_model != ScatteringModel::Elastic)
+        {
+            return Err(InputError::BadCrossSectionBlock);
+        }
+
         Ok(cross_section)
     }
+
+    /// Reads the tabulated data referenced by the cross-section, if any, i.e.
+    /// the cross-section itself and its nu bar. A relative path is resolved
+    /// from the directory passed as argument. See [read_table] for the expected
+    /// format. A cross-section table holding only zeros cannot be normalized
+    /// and is rejected.
+    pub fn load_table(&mut self, directory: &Path) -> Result<(), InputError> {
+        if !self.table_file.is_empty() {
+            let (energies, values) = read_table(directory.join(&self.table_file))?;
+            if values.iter().all(|v: &T| v.is_zero()) {
+                return Err(InputError::ZeroCrossSectionTable);
+            }
+            self.table_energies = energies;
+            self.table_values = values;
+        }
+        if !self.nu_bar_file.is_empty() {
+            let (energies, values) = read_table(directory.join(&self.nu_bar_file))?;
+            self.nu_bar_energies = energies;
+            self.nu_bar_values = values;
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the cross-section is described by tabulated data.
+    pub fn is_tabulated(&self) -> bool {
+        !self.table_energies.is_empty()
+    }
 }
 
 impl<T: CustomFloat> Default for CrossSectionParameters<T> {
@@ -299,6 +1060,21 @@ impl<T: CustomFloat> Default for CrossSectionParameters<T> {
             dd: Default::default(),
             ee: T::one(),
             nu_bar: T::from_f32(2.4).unwrap(),
+            nu_bar_polynomial: Vec::new(),
+            nu_bar_file: Default::default(),
+            nu_bar_energies: Vec::new(),
+            nu_bar_values: Vec::new(),
+            fission_spectrum: FissionSpectrumType::Legacy,
+            // U-235 thermal fission values
+            maxwell_temperature: T::from_f64(1.2895).unwrap(),
+            watt_a: T::from_f64(0.988).unwrap(),
+            watt_b: T::from_f64(2.249).unwrap(),
+            scattering_model: ScatteringModel::Legacy,
+            legendre_coefficients: Vec::new(),
+            table_file: Default::default(),
+            averaging: Default::default(),
+            table_energies: Vec::new(),
+            table_values: Vec::new(),
         }
     }
 }
@@ -307,7 +1083,7 @@ impl<T: CustomFloat> Default for CrossSectionParameters<T> {
 ///
 /// In the program's execution flow, it is first initialized using
 /// the CLI arguments, then optionally updated with a specified input file.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct SimulationParameters<T: CustomFloat> {
     /// Path to the input file, it can be relative or absolute.
     pub input_file: String,
@@ -315,8 +1091,46 @@ pub struct SimulationParameters<T: CustomFloat> {
     pub energy_spectrum: String,
     /// Name of the output file the cross-sections may be saved to.
     pub cross_sections_out: String,
+    /// Name of the output file the mesh, cell properties & cell tallies may be
+    /// saved to, using the legacy VTK format.
+    pub vtk_out: String,
+    /// Number of cycles between two intermediate VTK outputs. If zero, the
+    /// VTK file is only written at the end of the simulation.
+    pub vtk_frequency: usize,
+    /// Name of the output file the recorded particle tracks may be saved to.
+    /// See [`ParticleTracks`][crate::data::tracks::ParticleTracks] for more
+    /// information.
+    pub track_out: String,
+    /// Identifiers of the particles whose tracks are recorded.
+    pub track_particles: Vec<u64>,
+    /// Number of histories recorded at each cycle, in addition to the ones
+    /// of [Self::track_particles].
+    pub track_histories: usize,
+    /// Switch used to also write the tracks as VTK polylines.
+    pub track_vtk: bool,
+    /// Name of the output file the run report may be saved to. See
+    /// [`RunReport`][crate::utils::report::RunReport] for more information.
+    pub json_out: String,
+    /// Name of the output file the per-thread timing trace may be saved to.
+    /// See [`Tracer`][crate::utils::trace::Tracer] for more information.
+    pub trace_out: String,
+    /// Directory the output files are written to. See [`crate::utils::output`]
+    /// for the supported placeholders.
+    pub output_dir: String,
+    /// Prefix of the output files. See [`crate::utils::output`] for the
+    /// supported placeholders.
+    pub run_name: String,
+    /// Switch used to replace existing output files.
+    pub overwrite: bool,
+    /// Interval in seconds between two progress reports during tracking. If
+    /// zero, progress is not reported. See
+    /// [`Progress`][crate::utils::progress::Progress] for more information.
+    pub progress_interval: f64,
     /// Boundary conditions of the problem. Mesh is initialized according to this value.
     pub boundary_condition: String,
+    /// Boundary conditions of each face of the problem, ordered like [BoundaryFace].
+    /// Empty values default to [Self::boundary_condition].
+    pub face_boundary_conditions: [String; 6],
     /// Switch to enable or disable load balancing during execution.
     pub load_balance: bool,
     /// Switch to enable writing of tallies & timers data in `csv` files.
@@ -342,6 +1156,20 @@ pub struct SimulationParameters<T: CustomFloat> {
     pub ny: usize,
     /// Number of cells along the z-axis.
     pub nz: usize,
+    /// Positions of the mesh planes along the x-axis, starting at zero. If
+    /// specified, they define both `nx` and `lx`; the mesh is uniform otherwise.
+    pub x_planes: Vec<T>,
+    /// Positions of the mesh planes along the y-axis. See [Self::x_planes].
+    pub y_planes: Vec<T>,
+    /// Positions of the mesh planes along the z-axis. See [Self::x_planes].
+    pub z_planes: Vec<T>,
+    /// Number of sub-samples per axis used to estimate the material volume
+    /// fractions of each cell. If zero, cells are filled with the material
+    /// found at their center. Otherwise, partially filled cells are filled
+    /// with a mixture of their materials, weighted by volume fraction and
+    /// density, and the volumes of the geometries are compared to their
+    /// analytic values.
+    pub volume_samples: usize,
     /// Random number seed for the PRNG used by the simulation.
     pub seed: u64,
     /// Value of the time step in seconds.
@@ -358,8 +1186,27 @@ pub struct SimulationParameters<T: CustomFloat> {
     pub e_max: T,
     /// Number of energy groups to build a spectrum.
     pub n_groups: usize,
+    /// Energy group structure. See [GroupStructure] for more information.
+    pub group_structure: GroupStructure,
+    /// Explicit energy group boundaries, in MeV. Only used with an
+    /// [GroupStructure::Explicit] structure.
+    pub group_boundaries: Vec<T>,
     /// Low statistical weight cutoff used for population control.
     pub low_weight_cutoff: T,
+    /// Switch to replace analog absorption by implicit capture: particles are
+    /// never absorbed, their weight is reduced by the absorption probability
+    /// instead. Particles below the low weight cutoff play russian roulette.
+    /// The switch is set if either the input file or the command line sets
+    /// it.
+    pub implicit_capture: bool,
+    /// Path to a file holding weight windows. A relative path is resolved
+    /// from the directory of the input file. No windows are used if empty.
+    pub weight_window_file: String,
+    /// Weight windows of the problem, read from [Self::weight_window_file].
+    /// See [WeightWindowParameters] for more information. Not included in
+    /// run reports.
+    #[serde(skip)]
+    pub weight_windows: Vec<WeightWindowParameters<T>>,
     /// Benchmark type of the input problem. See [BenchType] for more information.
     pub coral_benchmark: BenchType,
 }
@@ -403,6 +1250,14 @@ impl<T: CustomFloat> SimulationParameters<T> {
         fetch_from_cli!(input_file);
         fetch_from_cli!(energy_spectrum);
         fetch_from_cli!(cross_sections_out);
+        fetch_from_cli!(vtk_out);
+        fetch_from_cli!(track_out);
+        fetch_from_cli!(json_out);
+        fetch_from_cli!(trace_out);
+        fetch_from_cli!(output_dir);
+        fetch_from_cli!(run_name);
+        simulation_params.overwrite = cli.overwrite;
+        fetch_from_cli!(progress_interval);
         fetch_t_from_cli!(dt);
         simulation_params.load_balance = cli.load_balance;
         simulation_params.csv = cli.csv;
@@ -420,9 +1275,71 @@ impl<T: CustomFloat> SimulationParameters<T> {
         fetch_from_cli!(ny);
         fetch_from_cli!(nz);
         fetch_from_cli!(seed);
+        simulation_params.implicit_capture = cli.implicit_capture;
 
         simulation_params
     }
+
+    /// Returns the boundaries of the energy groups, in MeV, according to the
+    /// group structure of the problem. Named structures are bounded by the
+    /// energy bounds of the problem; their inner boundaries lying outside of
+    /// these bounds are ignored.
+    pub fn energy_boundaries(&self) -> Vec<T> {
+        let inner: &[f64] = match self.group_structure {
+            GroupStructure::LogUniform => {
+                return log_uniform_boundaries(self.n_groups, self.e_min, self.e_max)
+            }
+            GroupStructure::Explicit => return self.group_boundaries.clone(),
+            GroupStructure::Casmo2 => &groups::CASMO_2,
+            GroupStructure::Casmo4 => &groups::CASMO_4,
+        };
+        let mut boundaries = vec![self.e_min];
+        boundaries.extend(
+            inner
+                .iter()
+                .map(|b| T::from_f64(*b).unwrap())
+                .filter(|b| (*b > self.e_min) & (*b < self.e_max)),
+        );
+        boundaries.push(self.e_max);
+        boundaries
+    }
+
+    /// Reads the weight windows referenced by the parameters, if any. A relative
+    /// path is resolved from the directory passed as argument. See
+    /// [read_weight_windows] for the expected format.
+    pub fn load_weight_windows(&mut self, directory: &Path) -> Result<(), InputError> {
+        if !self.weight_window_file.is_empty() {
+            self.weight_windows = read_weight_windows(directory.join(&self.weight_window_file))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the boundary condition of each face of the problem, ordered like
+    /// [BoundaryFace]. The `octant` condition reflects particles on the faces
+    /// containing the origin and lets them escape through the others.
+    pub fn boundary_conditions(&self) -> [&str; 6] {
+        let mut conditions: [&str; 6] = match self.boundary_condition.as_ref() {
+            "octant" => [
+                "reflect", "escape", "reflect", "escape", "reflect", "escape",
+            ],
+            bc => [bc; 6],
+        };
+        conditions
+            .iter_mut()
+            .zip(self.face_boundary_conditions.iter())
+            .filter(|(_, face_bc)| !face_bc.is_empty())
+            .for_each(|(bc, face_bc)| *bc = face_bc);
+        conditions
+    }
+
+    /// Updates the number of groups and the energy bounds of the problem so
+    /// that they are consistent with its group structure.
+    pub fn update_energy_groups(&mut self) {
+        let boundaries = self.energy_boundaries();
+        self.n_groups = boundaries.len() - 1;
+        self.e_min = boundaries[0];
+        self.e_max = boundaries[self.n_groups];
+    }
 }
 
 impl<T: CustomFloat> Default for SimulationParameters<T> {
@@ -431,7 +1348,20 @@ impl<T: CustomFloat> Default for SimulationParameters<T> {
             input_file: Default::default(),
             energy_spectrum: "".to_string(),
             cross_sections_out: "".to_string(),
+            vtk_out: "".to_string(),
+            vtk_frequency: 0,
+            track_out: "".to_string(),
+            track_particles: Vec::new(),
+            track_histories: 0,
+            track_vtk: false,
+            json_out: "".to_string(),
+            trace_out: "".to_string(),
+            output_dir: "".to_string(),
+            run_name: "".to_string(),
+            overwrite: false,
+            progress_interval: 0.0,
             boundary_condition: "reflect".to_string(),
+            face_boundary_conditions: Default::default(),
             load_balance: false,
             csv: false,
             debug_threads: false,
@@ -444,6 +1374,10 @@ impl<T: CustomFloat> Default for SimulationParameters<T> {
             nx: 10,
             ny: 10,
             nz: 10,
+            x_planes: Vec::new(),
+            y_planes: Vec::new(),
+            z_planes: Vec::new(),
+            volume_samples: 0,
             seed: 1029384756,
             dt: T::from_f64(1e-8).unwrap(),
             lx: T::from_f64(100.0).unwrap(),
@@ -452,12 +1386,318 @@ impl<T: CustomFloat> Default for SimulationParameters<T> {
             e_min: T::from_f64(1e-9).unwrap(),
             e_max: T::from_f64(20.0).unwrap(),
             n_groups: 230,
+            group_structure: GroupStructure::LogUniform,
+            group_boundaries: Vec::new(),
             low_weight_cutoff: T::from_f64(0.001).unwrap(),
+            implicit_capture: false,
+            weight_window_file: "".to_string(),
+            weight_windows: Vec::new(),
             coral_benchmark: BenchType::Standard,
         }
     }
 }
 
+/// Structure used to describe a weight window, i.e. bounds on the statistical
+/// weight of particles in a set of cells and energy groups.
+///
+/// Bounds are relative to the weight of source particles after population
+/// control, i.e. the weight of a fresh particle. Particles above the
+/// upper bound are split while particles below the lower bound play russian
+/// roulette.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightWindowParameters<T: CustomFloat> {
+    /// Global index of the cell the window applies to. All cells if `None`.
+    pub cell: Option<usize>,
+    /// Energy group the window applies to. All groups if `None`.
+    pub group: Option<usize>,
+    /// Lower bound of the window.
+    pub lower: T,
+    /// Upper bound of the window.
+    pub upper: T,
+}
+
+/// Structure used to describe an external source, i.e. a source of
+/// particles independent of the materials of the problem.
+///
+/// The intensity of sources is expressed in particles per second, so
+/// that external sources and material sources can be mixed in the same
+/// problem according to their relative strength.
+#[derive(Debug)]
+pub struct SourceParameters<T: CustomFloat> {
+    /// Name of the source.
+    pub name: String,
+    /// Species of the emitted particles.
+    pub species: Species,
+    /// Intensity of the source, in particles per second.
+    pub strength: T,
+    /// Spatial distribution of the source. Note that this value defines which
+    /// other fields are used:
+    /// - A point source uses the coordinates of the center.
+    /// - A sphere source uses the radius and the coordinates of the center.
+    /// - A box source uses the bounds on the axes.
+    /// - A surface source uses the face.
+    pub shape: SourceShape,
+    /// Radius of a sphere source.
+    pub radius: T,
+    /// x-coordinate of the center of a point or sphere source.
+    pub x_center: T,
+    /// y-coordinate of the center of a point or sphere source.
+    pub y_center: T,
+    /// z-coordinate of the center of a point or sphere source.
+    pub z_center: T,
+    /// Lower bound on the x-axis of a box source.
+    pub x_min: T,
+    /// Lower bound on the y-axis of a box source.
+    pub y_min: T,
+    /// Lower bound on the z-axis of a box source.
+    pub z_min: T,
+    /// Upper bound on the x-axis of a box source.
+    pub x_max: T,
+    /// Upper bound on the y-axis of a box source.
+    pub y_max: T,
+    /// Upper bound on the z-axis of a box source.
+    pub z_max: T,
+    /// Boundary face of a surface source.
+    pub face: BoundaryFace,
+    /// Angular distribution of the emitted particles.
+    pub angular_distribution: AngularDistribution,
+    /// Direction of a mono-directional source, as a `(x, y, z)` vector. It
+    /// does not need to be normalized.
+    pub direction: Vec<T>,
+    /// Energy distribution of the emitted particles.
+    pub energy_distribution: EnergyDistribution,
+    /// Energy of a mono-energetic source, in MeV.
+    pub energy: T,
+    /// Lower bound of a uniform energy distribution, in MeV.
+    pub e_low: T,
+    /// Upper bound of a uniform energy distribution, in MeV. If the bounds
+    /// are left unspecified, the energy bounds of the problem are used.
+    pub e_high: T,
+    /// `a` parameter of the Watt spectrum, in MeV.
+    pub watt_a: T,
+    /// `b` parameter of the Watt spectrum, in MeV⁻¹.
+    pub watt_b: T,
+    /// Bin boundaries of a histogram energy distribution, in MeV.
+    pub histogram_boundaries: Vec<T>,
+    /// Relative weights of the bins of a histogram energy distribution.
+    pub histogram_weights: Vec<T>,
+    /// Time dependence of the intensity.
+    pub time_profile: TimeProfile,
+    /// Time at which the source starts emitting, in seconds.
+    pub t_start: T,
+    /// Time at which the source stops emitting, in seconds.
+    pub t_end: T,
+    /// Characteristic time of an exponential profile, in seconds.
+    pub decay_time: T,
+}
+
+impl<T: CustomFloat> SourceParameters<T> {
+    /// Creates a [SourceParameters] object using the [Block] passed as
+    /// argument. Any field not specified in the block will have its default
+    /// value as defined in the [Default] implementation. May return an error
+    /// if the block isn't a proper Source block, i.e.:
+    /// - There is an unknown field
+    /// - A value associated to a valid field is invalid
+    /// In that case, the [SourceParameters] object is scrapped instead of being
+    /// returned as incomplete or potentially erroneous.
+    pub fn from_block(block: Block) -> Result<Self, InputError> {
+        let mut source_params = Self::default();
+
+        macro_rules! fetch_data {
+            ($f: ident, $v: expr) => {
+                source_params.$f = match $v.parse() {
+                    Ok(v) => v,
+                    Err(_) => return Err(InputError::BadSourceBlock),
+                }
+            };
+        }
+        macro_rules! fetch_list {
+            ($f: ident, $v: expr) => {
+                for item in split_list(&$v) {
+                    match item.parse() {
+                        Ok(v) => source_params.$f.push(v),
+                        Err(_) => return Err(InputError::BadSourceBlock),
+                    }
+                }
+            };
+        }
+        for (key, val) in block {
+            match key.as_ref() {
+                "name" => source_params.name = val,
+                "species" => {
+                    source_params.species = match val.as_ref() {
+                        "neutron" => Species::Neutron,
+                        "photon" => Species::Photon,
+                        _ => return Err(InputError::BadSourceBlock),
+                    }
+                }
+                "strength" => fetch_data!(strength, val),
+                "shape" => {
+                    source_params.shape = match val.as_ref() {
+                        "point" => SourceShape::Point,
+                        "box" => SourceShape::Box,
+                        "sphere" => SourceShape::Sphere,
+                        "surface" => SourceShape::Surface,
+                        _ => return Err(InputError::BadSourceBlock),
+                    }
+                }
+                "radius" => fetch_data!(radius, val),
+                "xCenter" => fetch_data!(x_center, val),
+                "yCenter" => fetch_data!(y_center, val),
+                "zCenter" => fetch_data!(z_center, val),
+                "xMin" => fetch_data!(x_min, val),
+                "yMin" => fetch_data!(y_min, val),
+                "zMin" => fetch_data!(z_min, val),
+                "xMax" => fetch_data!(x_max, val),
+                "yMax" => fetch_data!(y_max, val),
+                "zMax" => fetch_data!(z_max, val),
+                "face" => {
+                    source_params.face = match val.as_ref() {
+                        "xMin" => BoundaryFace::XMin,
+                        "xMax" => BoundaryFace::XMax,
+                        "yMin" => BoundaryFace::YMin,
+                        "yMax" => BoundaryFace::YMax,
+                        "zMin" => BoundaryFace::ZMin,
+                        "zMax" => BoundaryFace::ZMax,
+                        _ => return Err(InputError::BadSourceBlock),
+                    }
+                }
+                "angularDistribution" => {
+                    source_params.angular_distribution = match val.as_ref() {
+                        "isotropic" => AngularDistribution::Isotropic,
+                        "mono" => AngularDistribution::Mono,
+                        "cosine" => AngularDistribution::Cosine,
+                        _ => return Err(InputError::BadSourceBlock),
+                    }
+                }
+                "direction" => fetch_list!(direction, val),
+                "energyDistribution" => {
+                    source_params.energy_distribution = match val.as_ref() {
+                        "uniform" => EnergyDistribution::Uniform,
+                        "mono" => EnergyDistribution::Mono,
+                        "watt" => EnergyDistribution::Watt,
+                        "histogram" => EnergyDistribution::Histogram,
+                        _ => return Err(InputError::BadSourceBlock),
+                    }
+                }
+                "energy" => fetch_data!(energy, val),
+                "eLow" => fetch_data!(e_low, val),
+                "eHigh" => fetch_data!(e_high, val),
+                "wattA" => fetch_data!(watt_a, val),
+                "wattB" => fetch_data!(watt_b, val),
+                "histogramBoundaries" => fetch_list!(histogram_boundaries, val),
+                "histogramWeights" => fetch_list!(histogram_weights, val),
+                "timeProfile" => {
+                    source_params.time_profile = match val.as_ref() {
+                        "constant" => TimeProfile::Constant,
+                        "exponential" => TimeProfile::Exponential,
+                        _ => return Err(InputError::BadSourceBlock),
+                    }
+                }
+                "tStart" => fetch_data!(t_start, val),
+                "tEnd" => fetch_data!(t_end, val),
+                "decayTime" => fetch_data!(decay_time, val),
+                _ => return Err(InputError::BadSourceBlock),
+            }
+        }
+
+        // checks that do not depend on the rest of the problem
+        let sp = &source_params;
+        let zero = T::zero();
+        let bad_direction = match sp.direction.len() {
+            // only surface sources have a default direction
+            0 => {
+                (sp.angular_distribution == AngularDistribution::Mono)
+                    & (sp.shape != SourceShape::Surface)
+            }
+            3 => sp.direction.iter().all(|d| *d == zero),
+            _ => true,
+        };
+        // cosine distributions are defined relative to a face's normal
+        let bad_angular = (sp.angular_distribution == AngularDistribution::Cosine)
+            & (sp.shape != SourceShape::Surface);
+        let bad_histogram = (sp.energy_distribution == EnergyDistribution::Histogram)
+            & ((sp.histogram_boundaries.len() != sp.histogram_weights.len() + 1)
+                | sp.histogram_boundaries
+                    .windows(2)
+                    .any(|pair| pair[0] >= pair[1])
+                | sp.histogram_weights.iter().any(|w| *w < zero)
+                | (sp.histogram_weights.iter().copied().sum::<T>() <= zero));
+        let bad_energy = match sp.energy_distribution {
+            EnergyDistribution::Mono => sp.energy <= zero,
+            EnergyDistribution::Uniform => (sp.e_low < zero) | (sp.e_high < sp.e_low),
+            EnergyDistribution::Watt => (sp.watt_a <= zero) | (sp.watt_b < zero),
+            EnergyDistribution::Histogram => bad_histogram,
+        };
+        let bad_time = (sp.t_end <= sp.t_start)
+            | ((sp.time_profile == TimeProfile::Exponential) & (sp.decay_time <= zero));
+        if bad_direction | bad_angular | bad_energy | bad_time | (sp.strength < zero) {
+            return Err(InputError::BadSourceBlock);
+        }
+
+        Ok(source_params)
+    }
+
+    /// Returns `true` if the source lies within a problem of size
+    /// `lx * ly * lz`, i.e. if it can emit particles in the problem.
+    pub fn is_inside(&self, lx: T, ly: T, lz: T) -> bool {
+        let zero = T::zero();
+        match self.shape {
+            SourceShape::Point | SourceShape::Sphere => {
+                let in_x = (self.x_center >= zero) & (self.x_center <= lx);
+                let in_y = (self.y_center >= zero) & (self.y_center <= ly);
+                let in_z = (self.z_center >= zero) & (self.z_center <= lz);
+                let radius_ok = (self.shape == SourceShape::Point) | (self.radius > zero);
+                in_x & in_y & in_z & radius_ok
+            }
+            SourceShape::Box => {
+                let in_x = (self.x_min < self.x_max) & (self.x_min < lx) & (self.x_max > zero);
+                let in_y = (self.y_min < self.y_max) & (self.y_min < ly) & (self.y_max > zero);
+                let in_z = (self.z_min < self.z_max) & (self.z_min < lz) & (self.z_max > zero);
+                in_x & in_y & in_z
+            }
+            SourceShape::Surface => true,
+        }
+    }
+}
+
+impl<T: CustomFloat> Default for SourceParameters<T> {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            species: Species::Neutron,
+            strength: T::one(),
+            shape: Default::default(),
+            radius: Default::default(),
+            x_center: Default::default(),
+            y_center: Default::default(),
+            z_center: Default::default(),
+            x_min: Default::default(),
+            y_min: Default::default(),
+            z_min: Default::default(),
+            x_max: Default::default(),
+            y_max: Default::default(),
+            z_max: Default::default(),
+            face: Default::default(),
+            angular_distribution: Default::default(),
+            direction: Vec::new(),
+            energy_distribution: Default::default(),
+            energy: Default::default(),
+            e_low: Default::default(),
+            e_high: Default::default(),
+            // U-235 thermal fission values
+            watt_a: T::from_f64(0.988).unwrap(),
+            watt_b: T::from_f64(2.249).unwrap(),
+            histogram_boundaries: Vec::new(),
+            histogram_weights: Vec::new(),
+            time_profile: Default::default(),
+            t_start: Default::default(),
+            t_end: T::huge_float(),
+            decay_time: Default::default(),
+        }
+    }
+}
+
 /// Structure holding all the problem's parameters.
 #[derive(Debug, Default)]
 pub struct Parameters<T: CustomFloat> {
@@ -467,8 +1707,12 @@ pub struct Parameters<T: CustomFloat> {
     pub geometry_params: Vec<GeometryParameters<T>>,
     /// Map of materials. See [MaterialParameters] for more.
     pub material_params: FxHashMap<String, MaterialParameters<T>>,
+    /// Map of isotopes. See [IsotopeParameters] for more.
+    pub isotope_params: FxHashMap<String, IsotopeParameters<T>>,
     /// Map of cross-sections. See [CrossSectionParameters] for more.
     pub cross_section_params: FxHashMap<String, CrossSectionParameters<T>>,
+    /// List of external sources. See [SourceParameters] for more.
+    pub source_params: Vec<SourceParameters<T>>,
 }
 
 impl<T: CustomFloat> Parameters<T> {
@@ -476,13 +1720,16 @@ impl<T: CustomFloat> Parameters<T> {
     /// structure and return it. The function will fail if:
     /// - it cannot read or find the specified input_file (if specified)
     /// - the resulting [Parameters] object is compromised
+    /// - an output file would be replaced without the `overwrite` switch
     pub fn get_parameters(cli: Cli) -> Result<Self, Vec<InputError>> {
         // structs init
         let mut params = Self {
             simulation_params: SimulationParameters::from_cli(&cli),
             geometry_params: Vec::new(),
             material_params: FxHashMap::default(),
+            isotope_params: FxHashMap::default(),
             cross_section_params: FxHashMap::default(),
+            source_params: Vec::new(),
         };
 
         if let Some(filename) = cli.input_file {
@@ -494,12 +1741,48 @@ impl<T: CustomFloat> Parameters<T> {
         if let Some(filename) = cli.cross_sections_out {
             params.simulation_params.cross_sections_out = filename
         };
+        if let Some(filename) = cli.vtk_out {
+            params.simulation_params.vtk_out = filename
+        };
+        if let Some(filename) = cli.track_out {
+            params.simulation_params.track_out = filename
+        };
+        if let Some(filename) = cli.json_out {
+            params.simulation_params.json_out = filename
+        };
+        if let Some(filename) = cli.trace_out {
+            params.simulation_params.trace_out = filename
+        };
+        if let Some(dirname) = cli.output_dir {
+            params.simulation_params.output_dir = dirname
+        };
+        if let Some(name) = cli.run_name {
+            params.simulation_params.run_name = name
+        };
+        params.simulation_params.overwrite |= cli.overwrite;
+        params.simulation_params.implicit_capture |= cli.implicit_capture;
+        if let Some(interval) = cli.progress_interval {
+            params.simulation_params.progress_interval = interval
+        };
 
         params.supply_defaults();
         if let Err(e) = params.check_parameters_integrity() {
-            println!("{e:?}");
+            log::error!("{e:?}");
             return Err(vec![InputError::BadInputFile]);
         };
+        params.simulation_params.update_energy_groups();
+
+        output::expand_output_names(&mut params.simulation_params);
+        let existing = output::existing_outputs(&params.simulation_params);
+        if !existing.is_empty() {
+            existing.iter().for_each(|path| {
+                log::error!(
+                    "{} already exists, use --overwrite to replace it",
+                    path.display()
+                )
+            });
+            return Err(vec![InputError::ExistingOutputFile]);
+        }
 
         Ok(params)
     }
@@ -551,7 +1834,18 @@ impl<T: CustomFloat> Parameters<T> {
     /// 1. There is at least one geometry
     /// 2. All geometries shape are defined, i.e. set as brick or sphere
     /// 3. All material referenced in geometries exist in the material list
-    /// 4. All cross-sections referenced in materials exist in the cross-section list
+    /// 4. All cross-sections referenced in materials without explicit isotopes
+    ///    exist in the cross-section list
+    /// 5. All isotopes referenced in materials exist in the isotope list, with
+    ///    consistent atom fractions
+    /// 6. All cross-sections referenced in isotopes exist in the cross-section list
+    /// 7. The energy group boundaries are valid
+    /// 8. All external sources lie within the problem
+    /// 9. All weight windows refer to existing cells and energy groups
+    /// 10. All boundary conditions are known, periodic faces being paired
+    /// 11. All regions refer to existing geometries without cycles, and
+    ///     geometry names are unique
+    /// 12. Explicit mesh planes start at zero and are strictly increasing
     pub fn check_parameters_integrity(&self) -> Result<(), Vec<ParameterError>> {
         let mut errors: Vec<ParameterError> = Vec::new();
         // 1.
@@ -565,43 +1859,151 @@ impl<T: CustomFloat> Parameters<T> {
                 if g.shape == Shape::Undefined {
                     errors.push(ParameterError::UndefinedGeometry);
                 }
-                if !self.material_params.contains_key(&g.material_name) {
+                // named geometries without material are only used in regions
+                let primitive = g.material_name.is_empty() & !g.name.is_empty();
+                if !primitive & !self.material_params.contains_key(&g.material_name) {
                     errors.push(ParameterError::MissingMaterial(g.material_name.to_owned()));
                 }
             });
-        // 4.
-        self.material_params.iter().for_each(|(_, val)| {
-            if !self
-                .cross_section_params
-                .contains_key(&val.absorption_cross_section)
-            {
-                errors.push(ParameterError::MissingCrossSection(
-                    val.name.to_owned() + ":" + val.absorption_cross_section.as_ref(),
-                ));
+        // 4. and 5.
+        self.material_params.values().for_each(|mp| {
+            if mp.isotopes.is_empty() {
+                [
+                    &mp.absorption_cross_section,
+                    &mp.scattering_cross_section,
+                    &mp.fission_cross_section,
+                ]
+                .into_iter()
+                .filter(|xs_name| !self.cross_section_params.contains_key(*xs_name))
+                .for_each(|xs_name| {
+                    errors.push(ParameterError::MissingCrossSection(
+                        mp.name.to_owned() + ":" + xs_name.as_ref(),
+                    ))
+                });
+                return;
             }
-            if !self
-                .cross_section_params
-                .contains_key(&val.scattering_cross_section)
+            mp.isotopes
+                .iter()
+                .filter(|iso_name| !self.isotope_params.contains_key(*iso_name))
+                .for_each(|iso_name| {
+                    errors.push(ParameterError::MissingIsotope(
+                        mp.name.to_owned() + ":" + iso_name.as_ref(),
+                    ))
+                });
+            let total_fraction: T = mp.atom_fractions.iter().copied().sum();
+            if !mp.atom_fractions.is_empty()
+                & ((mp.atom_fractions.len() != mp.isotopes.len())
+                    | mp.atom_fractions.iter().any(|f| *f < T::zero())
+                    | (total_fraction <= T::zero()))
             {
-                errors.push(ParameterError::MissingCrossSection(
-                    val.name.to_owned() + ":" + val.scattering_cross_section.as_ref(),
-                ));
-            }
-            if !self
-                .cross_section_params
-                .contains_key(&val.fission_cross_section)
-            {
-                errors.push(ParameterError::MissingCrossSection(
-                    val.name.to_owned() + ":" + val.fission_cross_section.as_ref(),
-                ));
+                errors.push(ParameterError::BadComposition(mp.name.to_owned()));
             }
         });
+        // 6.
+        self.isotope_params.values().for_each(|ip| {
+            ip.cross_section_names()
+                .filter(|xs_name| !self.cross_section_params.contains_key(*xs_name))
+                .for_each(|xs_name| {
+                    errors.push(ParameterError::MissingCrossSection(
+                        ip.name.to_owned() + ":" + xs_name.as_ref(),
+                    ))
+                });
+        });
+        // 7.
+        let boundaries = self.simulation_params.energy_boundaries();
+        if (boundaries.len() < 2)
+            || (boundaries[0] <= T::zero())
+            || boundaries.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            errors.push(ParameterError::BadEnergyGroups);
+        }
+        // 8.
+        let sim = &self.simulation_params;
+        self.source_params
+            .iter()
+            .enumerate()
+            .filter(|(_, sp)| !sp.is_inside(sim.lx, sim.ly, sim.lz))
+            .for_each(|(idx, _)| errors.push(ParameterError::BadSource(idx)));
+        // 9.
+        let n_cells = sim.nx * sim.ny * sim.nz;
+        let n_groups = boundaries.len().saturating_sub(1);
+        sim.weight_windows
+            .iter()
+            .enumerate()
+            .filter(|(_, ww)| {
+                ww.cell.is_some_and(|cell| cell >= n_cells)
+                    | ww.group.is_some_and(|group| group >= n_groups)
+            })
+            .for_each(|(idx, _)| errors.push(ParameterError::BadWeightWindow(idx)));
+        // 10.
+        let conditions = sim.boundary_conditions();
+        conditions
+            .iter()
+            .enumerate()
+            .filter(|(idx, bc)| match **bc {
+                "escape" | "reflect" | "white" => false,
+                "periodic" => conditions[idx ^ 1] != "periodic",
+                _ => true,
+            })
+            .for_each(|(_, bc)| errors.push(ParameterError::BadBoundaryCondition(bc.to_string())));
+        // 11.
+        self.geometry_params
+            .iter()
+            .filter(|g| !g.name.is_empty() | g.region.is_some())
+            .filter(|g| {
+                let duplicate = !g.name.is_empty()
+                    & (self
+                        .geometry_params
+                        .iter()
+                        .filter(|other| other.name == g.name)
+                        .count()
+                        > 1);
+                duplicate | !self.valid_region(g, &mut Vec::new())
+            })
+            .for_each(|g| errors.push(ParameterError::BadRegion(g.name.to_owned())));
+        // 12.
+        if [&sim.x_planes, &sim.y_planes, &sim.z_planes]
+            .iter()
+            .filter(|planes| !planes.is_empty())
+            .any(|planes| {
+                (planes.len() < 2)
+                    | (planes[0] != T::zero())
+                    | planes.windows(2).any(|w| w[1] <= w[0])
+            })
+        {
+            errors.push(ParameterError::BadMeshPlanes);
+        }
         if errors.is_empty() {
             return Ok(());
         }
         Err(errors)
     }
 
+    /// Returns true if all the geometries referenced by a region exist and
+    /// do not refer back to the region. `path` holds the names of the regions
+    /// being checked.
+    fn valid_region<'a>(
+        &'a self,
+        geometry: &'a GeometryParameters<T>,
+        path: &mut Vec<&'a str>,
+    ) -> bool {
+        let Some(region) = &geometry.region else {
+            return true;
+        };
+        if path.contains(&geometry.name.as_str()) {
+            return false;
+        }
+        path.push(&geometry.name);
+        let valid = region.names().iter().all(|name| {
+            self.geometry_params
+                .iter()
+                .find(|g| g.name == *name)
+                .is_some_and(|g| self.valid_region(g, path))
+        });
+        path.pop();
+        valid
+    }
+
     /// Update the object's [SimulationParameters] field using the [Block] passed
     /// as argument. May return an error if the block isn't a proper Simulation
     /// block, i.e.:
@@ -631,7 +2033,39 @@ impl<T: CustomFloat> Parameters<T> {
                 "inputFile" => self.simulation_params.input_file = val,
                 "energySpectrum" => self.simulation_params.energy_spectrum = val,
                 "crossSectionsOut" => self.simulation_params.cross_sections_out = val,
+                "vtkOut" => self.simulation_params.vtk_out = val,
+                "vtkFrequency" => fetch_data!(vtk_frequency, val),
+                "trackOut" => self.simulation_params.track_out = val,
+                "trackParticles" => {
+                    self.simulation_params.track_particles.clear();
+                    for identifier in split_list(&val) {
+                        match identifier.parse() {
+                            Ok(v) => self.simulation_params.track_particles.push(v),
+                            Err(_) => return Err(InputError::BadSimulationBlock),
+                        }
+                    }
+                }
+                "trackHistories" => fetch_data!(track_histories, val),
+                "trackVtk" => {
+                    let chars: Vec<char> = val.chars().collect();
+                    fetch_bool!(track_vtk, chars[0]);
+                }
+                "jsonOut" => self.simulation_params.json_out = val,
+                "traceOut" => self.simulation_params.trace_out = val,
+                "outputDir" => self.simulation_params.output_dir = val,
+                "runName" => self.simulation_params.run_name = val,
+                "overwrite" => {
+                    let chars: Vec<char> = val.chars().collect();
+                    fetch_bool!(overwrite, chars[0]);
+                }
+                "progressInterval" => fetch_data!(progress_interval, val),
                 "boundaryCondition" => self.simulation_params.boundary_condition = val,
+                "boundaryXMin" => self.simulation_params.face_boundary_conditions[0] = val,
+                "boundaryXMax" => self.simulation_params.face_boundary_conditions[1] = val,
+                "boundaryYMin" => self.simulation_params.face_boundary_conditions[2] = val,
+                "boundaryYMax" => self.simulation_params.face_boundary_conditions[3] = val,
+                "boundaryZMin" => self.simulation_params.face_boundary_conditions[4] = val,
+                "boundaryZMax" => self.simulation_params.face_boundary_conditions[5] = val,
                 "loadBalance" => {
                     let chars: Vec<char> = val.chars().collect();
                     fetch_bool!(load_balance, chars[0]);
@@ -654,6 +2088,7 @@ impl<T: CustomFloat> Parameters<T> {
                 "nx" => fetch_data!(nx, val),
                 "ny" => fetch_data!(ny, val),
                 "nz" => fetch_data!(nz, val),
+                "volumeSamples" => fetch_data!(volume_samples, val),
                 "seed" => fetch_data!(seed, val),
                 "dt" => fetch_data!(dt, val),
                 "lx" => fetch_data!(lx, val),
@@ -662,7 +2097,44 @@ impl<T: CustomFloat> Parameters<T> {
                 "eMin" => fetch_data!(e_min, val),
                 "eMax" => fetch_data!(e_max, val),
                 "nGroups" => fetch_data!(n_groups, val),
+                "groupStructure" => {
+                    self.simulation_params.group_structure = match val.as_ref() {
+                        "logUniform" => GroupStructure::LogUniform,
+                        "casmo2" => GroupStructure::Casmo2,
+                        "casmo4" => GroupStructure::Casmo4,
+                        _ => return Err(InputError::BadSimulationBlock),
+                    }
+                }
+                "xPlanes" | "yPlanes" | "zPlanes" => {
+                    let planes = match key.as_ref() {
+                        "xPlanes" => &mut self.simulation_params.x_planes,
+                        "yPlanes" => &mut self.simulation_params.y_planes,
+                        _ => &mut self.simulation_params.z_planes,
+                    };
+                    planes.clear();
+                    for plane in split_list(&val) {
+                        match plane.parse() {
+                            Ok(v) => planes.push(v),
+                            Err(_) => return Err(InputError::BadSimulationBlock),
+                        }
+                    }
+                }
+                "groupBoundaries" => {
+                    self.simulation_params.group_structure = GroupStructure::Explicit;
+                    self.simulation_params.group_boundaries.clear();
+                    for boundary in split_list(&val) {
+                        match boundary.parse() {
+                            Ok(v) => self.simulation_params.group_boundaries.push(v),
+                            Err(_) => return Err(InputError::BadSimulationBlock),
+                        }
+                    }
+                }
                 "lowWeightCutoff" => fetch_data!(low_weight_cutoff, val),
+                "implicitCapture" => {
+                    let chars: Vec<char> = val.chars().collect();
+                    fetch_bool!(implicit_capture, chars[0]);
+                }
+                "weightWindows" => self.simulation_params.weight_window_file = val,
 
                 // Unused in fastiron; execution policy support is now CLI only
                 "cycleTimers" => (),
@@ -675,6 +2147,17 @@ impl<T: CustomFloat> Parameters<T> {
                 _ => return Err(InputError::BadSimulationBlock),
             }
         }
+        // explicit planes take precedence over the number of cells & size
+        let sim = &mut self.simulation_params;
+        if sim.x_planes.len() > 1 {
+            (sim.nx, sim.lx) = (sim.x_planes.len() - 1, sim.x_planes[sim.x_planes.len() - 1]);
+        }
+        if sim.y_planes.len() > 1 {
+            (sim.ny, sim.ly) = (sim.y_planes.len() - 1, sim.y_planes[sim.y_planes.len() - 1]);
+        }
+        if sim.z_planes.len() > 1 {
+            (sim.nz, sim.lz) = (sim.z_planes.len() - 1, sim.z_planes[sim.z_planes.len() - 1]);
+        }
         Ok(())
     }
     /// Add a new [GeometryParameters] object to the internal list.
@@ -686,9 +2169,25 @@ impl<T: CustomFloat> Parameters<T> {
         self.material_params
             .insert(some_material.name.to_owned(), some_material);
     }
+    /// Add a new [IsotopeParameters] object to the internal map.
+    pub fn add_isotope_parameter(&mut self, some_isotope: IsotopeParameters<T>) {
+        self.isotope_params
+            .insert(some_isotope.name.to_owned(), some_isotope);
+    }
+    /// Add a new [SourceParameters] object to the internal list.
+    pub fn add_source_parameter(&mut self, some_source: SourceParameters<T>) {
+        self.source_params.push(some_source);
+    }
     /// Add a new [CrossSectionParameters] object to the internal map.
     pub fn add_cross_section_parameter(&mut self, cross_section: CrossSectionParameters<T>) {
         self.cross_section_params
             .insert(cross_section.name.to_owned(), cross_section);
     }
 }
+
+/// Splits a list value of an input block. Items can be separated by
+/// whitespaces and/or commas.
+fn split_list(val: &str) -> impl Iterator<Item = &str> {
+    val.split(|c: char| (c == ',') | c.is_whitespace())
+        .filter(|item| !item.is_empty())
+}
@@ -6,10 +6,10 @@
 use std::iter::zip;
 
 use num::{one, zero, FromPrimitive};
-use tinyvec::ArrayVec;
+use tinyvec::TinyVec;
 
 use crate::{
-    constants::CustomFloat,
+    constants::{sim::MAX_WINDOW_SPLIT, CustomFloat},
     data::{
         mc_vector::MCVector,
         nuclear_data::{NuclearDataReaction, ReactionType},
@@ -26,8 +26,22 @@ pub enum Species {
     /// Invalid value.
     Unknown = -1,
     #[default]
-    /// Valid value. Quicksilver only supported one particle type.
-    Known = 0, // \o/
+    /// Neutron.
+    Neutron = 0,
+    /// Photon.
+    Photon = 1,
+}
+
+impl Species {
+    /// Returns the index associated to the species, used to access
+    /// species-sorted data. Panics if the species is [Species::Unknown].
+    pub fn index(&self) -> usize {
+        match self {
+            Species::Unknown => panic!("invalid particle species"),
+            Species::Neutron => 0,
+            Species::Photon => 1,
+        }
+    }
 }
 
 /// Structure used to hold all data of a particle.
@@ -109,11 +123,14 @@ impl<T: CustomFloat> MCParticle<T> {
         self.sample_num_mfp();
     }
 
-    /// Computes the particle speed from its energy. Note that this computation
-    /// should be species-specific.
+    /// Computes the particle speed from its energy. Photons travel at the
+    /// speed of light, neutrons at their relativistic speed.
     pub fn get_speed(&self) -> T {
-        let rest_mass_energy: T = T::neutron_mass_energy();
         let speed_of_light: T = T::light_speed();
+        if self.species == Species::Photon {
+            return speed_of_light;
+        }
+        let rest_mass_energy: T = T::neutron_mass_energy();
         let two: T = FromPrimitive::from_f64(2.0).unwrap();
         speed_of_light
             * (self.kinetic_energy * (self.kinetic_energy + two * (rest_mass_energy))
@@ -134,54 +151,54 @@ impl<T: CustomFloat> MCParticle<T> {
     pub fn sample_collision(
         &mut self,
         reaction: &NuclearDataReaction<T>,
-        material_mass: T,
+        isotope_mass: T,
         extra: &mut ParticleCollection<T>,
     ) -> usize {
         let one: T = FromPrimitive::from_f64(1.0).unwrap();
         let two: T = FromPrimitive::from_f64(2.0).unwrap();
-        // Need to replace with tinyvec types
         match reaction.reaction_type {
             ReactionType::Scatter => {
-                let energy = self.kinetic_energy
-                    * (one - rng_sample::<T>(&mut self.random_number_seed) * (one / material_mass));
-                let angle = rng_sample::<T>(&mut self.random_number_seed) * two - one;
+                let (energy, angle) = reaction.scattering.sample(
+                    self.kinetic_energy,
+                    isotope_mass,
+                    &mut self.random_number_seed,
+                );
                 self.update_trajectory(energy, angle);
                 1
             }
-            ReactionType::Absorption => 0,
+            ReactionType::Absorption => {
+                if reaction.photon_yield > zero() {
+                    self.emit_photons(reaction, extra);
+                }
+                0
+            }
             ReactionType::Fission => {
-                let num_particle_out: usize = (reaction.nu_bar
-                    + rng_sample(&mut self.random_number_seed))
-                .to_usize()
-                .unwrap();
-                let twenty: T = FromPrimitive::from_f64(20.0).unwrap(); // should be Emax ?
+                let nu_bar: T = reaction.nu_bar.val(self.kinetic_energy).max(zero());
+                let num_particle_out: usize = (nu_bar + rng_sample(&mut self.random_number_seed))
+                    .to_usize()
+                    .unwrap();
                 match num_particle_out {
                     0 => (),
                     1 => {
-                        let rand_f = (rng_sample::<T>(&mut self.random_number_seed) + one) / two;
-                        let energy = twenty * rand_f * rand_f;
+                        let energy = reaction.spectrum.sample(&mut self.random_number_seed);
                         let angle = rng_sample::<T>(&mut self.random_number_seed) * two - one;
                         self.update_trajectory(energy, angle);
                     }
                     _ => {
-                        assert!(num_particle_out < 5); // this is guaranteed by the way we sample and the nu bar value
-
                         // for the original particle
-                        let rand_f = (rng_sample::<T>(&mut self.random_number_seed) + one) / two;
-                        let energy = twenty * rand_f * rand_f;
+                        let energy = reaction.spectrum.sample(&mut self.random_number_seed);
                         let angle = rng_sample::<T>(&mut self.random_number_seed) * two - one;
 
-                        let mut out = ArrayVec::<[(T, T); 5]>::default();
+                        // inline storage for the usual multiplicities, heap otherwise
+                        let mut out = TinyVec::<[(T, T); 5]>::default();
                         out.extend((1..num_particle_out).map(|_| {
-                            let rand_f =
-                                (rng_sample::<T>(&mut self.random_number_seed) + one) / two;
-                            let energy_out = twenty * rand_f * rand_f;
+                            let energy_out = reaction.spectrum.sample(&mut self.random_number_seed);
                             let angle_out =
                                 rng_sample::<T>(&mut self.random_number_seed) * two - one;
                             (energy_out, angle_out)
                         }));
 
-                        let mut seeds = ArrayVec::<[u64; 5]>::default();
+                        let mut seeds = TinyVec::<[u64; 5]>::default();
                         seeds.extend(
                             (1..num_particle_out)
                                 .map(|_| spawn_rn_seed::<T>(&mut self.random_number_seed)),
@@ -204,6 +221,35 @@ impl<T: CustomFloat> MCParticle<T> {
         }
     }
 
+    /// Creates the photons emitted on a radiative capture and adds them to the
+    /// extra storage. The number of photons is sampled from the reaction's
+    /// yield; they are emitted isotropically with the reaction's photon energy.
+    pub fn emit_photons(
+        &mut self,
+        reaction: &NuclearDataReaction<T>,
+        extra: &mut ParticleCollection<T>,
+    ) {
+        let n_photons: usize = (reaction.photon_yield
+            + rng_sample::<T>(&mut self.random_number_seed))
+        .to_usize()
+        .unwrap();
+        let photons = (0..n_photons)
+            .map(|_| spawn_rn_seed::<T>(&mut self.random_number_seed))
+            .collect::<TinyVec<[u64; 5]>>()
+            .into_iter()
+            .map(|seed| {
+                let mut photon = self.clone();
+                photon.species = Species::Photon;
+                photon.random_number_seed = seed;
+                photon.identifier = seed;
+                photon.kinetic_energy = reaction.photon_energy;
+                photon.sample_isotropic();
+                photon.sample_num_mfp();
+                photon
+            });
+        extra.extend(photons);
+    }
+
     /// Sample the number of mean free paths to a collision.
     pub fn sample_num_mfp(&mut self) {
         self.num_mean_free_paths = -one::<T>() * rng_sample::<T>(&mut self.random_number_seed).ln();
@@ -290,6 +336,49 @@ impl<T: CustomFloat> MCParticle<T> {
         })
     }
 
+    /// Checks the weight of the particle against the window `[lower; upper]`
+    /// and returns the number of resulting particles, including the original.
+    /// Particles above the window are split, the copies being added to the
+    /// extra storage. Particles below the window play russian roulette and
+    /// survive with the average weight of the window.
+    pub fn weight_window(
+        &mut self,
+        lower: T,
+        upper: T,
+        extra: &mut ParticleCollection<T>,
+    ) -> usize {
+        if self.weight > upper {
+            let max_split: T = FromPrimitive::from_usize(MAX_WINDOW_SPLIT).unwrap();
+            let n_split = (self.weight / upper).ceil().min(max_split);
+            self.weight /= n_split;
+            let n_split: usize = n_split.to_usize().unwrap();
+            let copies = (1..n_split)
+                .map(|_| spawn_rn_seed::<T>(&mut self.random_number_seed))
+                .collect::<TinyVec<[u64; MAX_WINDOW_SPLIT]>>()
+                .into_iter()
+                .map(|seed| {
+                    let mut split_pp = self.clone();
+                    split_pp.random_number_seed = seed;
+                    split_pp.identifier = seed;
+                    split_pp.sample_num_mfp();
+                    split_pp
+                });
+            extra.extend(copies);
+            n_split
+        } else if self.weight < lower {
+            let two: T = FromPrimitive::from_f64(2.0).unwrap();
+            let survival_weight = (lower + upper) / two;
+            if rng_sample::<T>(&mut self.random_number_seed) < self.weight / survival_weight {
+                self.weight = survival_weight;
+                1
+            } else {
+                0
+            }
+        } else {
+            1
+        }
+    }
+
     /// Play russian-roulette with the particle, returning true if the particle survives,
     /// false otherwise. This function is meant to be used along the [`Vec::retain_mut()`]
     /// method.
@@ -350,6 +439,20 @@ mod tests {
         assert_eq!(particle.direction.z, 0.2026699815455325);
     }
 
+    #[test]
+    fn species_speed() {
+        let mut pp: MCParticle<f64> = MCParticle {
+            kinetic_energy: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(pp.species, Species::Neutron);
+        let neutron_speed = pp.get_speed();
+        assert!((neutron_speed - 1.953e9).abs() < 1e6);
+        pp.species = Species::Photon;
+        assert_eq!(pp.get_speed(), f64::light_speed::<f64>());
+        assert_eq!(Species::Photon.index(), 1);
+    }
+
     #[test]
     fn trajectory() {
         let mut pp: MCParticle<f64> = MCParticle::default();
@@ -393,4 +496,29 @@ mod tests {
         assert_eq!(particle.direction.y, 0.3496694784021821);
         assert_eq!(particle.direction.z, 0.6407833194623658);
     }
+
+    #[test]
+    fn weight_window() {
+        let mut particle: MCParticle<f64> = MCParticle {
+            random_number_seed: 90374384094798327,
+            weight: 3.5,
+            ..Default::default()
+        };
+        let mut extra: ParticleCollection<f64> = ParticleCollection::with_capacity(10);
+
+        // inside the window
+        assert_eq!(particle.weight_window(0.5, 4.0, &mut extra), 1);
+        assert_eq!(particle.weight, 3.5);
+        // above the window
+        assert_eq!(particle.weight_window(0.25, 1.0, &mut extra), 4);
+        assert_eq!(extra.len(), 3);
+        assert_eq!(particle.weight, 0.875);
+        assert!((&mut extra).into_iter().all(|pp| pp.weight == 0.875));
+        // below the window, the particle either dies or gets the average weight
+        match particle.weight_window(2.0, 4.0, &mut extra) {
+            0 => (),
+            1 => assert_eq!(particle.weight, 3.0),
+            _ => unreachable!(),
+        }
+    }
 }
@@ -21,6 +21,9 @@ use super::mc_particle::MCParticle;
 pub struct ParticleCollection<T: CustomFloat> {
     /// Vector holding all the particles of the collection.
     data: Vec<MCParticle<T>>,
+    /// Number of times the vector was reallocated to grow its capacity
+    /// since the last reset.
+    reallocations: u64,
 }
 
 impl<T: CustomFloat> ParticleCollection<T> {
@@ -28,6 +31,7 @@ impl<T: CustomFloat> ParticleCollection<T> {
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             data: Vec::with_capacity(capacity),
+            reallocations: 0,
         }
     }
 
@@ -56,14 +60,35 @@ impl<T: CustomFloat> ParticleCollection<T> {
         self.data.retain_mut(f);
     }
 
-    /// Propagating method.
+    /// Returns the number of reallocations since the last call to
+    /// [`Self::reset_reallocations()`].
+    pub fn reallocations(&self) -> u64 {
+        self.reallocations
+    }
+
+    /// Resets the reallocation counter.
+    pub fn reset_reallocations(&mut self) {
+        self.reallocations = 0;
+    }
+
+    /// Propagating method. Reallocations are counted.
     pub fn append(&mut self, other: &mut Self) {
-        self.data.append(&mut other.data)
+        let old_capacity = self.data.capacity();
+        self.data.append(&mut other.data);
+        self.count_reallocation(old_capacity);
     }
 
-    /// Propagating method.
+    /// Propagating method. Reallocations are counted.
     pub fn extend<I: IntoIterator<Item = MCParticle<T>>>(&mut self, iter: I) {
+        let old_capacity = self.data.capacity();
         self.data.extend(iter);
+        self.count_reallocation(old_capacity);
+    }
+
+    fn count_reallocation(&mut self, old_capacity: usize) {
+        if self.data.capacity() != old_capacity {
+            self.reallocations += 1;
+        }
     }
 
     /// Propagating method.
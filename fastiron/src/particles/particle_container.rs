@@ -2,7 +2,10 @@
 //!
 //! This module contains code used for the main structure holding particles.
 
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    Arc, Mutex,
+};
 
 use rayon::prelude::*;
 
@@ -11,7 +14,7 @@ use crate::{
     data::tallies::{Balance, TalliedEvent},
     montecarlo::{MonteCarloData, MonteCarloUnit},
     simulation::cycle_tracking::cycle_tracking_guts,
-    utils::mc_processor_info::ExecPolicy,
+    utils::{mc_processor_info::ExecPolicy, progress::ProgressCounter, trace::TraceBuffer},
 };
 
 use super::{mc_particle::Species, particle_collection::ParticleCollection};
@@ -28,6 +31,12 @@ pub struct ParticleContainer<T: CustomFloat> {
     /// Container for extra particles. This is used for fission-induced
     /// particles and incoming off-processor particles.
     pub extra_particles: ParticleCollection<T>,
+    /// Largest total capacity, in number of particles, of the per-chunk extra
+    /// buffers alive at the same time when processing in parallel.
+    pub peak_chunk_capacity: usize,
+    /// Number of reallocations of the per-chunk extra buffers since the last
+    /// call to [Self::take_reallocations].
+    pub chunk_reallocations: u64,
 }
 
 impl<T: CustomFloat> ParticleContainer<T> {
@@ -37,9 +46,38 @@ impl<T: CustomFloat> ParticleContainer<T> {
             processing_particles: ParticleCollection::with_capacity(regular_capacity),
             processed_particles: ParticleCollection::with_capacity(regular_capacity),
             extra_particles: ParticleCollection::with_capacity(extra_capacity),
+            peak_chunk_capacity: 0,
+            chunk_reallocations: 0,
         }
     }
 
+    /// Returns the total capacity of the collections, in number of particles.
+    pub fn capacity(&self) -> usize {
+        self.processing_particles.capacity()
+            + self.processed_particles.capacity()
+            + self.extra_particles.capacity()
+    }
+
+    /// Returns the number of reallocations of the collections, including the
+    /// per-chunk extra buffers, since the last call and resets their counters.
+    pub fn take_reallocations(&mut self) -> u64 {
+        let chunk_reallocations = std::mem::take(&mut self.chunk_reallocations);
+        let collections = [
+            &mut self.processing_particles,
+            &mut self.processed_particles,
+            &mut self.extra_particles,
+        ];
+        collections
+            .into_iter()
+            .map(|collection| {
+                let reallocations = collection.reallocations();
+                collection.reset_reallocations();
+                reallocations
+            })
+            .sum::<u64>()
+            + chunk_reallocations
+    }
+
     /// Swap the processing and processed particle lists. This function is used in-between
     /// iterations.
     pub fn swap_processing_processed(&mut self) {
@@ -98,6 +136,9 @@ impl<T: CustomFloat> ParticleContainer<T> {
             // Process unit sequentially
             ExecPolicy::Sequential | ExecPolicy::Distributed => {
                 let mut tmp = Balance::default();
+                let chunk_start = mcunit.fast_timer.tracer.now();
+                let old_n_extra = self.extra_particles.len();
+                let mut counter = ProgressCounter::new(&mcunit.progress, &tmp);
                 (&mut self.processing_particles)
                     .into_iter()
                     .for_each(|particle| {
@@ -107,8 +148,22 @@ impl<T: CustomFloat> ParticleContainer<T> {
                             particle,
                             &mut tmp,
                             &mut self.extra_particles,
-                        )
+                        );
+                        counter.tick(&tmp);
                     });
+                mcunit
+                    .progress
+                    .add_queued(self.extra_particles.len() - old_n_extra);
+                counter.flush(&tmp);
+                let tracer = &mcunit.fast_timer.tracer;
+                let mut trace = TraceBuffer::default();
+                tracer.record_chunk(
+                    &mut trace,
+                    chunk_start,
+                    None,
+                    self.processing_particles.len(),
+                );
+                tracer.merge(trace);
                 mcunit.tallies.balance_cycle.add_to_self(&tmp);
             }
             // Process unit in parallel
@@ -119,8 +174,14 @@ impl<T: CustomFloat> ParticleContainer<T> {
                     0 => (self.processing_particles.len() / exinf.n_rayon_threads) + 1,
                     _ => exinf.chunk_size,
                 };
+                // capacity of the chunk buffers currently alive & its peak
+                let live_capacity = AtomicUsize::new(0);
+                let peak_capacity = AtomicUsize::new(0);
+                let chunk_reallocations = AtomicU64::new(0);
 
-                let res: Balance = self
+                let tracer = &mcunit.fast_timer.tracer;
+                let progress = &mcunit.progress;
+                let (res, trace): (Balance, TraceBuffer) = self
                     .processing_particles
                     .par_iter_mut()
                     .chunks(chunk_size)
@@ -132,11 +193,17 @@ impl<T: CustomFloat> ParticleContainer<T> {
                         // 2. Use a local extra collection that is later used to extend the global
                         //    container. This reduces the total number of lock (and probably
                         //    lock time)
+                        let chunk_start = tracer.now();
                         let mut local_balance: Balance = Balance::default();
                         // chunk_size * 5 is enough capacity to handle all particles undergoing
                         // fission & splitting into the max possible nb of particles.
                         let mut local_extra: ParticleCollection<T> =
                             ParticleCollection::with_capacity(chunk_size * 5);
+                        let initial_capacity = local_extra.capacity();
+                        let live = live_capacity.fetch_add(initial_capacity, Ordering::Relaxed)
+                            + initial_capacity;
+                        peak_capacity.fetch_max(live, Ordering::Relaxed);
+                        let mut counter = ProgressCounter::new(progress, &local_balance);
                         particles.iter_mut().for_each(|particle| {
                             cycle_tracking_guts(
                                 mcdata,
@@ -144,18 +211,47 @@ impl<T: CustomFloat> ParticleContainer<T> {
                                 particle,
                                 &mut local_balance,
                                 &mut local_extra,
-                            )
+                            );
+                            counter.tick(&local_balance);
                         });
-                        extra.lock().unwrap().append(&mut local_extra);
-                        local_balance
+                        progress.add_queued(local_extra.len());
+                        counter.flush(&local_balance);
+                        // the buffer is dropped after the append
+                        let capacity = local_extra.capacity();
+                        let live = live_capacity
+                            .fetch_add(capacity - initial_capacity, Ordering::Relaxed)
+                            + capacity
+                            - initial_capacity;
+                        peak_capacity.fetch_max(live, Ordering::Relaxed);
+                        chunk_reallocations
+                            .fetch_add(local_extra.reallocations(), Ordering::Relaxed);
+                        let lock_start = tracer.now();
+                        let mut guard = extra.lock().unwrap();
+                        let lock_end = tracer.now();
+                        guard.append(&mut local_extra);
+                        drop(guard);
+                        live_capacity.fetch_sub(capacity, Ordering::Relaxed);
+                        // events are buffered until the end of the cycle
+                        let mut local_trace = TraceBuffer::default();
+                        tracer.record_chunk(
+                            &mut local_trace,
+                            chunk_start,
+                            lock_start.zip(lock_end),
+                            particles.len(),
+                        );
+                        (local_balance, local_trace)
                     })
-                    .fold_with(Balance::default(), |a, b| a + b)
-                    .sum::<Balance>();
+                    .reduce(Default::default, |(bal_a, trace_a), (bal_b, trace_b)| {
+                        (bal_a + bal_b, trace_a.merge(trace_b))
+                    });
+                tracer.merge(trace);
                 // It should be safe to simply add this to the one in mcunit
                 assert_eq!(res[TalliedEvent::Start], 0);
                 assert_eq!(res[TalliedEvent::End], 0);
                 assert_eq!(res[TalliedEvent::Source], 0);
                 mcunit.tallies.balance_cycle.add_to_self(&res);
+                self.peak_chunk_capacity = self.peak_chunk_capacity.max(peak_capacity.into_inner());
+                self.chunk_reallocations += chunk_reallocations.into_inner();
             }
         }
         self.processing_particles
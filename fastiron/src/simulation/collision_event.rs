@@ -4,7 +4,7 @@
 //! from beginning to end. Note that _collision_ refers to reaction with the
 //! particle's environment, not in-between particles.
 
-use num::{zero, FromPrimitive};
+use num::{one, zero, FromPrimitive};
 
 use crate::{
     constants::CustomFloat,
@@ -13,7 +13,11 @@ use crate::{
         tallies::{Balance, TalliedEvent},
     },
     montecarlo::MonteCarloData,
-    particles::{mc_particle::MCParticle, particle_collection::ParticleCollection},
+    particles::{
+        mc_particle::{MCParticle, Species},
+        particle_collection::ParticleCollection,
+    },
+    utils::mc_rng_state::rng_sample,
 };
 
 /// Transforms a given particle according to an internally drawn type of collision.
@@ -23,7 +27,11 @@ use crate::{
 /// collision tallied. Finally, particles are created / invalidated accordingly to
 /// the picked reaction:
 ///
-/// - Absorption reaction: the particle is invalidated.
+/// - Absorption reaction: the particle is invalidated. Photons may be emitted
+///   if the reaction is a neutron capture. With implicit capture, absorption is
+///   never sampled: the weight of the particle is reduced by the absorption
+///   probability before picking another reaction, and light particles play
+///   russian roulette.
 /// - Fission reaction: offspring particles are created from the colliding one.
 /// - Scattering reaction: no additional modifications occur.
 pub fn collision_event<T: CustomFloat>(
@@ -34,16 +42,59 @@ pub fn collision_event<T: CustomFloat>(
     particle: &mut MCParticle<T>,
     extra: &mut ParticleCollection<T>,
 ) -> bool {
+    let implicit_capture = mcdata.params.simulation_params.implicit_capture;
+    let species = particle.species;
+    let mut current_xsection: T = particle.get_current_xs();
+
+    // ========================
+    // Implicit capture, if any
+
+    if implicit_capture {
+        let (absorption_xs, remaining_xs) =
+            implicit_cross_sections(mcdata, mat_gid, cell_nb_density, particle);
+        let absorbed_fraction = if remaining_xs == zero() {
+            one()
+        } else {
+            absorption_xs / (absorption_xs + remaining_xs)
+        };
+        let absorbed_weight = particle.weight * absorbed_fraction;
+        balance.absorb(species, absorbed_weight);
+        emit_capture_photons(
+            mcdata,
+            balance,
+            mat_gid,
+            cell_nb_density,
+            particle,
+            absorbed_weight,
+            extra,
+        );
+
+        if remaining_xs == zero() {
+            // nothing left to sample
+            balance.tally(species, TalliedEvent::Collision, 1);
+            balance.tally(species, TalliedEvent::Absorb, 1);
+            return false;
+        }
+        particle.weight -= absorbed_weight;
+        // remaining reactions are sampled among the non-absorbing ones
+        current_xsection *= remaining_xs / particle.total_cross_section;
+    }
+
     // ==========================
     // Pick an isotope & reaction
 
-    let mut current_xsection: T = particle.get_current_xs();
     let mut reaction = None;
+    let mut isotope_mass: T = zero();
 
     while current_xsection >= zero() {
         for isotope in &mcdata.material_database.mat[mat_gid].iso {
             let atom_fraction = isotope.atom_fraction;
-            for curr_reaction in &mcdata.nuclear_data.isotopes[isotope.gid][0].reactions {
+            for curr_reaction in
+                &mcdata.nuclear_data.isotopes[isotope.gid][particle.species.index()].reactions
+            {
+                if implicit_capture & (curr_reaction.reaction_type == ReactionType::Absorption) {
+                    continue;
+                }
                 if (atom_fraction == zero()) | (cell_nb_density == zero()) {
                     current_xsection -= FromPrimitive::from_f64(1e-20).unwrap();
                 } else {
@@ -53,6 +104,7 @@ pub fn collision_event<T: CustomFloat>(
                 }
                 if current_xsection < zero() {
                     reaction = Some(curr_reaction);
+                    isotope_mass = isotope.mass;
                     break;
                 }
             }
@@ -64,31 +116,132 @@ pub fn collision_event<T: CustomFloat>(
     assert!(reaction.is_some());
     let reaction = reaction.unwrap();
 
-    let mat_mass = mcdata.material_database.mat[mat_gid].mass;
-
     // ================
     // Do the collision
     //
     // number of particles resulting from the collision, including the original
     // e.g. zero means the original particle was absorbed or invalidated in some way
-    let n_out = particle.sample_collision(reaction, mat_mass, extra);
+    let n_extra = extra.len();
+    let weight = particle.weight;
+    let mut n_out = particle.sample_collision(reaction, isotope_mass, extra);
 
     //====================
     // Tally the collision
 
-    balance[TalliedEvent::Collision] += 1;
+    balance.tally(species, TalliedEvent::Collision, 1);
     match reaction.reaction_type {
         ReactionType::Scatter => {
-            balance[TalliedEvent::Scatter] += 1;
+            balance.tally(species, TalliedEvent::Scatter, 1);
         }
         ReactionType::Absorption => {
-            balance[TalliedEvent::Absorb] += 1;
+            balance.tally(species, TalliedEvent::Absorb, 1);
+            balance.absorb(species, weight);
+            // photons emitted by the capture
+            let n_photons = (extra.len() - n_extra) as u64;
+            balance.tally(Species::Photon, TalliedEvent::Produce, n_photons);
         }
         ReactionType::Fission => {
-            balance[TalliedEvent::Fission] += 1;
-            balance[TalliedEvent::Produce] += n_out as u64;
+            balance.tally(species, TalliedEvent::Fission, 1);
+            balance.tally(species, TalliedEvent::Produce, n_out as u64);
         }
     };
 
+    // play russian roulette with particles made light by implicit capture
+    if implicit_capture
+        && (n_out >= 1)
+        && !particle.low_weight_rr(
+            mcdata.params.simulation_params.low_weight_cutoff,
+            mcdata.source_particle_weight,
+        )
+    {
+        balance.tally(species, TalliedEvent::WeightRr, 1);
+        n_out = 0;
+    }
+
     n_out >= 1
 }
+
+/// Returns the macroscopic absorption cross-section seen by the particle, and
+/// the sum of the other macroscopic cross-sections.
+fn implicit_cross_sections<T: CustomFloat>(
+    mcdata: &MonteCarloData<T>,
+    mat_gid: usize,
+    cell_nb_density: T,
+    particle: &MCParticle<T>,
+) -> (T, T) {
+    let mut absorption_xs: T = zero();
+    let mut remaining_xs: T = zero();
+    mcdata.material_database.mat[mat_gid]
+        .iso
+        .iter()
+        .for_each(|isotope| {
+            mcdata.nuclear_data.isotopes[isotope.gid][particle.species.index()]
+                .reactions
+                .iter()
+                .for_each(|reaction| {
+                    let xs = isotope.atom_fraction
+                        * cell_nb_density
+                        * reaction.cross_section[particle.energy_group];
+                    match reaction.reaction_type {
+                        ReactionType::Absorption => absorption_xs += xs,
+                        _ => remaining_xs += xs,
+                    }
+                })
+        });
+    (absorption_xs, remaining_xs)
+}
+
+/// Emits the photons of a radiative capture when using implicit capture. The
+/// capture reaction is sampled among the absorption reactions, and emitted photons
+/// carry the absorbed weight. No random number is consumed if the material does
+/// not emit capture photons.
+fn emit_capture_photons<T: CustomFloat>(
+    mcdata: &MonteCarloData<T>,
+    balance: &mut Balance,
+    mat_gid: usize,
+    cell_nb_density: T,
+    particle: &mut MCParticle<T>,
+    absorbed_weight: T,
+    extra: &mut ParticleCollection<T>,
+) {
+    let iso = &mcdata.material_database.mat[mat_gid].iso;
+    let (species_idx, group) = (particle.species.index(), particle.energy_group);
+    let captures = || {
+        iso.iter().flat_map(move |isotope| {
+            mcdata.nuclear_data.isotopes[isotope.gid][species_idx]
+                .reactions
+                .iter()
+                .filter(|reaction| reaction.reaction_type == ReactionType::Absorption)
+                .map(move |reaction| {
+                    let xs =
+                        isotope.atom_fraction * cell_nb_density * reaction.cross_section[group];
+                    (reaction, xs)
+                })
+        })
+    };
+    if (absorbed_weight == zero()) | captures().all(|(reaction, _)| reaction.photon_yield == zero())
+    {
+        return;
+    }
+
+    let absorption_xs: T = captures().map(|(_, xs)| xs).sum();
+    let mut current_xsection = absorption_xs * rng_sample::<T>(&mut particle.random_number_seed);
+    let capture = captures()
+        .find(|(_, xs)| {
+            current_xsection -= *xs;
+            current_xsection < zero()
+        })
+        .map(|(reaction, _)| reaction);
+    if let Some(reaction) = capture {
+        let n_extra = extra.len();
+        let weight = particle.weight;
+        particle.weight = absorbed_weight;
+        particle.emit_photons(reaction, extra);
+        particle.weight = weight;
+        balance.tally(
+            Species::Photon,
+            TalliedEvent::Produce,
+            (extra.len() - n_extra) as u64,
+        );
+    }
+}
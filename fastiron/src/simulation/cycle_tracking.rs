@@ -9,13 +9,19 @@ use num::{one, zero};
 
 use crate::{
     constants::CustomFloat,
-    data::tallies::{Balance, MCTallyEvent, TalliedEvent},
+    data::{
+        mc_vector::MCVector,
+        tallies::{Balance, MCTallyEvent, TalliedEvent},
+    },
     montecarlo::{MonteCarloData, MonteCarloUnit},
     particles::{
         mc_particle::{MCParticle, Species},
         particle_collection::ParticleCollection,
     },
-    simulation::{mc_facet_crossing_event::facet_crossing_event, mct::reflect_particle},
+    simulation::{
+        mc_facet_crossing_event::facet_crossing_event,
+        mct::{reflect_particle, white_reflect_particle},
+    },
 };
 
 use super::{
@@ -56,15 +62,27 @@ fn cycle_tracking_function<T: CustomFloat>(
     extra: &mut ParticleCollection<T>,
 ) {
     let mut keep_tracking: bool;
+    let sim_params = &mcdata.params.simulation_params;
+    let extent = MCVector {
+        x: sim_params.lx,
+        y: sim_params.ly,
+        z: sim_params.lz,
+    };
+    let cell_gid = |cell_idx: usize| mcunit.domain.cell_state[cell_idx].id / 0x0100000000;
+    let mut track = mcunit.tracks.select(particle);
+    if let Some(track) = &mut track {
+        track.push(particle, particle.coordinate, None, cell_gid(particle.cell));
+    }
 
     loop {
         // compute event for segment
         let segment_outcome = outcome(mcdata, mcunit, particle);
+        let segment_end = particle.coordinate;
         // update # of segments
-        balance[TalliedEvent::NumSegments] += 1;
+        balance.tally(particle.species, TalliedEvent::NumSegments, 1);
         particle.num_segments += one();
         // update scalar flux tally
-        mcunit.tallies.scalar_flux_domain[(particle.cell, particle.energy_group)]
+        mcunit.tallies.flux_domain(particle.species)[(particle.cell, particle.energy_group)]
             .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| {
                 Some(x + particle.segment_path_length * particle.weight)
             })
@@ -81,6 +99,9 @@ fn cycle_tracking_function<T: CustomFloat>(
                 particle.energy_group = mcdata
                     .nuclear_data
                     .get_energy_groups(particle.kinetic_energy);
+                if keep_tracking {
+                    keep_tracking = apply_weight_window(mcdata, mcunit, particle, balance, extra);
+                }
                 if !keep_tracking {
                     particle.species = Species::Unknown;
                 }
@@ -91,12 +112,21 @@ fn cycle_tracking_function<T: CustomFloat>(
                     [particle.facet]
                     .subfacet;
 
-                facet_crossing_event(particle, facet_adjacency);
+                facet_crossing_event(
+                    particle,
+                    facet_adjacency,
+                    &mcunit.domain.cell_state,
+                    &extent,
+                    balance,
+                    extra,
+                );
 
                 keep_tracking = match particle.last_event {
                     // ~~~ on unit case
                     // on-unit transit
-                    MCTallyEvent::FacetCrossingTransitExit => true,
+                    MCTallyEvent::FacetCrossingTransitExit => {
+                        apply_weight_window(mcdata, mcunit, particle, balance, extra)
+                    }
                     // bound reflection
                     MCTallyEvent::FacetCrossingReflection => {
                         // plane on which particle is reflected
@@ -106,14 +136,27 @@ fn cycle_tracking_function<T: CustomFloat>(
                         reflect_particle(particle, plane);
                         true
                     }
+                    // bound diffuse reflection
+                    MCTallyEvent::FacetCrossingWhiteReflection => {
+                        let plane =
+                            &mcunit.domain.mesh.cell_geometry[particle.cell][particle.facet];
+
+                        white_reflect_particle(particle, plane);
+                        true
+                    }
                     // ~~~ off unit case
                     // off-unit transit
                     MCTallyEvent::FacetCrossingCommunication => {
                         unimplemented!()
                     }
+                    // killed when entering a less important cell
+                    MCTallyEvent::FacetCrossingRoulette => {
+                        particle.species = Species::Unknown;
+                        false
+                    }
                     // bound escape
                     MCTallyEvent::FacetCrossingEscape => {
-                        balance[TalliedEvent::Escape] += 1;
+                        balance.tally(particle.species, TalliedEvent::Escape, 1);
                         particle.last_event = MCTallyEvent::FacetCrossingEscape;
                         particle.species = Species::Unknown;
                         false
@@ -123,14 +166,55 @@ fn cycle_tracking_function<T: CustomFloat>(
                 };
             }
             MCSegmentOutcome::Census => {
-                balance[TalliedEvent::Census] += 1;
+                balance.tally(particle.species, TalliedEvent::Census, 1);
                 // we're done tracking the particle FOR THIS STEP; Species stays valid
                 keep_tracking = false;
             }
         }
 
+        if let Some(track) = &mut track {
+            let event = Some(particle.last_event);
+            track.push(particle, segment_end, event, cell_gid(particle.cell));
+        }
+
         if !keep_tracking {
             break;
         }
     }
+
+    if let Some(track) = track {
+        mcunit.tracks.record(track);
+    }
+}
+
+/// Checks the particle against the weight window of its current cell & energy
+/// group, if the problem has any. Returns `false` if the particle was killed.
+fn apply_weight_window<T: CustomFloat>(
+    mcdata: &MonteCarloData<T>,
+    mcunit: &MonteCarloUnit<T>,
+    particle: &mut MCParticle<T>,
+    balance: &mut Balance,
+    extra: &mut ParticleCollection<T>,
+) -> bool {
+    let Some(weight_windows) = &mcdata.weight_windows else {
+        return true;
+    };
+    let cell_gid = mcunit.domain.cell_state[particle.cell].id / 0x0100000000;
+    let (lower, upper) = weight_windows.get(cell_gid, particle.energy_group);
+    let n_out = particle.weight_window(
+        lower * mcunit.window_weight,
+        upper * mcunit.window_weight,
+        extra,
+    );
+    match n_out {
+        0 => {
+            balance.tally(particle.species, TalliedEvent::WeightRr, 1);
+            particle.species = Species::Unknown;
+            false
+        }
+        _ => {
+            balance.tally(particle.species, TalliedEvent::Split, n_out as u64 - 1);
+            true
+        }
+    }
 }
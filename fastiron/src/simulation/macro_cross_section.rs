@@ -5,17 +5,17 @@
 
 use num::{zero, FromPrimitive};
 
-use crate::{constants::CustomFloat, montecarlo::MonteCarloData};
+use crate::{constants::CustomFloat, montecarlo::MonteCarloData, particles::mc_particle::Species};
 
 /// Computes the total number-density-weighted macroscopic
 /// cross-section in the cell.
 ///
-/// Note that this function is isotope-specific; However the proxy-app
-/// only accounts for simulation of a single isotope type.
+/// Note that this function is isotope-specific and species-specific.
 fn macroscopic_total_cross_section<T: CustomFloat>(
     mcdata: &MonteCarloData<T>,
     mat_gid: usize,
     isotope_idx: usize,
+    species: Species,
     cell_nb_density: T,
     energy_group: usize,
 ) -> T {
@@ -28,21 +28,23 @@ fn macroscopic_total_cross_section<T: CustomFloat>(
     }
 
     let isotope_gid = mcdata.material_database.mat[mat_gid].iso[isotope_idx].gid;
-    let micro_cross_section: T = mcdata
-        .nuclear_data
-        .get_total_cross_section(isotope_gid, energy_group);
+    let micro_cross_section: T =
+        mcdata
+            .nuclear_data
+            .get_total_cross_section(isotope_gid, species, energy_group);
 
     atom_fraction * cell_nb_density * micro_cross_section
 }
 
 /// Computes the number-density-weighted macroscopic cross-section
-/// of a collection of isotopes in the cell.
+/// of a collection of isotopes in the cell, for a given particle species.
 ///
 /// Note that there is not really any weighting, this comes from the original
 /// choice of Quicksilver to leave material weighting out of the proxy-app.
 pub fn weighted_macroscopic_cross_section<T: CustomFloat>(
     mcdata: &MonteCarloData<T>,
     mat_gid: usize,
+    species: Species,
     cell_nb_density: T,
     energy_group: usize,
 ) -> T {
@@ -54,6 +56,7 @@ pub fn weighted_macroscopic_cross_section<T: CustomFloat>(
                 mcdata,
                 mat_gid,
                 isotope_idx,
+                species,
                 cell_nb_density,
                 energy_group,
             )
@@ -3,30 +3,84 @@
 //! This module contains code that updates a particle according to the facet
 //! it is crossing. See [MCSubfacetAdjacencyEvent] for more information.
 
+use num::{one, zero};
+
 use crate::{
     constants::CustomFloat,
-    data::tallies::MCTallyEvent,
-    geometry::facets::{MCSubfacetAdjacencyEvent, SubfacetAdjacency},
-    particles::mc_particle::MCParticle,
+    data::{
+        mc_vector::MCVector,
+        tallies::{Balance, MCTallyEvent, TalliedEvent},
+    },
+    geometry::{
+        facets::{MCSubfacetAdjacencyEvent, SubfacetAdjacency},
+        mc_cell_state::MCCellState,
+        FACE_OFFSET,
+    },
+    particles::{mc_particle::MCParticle, particle_collection::ParticleCollection},
 };
 
 /// Computes and transform accordingly a [MCParticle] object crossing a facet.
 ///
 /// This function updates a particle's locational data according to one of the
-/// four defined adjacency events ([MCSubfacetAdjacencyEvent]). Note that in a
+/// defined adjacency events ([MCSubfacetAdjacencyEvent]). Particles crossing a
+/// periodic boundary are translated by the extent of the problem to the opposite
+/// face. Note that in a
 /// sequential or a memory-shared parallelism context, there are no off-processor
 /// transit as the mesh is not divided for management.
+///
+/// Particles entering a cell of different importance are split or play russian
+/// roulette according to the importance ratio of the two cells. Created particles
+/// are added to the extra storage; killed particles are marked by a
+/// [`MCTallyEvent::FacetCrossingRoulette`] event.
 pub fn facet_crossing_event<T: CustomFloat>(
     particle: &mut MCParticle<T>,
     facet_adjacency: &SubfacetAdjacency,
+    cell_state: &[MCCellState<T>],
+    extent: &MCVector<T>,
+    balance: &mut Balance,
+    extra: &mut ParticleCollection<T>,
 ) {
     match facet_adjacency.event {
-        MCSubfacetAdjacencyEvent::TransitOnProcessor => {
+        MCSubfacetAdjacencyEvent::TransitOnProcessor
+        | MCSubfacetAdjacencyEvent::BoundaryPeriodic => {
             // particle enters an adjacent cell
+            let old_importance = cell_state[particle.cell].importance;
             particle.domain = facet_adjacency.adjacent.domain.unwrap();
             particle.cell = facet_adjacency.adjacent.cell.unwrap();
             particle.facet = facet_adjacency.adjacent.facet.unwrap();
             particle.last_event = MCTallyEvent::FacetCrossingTransitExit;
+
+            if facet_adjacency.event == MCSubfacetAdjacencyEvent::BoundaryPeriodic {
+                // the particle enters through the opposite face of the problem
+                let offset = FACE_OFFSET[particle.facet / 4];
+                particle.coordinate += MCVector {
+                    x: extent.x * T::from_i32(offset.0).unwrap(),
+                    y: extent.y * T::from_i32(offset.1).unwrap(),
+                    z: extent.z * T::from_i32(offset.2).unwrap(),
+                };
+            }
+
+            let new_importance = cell_state[particle.cell].importance;
+            if new_importance == zero() {
+                particle.last_event = MCTallyEvent::FacetCrossingRoulette;
+            } else if (old_importance != zero()) & (old_importance != new_importance) {
+                let ratio = new_importance / old_importance;
+                if ratio > one() {
+                    let n_extra = extra.len();
+                    // copies must not share the parent's distance to collision
+                    extra.extend(particle.under_populated_split(ratio).map(|mut split_pp| {
+                        split_pp.sample_num_mfp();
+                        split_pp
+                    }));
+                    let n_split = (extra.len() - n_extra) as u64;
+                    balance.tally(particle.species, TalliedEvent::ImportanceSplit, n_split);
+                } else if !particle.over_populated_rr(ratio) {
+                    particle.last_event = MCTallyEvent::FacetCrossingRoulette;
+                }
+            }
+            if particle.last_event == MCTallyEvent::FacetCrossingRoulette {
+                balance.tally(particle.species, TalliedEvent::ImportanceRr, 1);
+            }
         }
         MCSubfacetAdjacencyEvent::BoundaryEscape => {
             // particle escape the system
@@ -36,6 +90,10 @@ pub fn facet_crossing_event<T: CustomFloat>(
             // particle reflect off a system boundary
             particle.last_event = MCTallyEvent::FacetCrossingReflection
         }
+        MCSubfacetAdjacencyEvent::BoundaryWhite => {
+            // particle is re-emitted by a system boundary
+            particle.last_event = MCTallyEvent::FacetCrossingWhiteReflection
+        }
         MCSubfacetAdjacencyEvent::TransitOffProcessor => {
             // particle enters an adjacent cell that belongs to
             // a domain managed by another processor.
@@ -119,7 +119,8 @@ pub fn outcome<T: CustomFloat>(
     // get cross-section
     // lazily computed
     // This ordering should make it so that we don't compute a XS multiple times?
-    let pcxs = mcunit.xs_cache[(particle.cell, particle.energy_group)].load(Ordering::Acquire);
+    let cache_idx = (particle.cell, particle.species, particle.energy_group);
+    let pcxs = mcunit.xs_cache[cache_idx].load(Ordering::Acquire);
     let macroscopic_total_xsection = if pcxs > zero() {
         // use precomputed value
         pcxs
@@ -130,10 +131,11 @@ pub fn outcome<T: CustomFloat>(
         let tmp = weighted_macroscopic_cross_section(
             mcdata,
             mat_gid,
+            particle.species,
             cell_nb_density,
             particle.energy_group,
         );
-        mcunit.xs_cache[(particle.cell, particle.energy_group)].store(tmp, Ordering::Release);
+        mcunit.xs_cache[cache_idx].store(tmp, Ordering::Release);
         tmp
     };
 
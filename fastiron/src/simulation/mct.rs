@@ -119,6 +119,30 @@ pub fn reflect_particle<T: CustomFloat>(particle: &mut MCParticle<T>, plane: &MC
     }
 }
 
+/// Reflects a particle diffusely off a plane.
+///
+/// This function is called when a particle undergo a white reflection event at
+/// the boundary of the problem. The particle is re-emitted towards the inside of
+/// the problem following a cosine distribution around the facet normal. As for
+/// specular reflection, this does not result in a loss of energy.
+pub fn white_reflect_particle<T: CustomFloat>(
+    particle: &mut MCParticle<T>,
+    plane: &MCGeneralPlane<T>,
+) {
+    // planes' normals are outward-facing
+    particle.direction = MCVector {
+        x: -plane.a,
+        y: -plane.b,
+        z: -plane.c,
+    };
+
+    let two: T = FromPrimitive::from_f64(2.0).unwrap();
+    let cos_theta: T = rng_sample::<T>(&mut particle.random_number_seed).sqrt();
+    let sin_theta: T = (one::<T>() - cos_theta * cos_theta).sqrt();
+    let phi: T = two * T::pi() * rng_sample::<T>(&mut particle.random_number_seed);
+    particle.rotate_direction(sin_theta, cos_theta, phi.sin(), phi.cos());
+}
+
 // ==============================
 //       Private functions
 // ==============================
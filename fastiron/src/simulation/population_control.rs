@@ -4,13 +4,17 @@
 //! of particles in the simulation as well as two internal functions used by
 //! those.
 
-use num::{one, FromPrimitive};
+use num::{one, zero, FromPrimitive};
 
 use crate::{
     constants::CustomFloat,
     data::tallies::TalliedEvent,
+    geometry::global_fcc_grid::GlobalFccGrid,
     montecarlo::{MonteCarloData, MonteCarloUnit},
-    particles::{mc_particle::MCParticle, particle_container::ParticleContainer},
+    particles::{
+        mc_particle::{MCParticle, Species},
+        particle_container::ParticleContainer,
+    },
     simulation::mct::generate_coordinate_3dg,
     utils::mc_rng_state::{rng_sample, spawn_rn_seed},
 };
@@ -68,18 +72,27 @@ pub fn compute_split_factor<T: CustomFloat>(
 ///
 /// The amount of particle spawned is a consequence of the source particle weight
 /// computation as the value is adjusted according to the problem's total weight.
+///
+/// External sources are then processed the same way: the weight emitted by each
+/// source during the time step gives the number of particles to spawn. Their
+/// position, direction, energy and birth time are sampled from the source's
+/// distributions.
 pub fn source_now<T: CustomFloat>(
     mcdata: &MonteCarloData<T>,
     mcunit: &mut MonteCarloUnit<T>,
     container: &mut ParticleContainer<T>,
 ) {
     let time_step = mcdata.params.simulation_params.dt;
+    // nothing to source
+    if mcdata.source_particle_weight == zero() {
+        return;
+    }
 
     let source_rate: Vec<T> = mcdata
         .material_database
         .mat
         .iter()
-        .map(|mat| mcdata.params.material_params[&mat.name].source_rate)
+        .map(|mat| mat.source_rate)
         .collect();
     let mut seeds: Vec<u64> = Vec::new();
 
@@ -105,7 +118,11 @@ pub fn source_now<T: CustomFloat>(
                 seeds.push(rand_n_seed);
             }
 
-            mcunit.tallies.balance_cycle[TalliedEvent::Source] += seeds.len() as u64;
+            mcunit.tallies.balance_cycle.tally(
+                Species::Neutron,
+                TalliedEvent::Source,
+                seeds.len() as u64,
+            );
 
             container
                 .processing_particles
@@ -142,4 +159,57 @@ pub fn source_now<T: CustomFloat>(
                     particle
                 }));
         });
+
+    // external sources
+    let sim_params = &mcdata.params.simulation_params;
+    let grid: GlobalFccGrid<T> = GlobalFccGrid::from_params(sim_params);
+    // seeds of external sources are offset from the ones of the cells
+    let n_cells = (sim_params.nx * sim_params.ny * sim_params.nz) as u64;
+    let mut sourced: Vec<MCParticle<T>> = Vec::new();
+    mcdata
+        .sources
+        .iter()
+        .enumerate()
+        .for_each(|(source_idx, source)| {
+            let source_n_particles: usize = (source.weight(mcdata.time, time_step)
+                / mcdata.source_particle_weight)
+                .floor()
+                .to_usize()
+                .unwrap();
+
+            for _ in 0..source_n_particles {
+                let mut rand_n_seed = (n_cells + source_idx as u64) * 0x0100000000
+                    + mcunit.source_tallies[source_idx];
+                mcunit.source_tallies[source_idx] += 1;
+
+                let mut particle: MCParticle<T> = MCParticle::default();
+                particle.random_number_seed = spawn_rn_seed::<T>(&mut rand_n_seed);
+                particle.identifier = rand_n_seed;
+                particle.coordinate = source.sample_position(&mut particle.random_number_seed);
+                // the particle may belong to another domain
+                let Some(cell_idx) = mcunit.domain.which_cell(&grid, &particle.coordinate) else {
+                    continue;
+                };
+                particle.domain = mcunit.domain.global_domain;
+                particle.cell = cell_idx;
+                particle.species = source.species;
+                particle.weight = mcdata.source_particle_weight;
+                particle.direction = source.sample_direction(&mut particle.random_number_seed);
+                particle.kinetic_energy = source.energy.sample(&mut particle.random_number_seed);
+                particle.sample_num_mfp();
+                let birth_time = source.time.sample(
+                    mcdata.time,
+                    mcdata.time + time_step,
+                    &mut particle.random_number_seed,
+                );
+                particle.time_to_census = mcdata.time + time_step - birth_time;
+
+                mcunit
+                    .tallies
+                    .balance_cycle
+                    .tally(source.species, TalliedEvent::Source, 1);
+                sourced.push(particle);
+            }
+            container.processing_particles.extend(sourced.drain(..));
+        });
 }
@@ -4,33 +4,65 @@
 //! If there is a need for additional checks on data yielded by a _homemade_
 //! example, this is where to start.
 
+use std::collections::BTreeMap;
+
 use num::{one, zero, FromPrimitive};
+use serde::Serialize;
 
 use crate::{
     constants::CustomFloat, data::tallies::TalliedEvent, montecarlo::MonteCarloResults,
     parameters::BenchType,
 };
 
-/// Runs additional tests according to the [BenchType].
-pub fn coral_benchmark_correctness<T: CustomFloat>(mcresults: &MonteCarloResults<T>) {
+/// Structure used to hold the outcome of a correctness test, along with the
+/// values it was computed from.
+#[derive(Debug, Clone, Serialize)]
+pub struct CorrectnessTest {
+    /// Name of the test.
+    pub name: &'static str,
+    /// Whether the test passed.
+    pub pass: bool,
+    /// Named values computed by the test.
+    pub values: BTreeMap<&'static str, f64>,
+}
+
+impl CorrectnessTest {
+    fn new<T: CustomFloat>(name: &'static str, pass: bool, values: &[(&'static str, T)]) -> Self {
+        Self {
+            name,
+            pass,
+            values: values
+                .iter()
+                .map(|(key, val)| (*key, val.to_f64().unwrap()))
+                .collect(),
+        }
+    }
+}
+
+/// Runs additional tests according to the [BenchType] and returns their
+/// results. No test is run for [`BenchType::Standard`].
+pub fn coral_benchmark_correctness<T: CustomFloat>(
+    mcresults: &MonteCarloResults<T>,
+) -> Vec<CorrectnessTest> {
     if mcresults.bench_type == BenchType::Standard {
-        return;
+        return Vec::new();
     }
 
     // only on mpi rank 0 in QS code
-    balance_ratio_test(mcresults);
-    balance_event_test(mcresults);
-    missing_particle_test(mcresults);
-
-    // add a condition on cycles/total particles?
-    fluence_test(mcresults);
+    vec![
+        balance_ratio_test(mcresults),
+        balance_event_test(mcresults),
+        missing_particle_test(mcresults),
+        // add a condition on cycles/total particles?
+        fluence_test(mcresults),
+    ]
 }
 
 /// Test Balance Tallies for relative correctness.
 ///
 /// Expected ratios of absorbs, fissions, scatters are maintained
 /// withing some tolerance, based on input expectation.
-pub fn balance_ratio_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) {
+pub fn balance_ratio_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) -> CorrectnessTest {
     println!("Testing if ratios for absorption, fission & scattering are maintained...");
 
     let balance_tally = &mcresults.balance_cumulative;
@@ -85,11 +117,24 @@ pub fn balance_ratio_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) {
         println!("fission to absorb: {fsn2abs}");
         println!("fission to scatter: {fsn2sct}");
     }
+    CorrectnessTest::new(
+        "balance_ratio",
+        pass,
+        &[
+            ("absorb_to_scatter", abs2sct),
+            ("absorb_to_fission", abs2fsn),
+            ("scatter_to_absorb", sct2abs),
+            ("scatter_to_fission", sct2fsn),
+            ("fission_to_absorb", fsn2abs),
+            ("fission_to_scatter", fsn2sct),
+            ("tolerance", tolerance),
+        ],
+    )
 }
 
 /// Test Balance Tallies for equality in number of facet crossing
 /// and collision events.
-pub fn balance_event_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) {
+pub fn balance_event_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) -> CorrectnessTest {
     println!("Testing balance between number of facet crossings and reactions...");
 
     let balance_tally = &mcresults.balance_cumulative;
@@ -111,6 +156,16 @@ pub fn balance_event_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) {
         println!("FAIL:: Ratio not maintained within {percent_tolerance}%");
         println!("facet crossing to collision: {ratio}");
     }
+    CorrectnessTest::new(
+        "balance_event",
+        pass,
+        &[
+            ("facet_crossing", facet_crossing),
+            ("collision", collision),
+            ("ratio", ratio),
+            ("percent_tolerance", percent_tolerance),
+        ],
+    )
 }
 
 /// Test for lost particles during the simulation.
@@ -118,33 +173,41 @@ pub fn balance_event_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) {
 /// This test should always succeed unless test for
 /// done was broken, or we are running with 1 MPI rank
 /// and so never preform this test during test_for_done
-pub fn missing_particle_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) {
+pub fn missing_particle_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) -> CorrectnessTest {
     println!("Testing for lost / unaccounted for particles in this simulation...");
 
     let bt = &mcresults.balance_cumulative;
     let gains: u64 = bt[TalliedEvent::Start]
         + bt[TalliedEvent::Source]
         + bt[TalliedEvent::Produce]
-        + bt[TalliedEvent::Split];
+        + bt[TalliedEvent::Split]
+        + bt[TalliedEvent::ImportanceSplit];
     let losses: u64 = bt[TalliedEvent::Absorb]
         + bt[TalliedEvent::Census]
         + bt[TalliedEvent::Escape]
         + bt[TalliedEvent::OverRr]
         + bt[TalliedEvent::WeightRr]
+        + bt[TalliedEvent::ImportanceRr]
         + bt[TalliedEvent::Fission];
 
-    if gains == losses {
+    let pass = gains == losses;
+    if pass {
         println!("PASS:: No particles lost during run");
     } else {
         println!("FAIL:: Particles lost during run");
     }
+    CorrectnessTest::new(
+        "missing_particle",
+        pass,
+        &[("gains", gains as f64), ("losses", losses as f64)],
+    )
 }
 
 /// Test that the scalar flux is homogenous across cells for the problem.
 ///
 /// This test really requires a slot of particles or cycles or both
 /// This solution should converge to a homogenous solution
-pub fn fluence_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) {
+pub fn fluence_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) -> CorrectnessTest {
     println!("Testing fluence for homogeneity across the cells");
     let mut max_diff: T = zero();
     let mut local_sum: T = zero();
@@ -170,4 +233,13 @@ pub fn fluence_test<T: CustomFloat>(mcresults: &MonteCarloResults<T>) {
         println!("Current max difference: {max_diff}%");
         println!("Try running more particles / cycles to check if the max difference % goes down");
     }
+    CorrectnessTest::new(
+        "fluence",
+        pass,
+        &[
+            ("average", average),
+            ("max_percent_difference", max_diff),
+            ("percent_tolerance", percent_tolerance),
+        ],
+    )
 }
@@ -2,13 +2,14 @@
 //!
 //! This module contains code used for the CLI and parsing input files.
 
-use std::{fs::File, io::Read};
+use std::{fs::File, io::Read, path::Path};
 
-use crate::constants::CustomFloat;
+use crate::{constants::CustomFloat, utils::logging::LogLevel};
 use clap::Parser;
 
 use crate::parameters::{
-    Block, CrossSectionParameters, GeometryParameters, MaterialParameters, Parameters,
+    Block, CrossSectionParameters, GeometryParameters, IsotopeParameters, MaterialParameters,
+    Parameters, SourceParameters, WeightWindowParameters,
 };
 
 /// Enum used to categorize error related to the input of the program.
@@ -18,8 +19,14 @@ pub enum InputError {
     BadSimulationBlock,
     BadGeometryBlock,
     BadMaterialBlock,
+    BadIsotopeBlock,
     BadCrossSectionBlock,
+    BadSourceBlock,
     BadBlockType,
+    BadTableFile,
+    ZeroCrossSectionTable,
+    BadWeightWindowFile,
+    ExistingOutputFile,
 }
 
 /// Fastiron, a Rust port of the Quicksilver proxy-app
@@ -38,6 +45,46 @@ pub struct Cli {
     #[arg(short = 'S', long = "cross-sections", num_args(1))]
     pub cross_sections_out: Option<String>,
 
+    /// name of VTK mesh & tallies output file
+    #[arg(long = "vtk", num_args(1))]
+    pub vtk_out: Option<String>,
+
+    /// name of particle tracks output file
+    #[arg(long = "tracks", num_args(1))]
+    pub track_out: Option<String>,
+
+    /// name of JSON run report output file
+    #[arg(long = "json", num_args(1))]
+    pub json_out: Option<String>,
+
+    /// name of per-thread timing trace output file, in the Chrome trace-event format
+    #[arg(long = "trace", num_args(1))]
+    pub trace_out: Option<String>,
+
+    /// directory output files are written to -- {threads}, {units}, {particles}, {steps}, {seed} & {timestamp} are replaced
+    #[arg(long = "output-dir", num_args(1))]
+    pub output_dir: Option<String>,
+
+    /// prefix of the output files -- supports the same placeholders as the output directory
+    #[arg(long = "run-name", num_args(1))]
+    pub run_name: Option<String>,
+
+    /// replace existing output files if present
+    #[arg(long = "overwrite", num_args(0))]
+    pub overwrite: bool,
+
+    /// interval in seconds between two progress reports during tracking -- if absent or set to 0, progress is not reported
+    #[arg(long = "progress", num_args(1), allow_negative_numbers(false))]
+    pub progress_interval: Option<f64>,
+
+    /// verbosity of the log messages
+    #[arg(long = "log-level", value_enum, default_value_t = LogLevel::Info)]
+    pub log_level: LogLevel,
+
+    /// only print errors & reports, overriding the log level
+    #[arg(long = "quiet", num_args(0))]
+    pub quiet: bool,
+
     /// time step in seconds
     #[arg(short = 'D', long = "dt", num_args(1), allow_negative_numbers(false))]
     pub dt: Option<f32>,
@@ -101,6 +148,10 @@ pub struct Cli {
     #[arg(long = "bind-threads", num_args(0))]
     pub bind_threads: bool,
 
+    /// use implicit capture instead of analog absorption if present
+    #[arg(long = "implicit-capture", num_args(0))]
+    pub implicit_capture: bool,
+
     /// number of units that should be used to run the simulation
     #[arg(
         short = 'u',
@@ -146,12 +197,17 @@ pub fn parse_input_file<T: CustomFloat>(
 ) -> Result<(), Vec<InputError>> {
     let mut content = String::new();
 
-    let mut file = match File::open(filename) {
+    let mut file = match File::open(&filename) {
         Ok(file) => file,
         Err(_) => return Err(vec![InputError::BadInputFile]),
     };
 
     file.read_to_string(&mut content).unwrap();
+    // tables referenced by the input file are located relatively to it
+    let directory = Path::new(&filename)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
 
     let res: Vec<Result<(), InputError>> = content
         .rsplit("\n\n")
@@ -161,7 +217,10 @@ pub fn parse_input_file<T: CustomFloat>(
                 //println!("{:#?}", some_struct); // uncomment if a parsing issue occur.
 
                 match &raw_block[0..val] {
-                    "Simulation:" => params.update_simulation_parameters(some_struct),
+                    "Simulation:" => {
+                        params.update_simulation_parameters(some_struct)?;
+                        params.simulation_params.load_weight_windows(&directory)
+                    }
                     "Geometry:" => match GeometryParameters::from_block(some_struct) {
                         Ok(some_geometry) => {
                             params.add_geometry_parameter(some_geometry);
@@ -176,8 +235,23 @@ pub fn parse_input_file<T: CustomFloat>(
                         }
                         Err(e) => Err(e),
                     },
+                    "Isotope:" => match IsotopeParameters::from_block(some_struct) {
+                        Ok(some_isotope) => {
+                            params.add_isotope_parameter(some_isotope);
+                            return Ok(());
+                        }
+                        Err(e) => Err(e),
+                    },
+                    "Source:" => match SourceParameters::from_block(some_struct) {
+                        Ok(some_source) => {
+                            params.add_source_parameter(some_source);
+                            return Ok(());
+                        }
+                        Err(e) => Err(e),
+                    },
                     "CrossSection:" => match CrossSectionParameters::from_block(some_struct) {
-                        Ok(some_cross_section) => {
+                        Ok(mut some_cross_section) => {
+                            some_cross_section.load_table(&directory)?;
                             params.add_cross_section_parameter(some_cross_section);
                             return Ok(());
                         }
@@ -197,3 +271,125 @@ pub fn parse_input_file<T: CustomFloat>(
     }
     Err(errors)
 }
+
+/// Reads a two-column table from the specified file and returns its columns,
+/// i.e. an energy grid and the associated values. Columns can be separated by
+/// whitespaces and/or commas; empty lines, lines starting with `#` and a
+/// leading header line are ignored. The function will fail if:
+/// - it cannot read or find the file
+/// - a line does not hold exactly two numbers
+/// - the energy grid is not strictly increasing or not positive
+/// - a value is negative
+pub fn read_table<T: CustomFloat>(
+    filename: impl AsRef<Path>,
+) -> Result<(Vec<T>, Vec<T>), InputError> {
+    let mut content = String::new();
+    match File::open(filename) {
+        Ok(mut file) => {
+            if file.read_to_string(&mut content).is_err() {
+                return Err(InputError::BadTableFile);
+            }
+        }
+        Err(_) => return Err(InputError::BadTableFile),
+    }
+
+    let mut energies: Vec<T> = Vec::new();
+    let mut values: Vec<T> = Vec::new();
+    let lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !(line.is_empty() | line.starts_with('#')));
+    for (line_idx, line) in lines.enumerate() {
+        let items: Vec<&str> = line
+            .split(|c: char| (c == ',') | c.is_whitespace())
+            .filter(|item| !item.is_empty())
+            .collect();
+        match (items.len(), items.first().map(|item| item.parse::<T>())) {
+            (2, Some(Ok(energy))) => match items[1].parse::<T>() {
+                Ok(value) => {
+                    energies.push(energy);
+                    values.push(value);
+                }
+                Err(_) => return Err(InputError::BadTableFile),
+            },
+            // header line
+            (_, Some(Err(_))) if line_idx == 0 => (),
+            _ => return Err(InputError::BadTableFile),
+        }
+    }
+
+    let increasing = energies.windows(2).all(|pair| pair[0] < pair[1]);
+    let positive = energies.first().is_some_and(|e| *e > T::zero());
+    if !increasing | !positive | values.iter().any(|v| *v < T::zero()) {
+        return Err(InputError::BadTableFile);
+    }
+    Ok((energies, values))
+}
+
+/// Reads weight windows from the specified file. Each line holds a window:
+/// the global index of a cell, an energy group, the lower bound and the upper
+/// bound. Cell or group can be replaced by `*` for the window to apply to all
+/// cells or groups; later windows take priority over earlier ones. Items can be
+/// separated by whitespaces and/or commas; empty lines, lines starting with `#`
+/// and a leading header line are ignored. The function will fail if:
+/// - it cannot read or find the file
+/// - a line does not hold exactly four items
+/// - the bounds are not positive and increasing
+pub fn read_weight_windows<T: CustomFloat>(
+    filename: impl AsRef<Path>,
+) -> Result<Vec<WeightWindowParameters<T>>, InputError> {
+    let mut content = String::new();
+    match File::open(filename) {
+        Ok(mut file) => {
+            if file.read_to_string(&mut content).is_err() {
+                return Err(InputError::BadWeightWindowFile);
+            }
+        }
+        Err(_) => return Err(InputError::BadWeightWindowFile),
+    }
+
+    // `*` matches everything
+    fn parse_index(item: &str) -> Result<Option<usize>, InputError> {
+        match item {
+            "*" => Ok(None),
+            _ => match item.parse() {
+                Ok(idx) => Ok(Some(idx)),
+                Err(_) => Err(InputError::BadWeightWindowFile),
+            },
+        }
+    }
+
+    let mut windows: Vec<WeightWindowParameters<T>> = Vec::new();
+    let lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !(line.is_empty() | line.starts_with('#')));
+    for (line_idx, line) in lines.enumerate() {
+        let items: Vec<&str> = line
+            .split(|c: char| (c == ',') | c.is_whitespace())
+            .filter(|item| !item.is_empty())
+            .collect();
+        match (items.len(), parse_index(items[0])) {
+            (4, Ok(cell)) => {
+                let group = parse_index(items[1])?;
+                let (lower, upper): (T, T) = match (items[2].parse(), items[3].parse()) {
+                    (Ok(lower), Ok(upper)) => (lower, upper),
+                    _ => return Err(InputError::BadWeightWindowFile),
+                };
+                if (lower <= T::zero()) | (upper <= lower) {
+                    return Err(InputError::BadWeightWindowFile);
+                }
+                windows.push(WeightWindowParameters {
+                    cell,
+                    group,
+                    lower,
+                    upper,
+                });
+            }
+            // header line
+            (_, Err(_)) if line_idx == 0 => (),
+            _ => return Err(InputError::BadWeightWindowFile),
+        }
+    }
+    Ok(windows)
+}
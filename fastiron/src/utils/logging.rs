@@ -0,0 +1,74 @@
+//! Code used to print leveled log messages
+//!
+//! This module contains a minimal implementation of the [log] facade. Messages
+//! are printed to the standard output, along with the reports of the
+//! simulation; errors & warnings are prefixed by their level, other messages
+//! are printed as-is.
+//!
+//! The logger is set up using the command line only, before the input file is
+//! read, so that parsing errors can be reported.
+
+use clap::ValueEnum;
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Enum used to represent the verbosity of the program.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogLevel {
+    /// Only print errors.
+    Error,
+    /// Print errors & warnings.
+    Warn,
+    /// Print errors, warnings & general information on the run.
+    #[default]
+    Info,
+    /// Print everything, including the steps of the initialization.
+    Debug,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+        }
+    }
+}
+
+/// Logger printing messages to the standard output.
+struct StdoutLogger;
+
+impl Log for StdoutLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match record.level() {
+            log::Level::Error => println!("[ERROR] {}", record.args()),
+            log::Level::Warn => println!("[WARNING] {}", record.args()),
+            _ => println!("{}", record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StdoutLogger = StdoutLogger;
+
+/// Sets up the logger of the program. In quiet mode, only errors are printed,
+/// whatever the value of `level`. This should be called only once; following
+/// calls only update the level.
+pub fn init(level: LogLevel, quiet: bool) {
+    // fails if a logger is already set up, which is fine
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(if quiet {
+        LevelFilter::Error
+    } else {
+        level.into()
+    });
+}
@@ -4,16 +4,22 @@
 
 use std::{
     fmt::Display,
-    fs::OpenOptions,
-    io::Write,
+    io::{Result, Write},
     time::{Duration, Instant},
 };
 
-use crate::constants::sim::N_TIMERS;
+use crate::{
+    constants::{sim::N_TIMERS, CustomFloat},
+    parameters::SimulationParameters,
+    utils::{
+        output::{self, TIMERS_REPORT},
+        trace::Tracer,
+    },
+};
 
 /// Enum used to identify sections and their corresponding
 /// timers.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Section {
     /// Full execution time.
     Main = 0,
@@ -29,6 +35,30 @@ pub enum Section {
     CycleSync,
 }
 
+impl Section {
+    /// All sections, in index order.
+    pub const ALL: [Section; N_TIMERS] = [
+        Section::Main,
+        Section::PopulationControl,
+        Section::CycleTracking,
+        Section::CycleTrackingProcess,
+        Section::CycleTrackingSort,
+        Section::CycleSync,
+    ];
+
+    /// Returns the name of the section, in snake case.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Section::Main => "main",
+            Section::PopulationControl => "population_control",
+            Section::CycleTracking => "cycle_tracking",
+            Section::CycleTrackingProcess => "cycle_tracking_process",
+            Section::CycleTrackingSort => "cycle_tracking_sort",
+            Section::CycleSync => "cycle_sync",
+        }
+    }
+}
+
 impl Display for Section {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -89,6 +119,9 @@ pub struct MCFastTimerContainer {
     /// Total duration of each timer. This is the value that should be compared to
     /// Quicksilver's cumulative report despite what its header says.
     pub tots: [Duration; N_TIMERS],
+    /// Per-thread event recorder. Each timer measurement is recorded as an
+    /// event of the main thread. See [Tracer] for more information.
+    pub tracer: Tracer,
 }
 
 impl MCFastTimerContainer {
@@ -112,7 +145,14 @@ impl MCFastTimerContainer {
     /// [`Section::PopulationControl`] and [`Section::CycleSync`] do not really have an
     /// equivalent in Quicksilver, the comparable values are those of [`Section::Main`]
     /// and [`Section::CycleTracking`] as well as the figure of merit.
-    pub fn cumulative_report(&self, num_segments: u64, csv: bool) {
+    ///
+    /// If the `csv` switch of the parameters is set, the data is also written
+    /// to the [`TIMERS_REPORT`] output file.
+    pub fn cumulative_report<T: CustomFloat>(
+        &self,
+        num_segments: u64,
+        sim_params: &SimulationParameters<T>,
+    ) -> Result<()> {
         // Print header
         println!("[Timer Report]");
         println!(
@@ -124,35 +164,34 @@ impl MCFastTimerContainer {
             "Total in section (µs)",
             "Efficiency rating (%)",
         );
-        if csv {
-            let mut file = OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open("timers_report.csv")
-                .unwrap();
+        let mut file = if sim_params.csv {
+            let mut file = output::create_output(sim_params, TIMERS_REPORT)?;
             writeln!(
                 file,
                 "Timer Name;#calls;Shortest(µs);Average(µs);Longest(µs);Total(µs);Efficiency(%)",
-            )
-            .unwrap();
-        }
+            )?;
+            Some(file)
+        } else {
+            None
+        };
         // print data
-        self.timers
-            .iter()
-            .enumerate()
-            .for_each(|(timer_idx, timer)| {
-                let section = match timer_idx {
-                    0 => Section::Main,
-                    1 => Section::PopulationControl,
-                    2 => Section::CycleTracking,
-                    3 => Section::CycleTrackingProcess,
-                    4 => Section::CycleTrackingSort,
-                    5 => Section::CycleSync,
-                    _ => unreachable!(),
-                };
-                println!(
-                    "{} | {:>16} | {:>14.6e}      | {:>17.6e}      | {:>13.6e}      | {:>16.6e}      | {:>17.1}",
+        for (timer_idx, timer) in self.timers.iter().enumerate() {
+            let section = Section::ALL[timer_idx];
+            println!(
+                "{} | {:>16} | {:>14.6e}      | {:>17.6e}      | {:>13.6e}      | {:>16.6e}      | {:>17.1}",
+                section,
+                timer.num_calls,
+                self.mins[timer_idx].as_micros(),
+                self.avgs[timer_idx].as_micros(),
+                self.maxs[timer_idx].as_micros(),
+                self.tots[timer_idx].as_micros(),
+                (100.0 * self.avgs[timer_idx].as_secs_f64())
+                    / (self.maxs[timer_idx].as_secs_f64() + 1.0e-80),
+            );
+            if let Some(file) = &mut file {
+                writeln!(
+                    file,
+                    "{};{};{:e};{:e};{:e};{:e};{:.1}",
                     section,
                     timer.num_calls,
                     self.mins[timer_idx].as_micros(),
@@ -160,29 +199,15 @@ impl MCFastTimerContainer {
                     self.maxs[timer_idx].as_micros(),
                     self.tots[timer_idx].as_micros(),
                     (100.0 * self.avgs[timer_idx].as_secs_f64())
-                        / (self.maxs[timer_idx].as_secs_f64() + 1.0e-80),
-                );
-                if csv {
-                    let mut file = OpenOptions::new()
-                        .append(true)
-                        .open("timers_report.csv")
-                        .unwrap();
-                    writeln!(file, "{};{};{:e};{:e};{:e};{:e};{:.1}", 
-                        section,
-                        timer.num_calls,
-                        self.mins[timer_idx].as_micros(),
-                        self.avgs[timer_idx].as_micros(),
-                        self.maxs[timer_idx].as_micros(),
-                        self.tots[timer_idx].as_micros(),
-                        (100.0 * self.avgs[timer_idx].as_secs_f64())
-                            / (self.maxs[timer_idx].as_secs_f64() + 1.0e-80)
-                    ).unwrap();
-                };
-            });
+                        / (self.maxs[timer_idx].as_secs_f64() + 1.0e-80)
+                )?;
+            };
+        }
         println!(
             "Figure of merit: {:>.3e} [segments / cycle tracking time]",
             (num_segments as f64) / (self.tots[Section::CycleTracking as usize].as_secs_f64())
         );
+        Ok(())
     }
 
     /// Update statistics and clear the timers for the next cycle.
@@ -235,6 +260,7 @@ impl Default for MCFastTimerContainer {
             maxs: [Duration::ZERO; N_TIMERS],
             mins: [Duration::MAX; N_TIMERS],
             tots: [Duration::ZERO; N_TIMERS],
+            tracer: Default::default(),
         }
     }
 }
@@ -249,6 +275,13 @@ pub fn start(timer_container: &mut MCFastTimerContainer, section: Section) {
 pub fn stop(timer_container: &mut MCFastTimerContainer, section: Section) {
     let index = section as usize;
     timer_container.timers[index].end_clock = Instant::now();
+    timer_container.tracer.record_span(
+        section.name(),
+        "section",
+        timer_container.timers[index].start_clock,
+        timer_container.timers[index].end_clock,
+        &[],
+    );
     timer_container.timers[index].last_cycle_clock += timer_container.timers[index]
         .end_clock
         .duration_since(timer_container.timers[index].start_clock);
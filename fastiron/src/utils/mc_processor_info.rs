@@ -5,10 +5,12 @@
 
 use std::fmt::Display;
 
+use serde::Serialize;
+
 use crate::{constants::CustomFloat, parameters::SimulationParameters};
 
 /// Enum used to represent the execution mode of the simulation.
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
 pub enum ExecPolicy {
     /// Default value. Sequential execution.
     #[default]
@@ -30,7 +32,7 @@ impl Display for ExecPolicy {
 }
 
 /// Structure holding execution information of a given run.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct MCProcessorInfo {
     /// Execution mode.
     pub exec_policy: ExecPolicy,
@@ -0,0 +1,339 @@
+//! Code used to account for the memory footprint of the simulation
+//!
+//! This module contains the structures used to estimate the memory used by
+//! the main data structures of the simulation, and to track the reallocations
+//! of the particle collections over cycles. Sizes are computed from the
+//! capacity of the vectors held by each structure: they represent allocated
+//! memory rather than used memory, and ignore the allocator's overhead.
+//!
+//! The peak resident set size of the process is read from `/proc/self/status`
+//! when available, i.e. on Linux.
+
+use std::mem::size_of;
+
+use serde::Serialize;
+
+use crate::{
+    constants::CustomFloat,
+    data::nuclear_data::{NuBar, NuclearData, ScatteringLaw},
+    geometry::mc_domain::MCDomain,
+    montecarlo::{MonteCarloData, MonteCarloResults, MonteCarloUnit},
+    particles::{mc_particle::MCParticle, particle_container::ParticleContainer},
+};
+
+/// Number of bytes in a mebibyte.
+const MIB: f64 = (1 << 20) as f64;
+
+/// Structure used to hold the memory state of the particle collections at
+/// the end of a cycle.
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleMemory {
+    /// Index of the cycle.
+    pub cycle: usize,
+    /// Memory allocated for the particle collections, in bytes.
+    pub particles: usize,
+    /// Number of reallocations of the particle collections during the cycle.
+    pub reallocations: u64,
+}
+
+impl CycleMemory {
+    /// Constructor. The reallocation counters of the containers are reset.
+    pub fn new<T: CustomFloat>(cycle: usize, containers: &mut [ParticleContainer<T>]) -> Self {
+        Self {
+            cycle,
+            particles: particles_size(containers),
+            reallocations: containers
+                .iter_mut()
+                .map(|container| container.take_reallocations())
+                .sum(),
+        }
+    }
+}
+
+/// Structure used to hold the memory allocated for each data structure of
+/// the simulation. Sizes are given in bytes.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct MemoryFootprint {
+    /// Particle collections of the containers.
+    pub particles: usize,
+    /// Per-chunk extra buffers used when processing particles in parallel, at
+    /// their peak. These are only allocated during tracking.
+    pub chunk_buffers: usize,
+    /// Mesh, cell states & cell index maps of the domains.
+    pub mesh: usize,
+    /// Cross-section caches.
+    pub xs_cache: usize,
+    /// Scalar flux tallies, source tallies, fluence & cell fluxes.
+    pub tallies: usize,
+    /// Nuclear data & material database.
+    pub nuclear_data: usize,
+}
+
+impl MemoryFootprint {
+    /// Constructor. Computes the size of each structure.
+    pub fn new<T: CustomFloat>(
+        mcdata: &MonteCarloData<T>,
+        mcunits: &[MonteCarloUnit<T>],
+        containers: &[ParticleContainer<T>],
+        mcresults: &MonteCarloResults<T>,
+    ) -> Self {
+        let cell_flux = mcresults.cell_flux.as_ref().map_or(0, |cell_flux| {
+            cell_flux.iter().map(vec_size).sum::<usize>() + vec_size(cell_flux)
+        });
+        let materials: usize = mcdata
+            .material_database
+            .mat
+            .iter()
+            .map(|material| vec_size(&material.iso))
+            .sum::<usize>()
+            + vec_size(&mcdata.material_database.mat)
+            + vec_size(&mcdata.material_database.cell_materials);
+        Self {
+            particles: particles_size(containers),
+            chunk_buffers: containers
+                .iter()
+                .map(|container| container.peak_chunk_capacity * size_of::<MCParticle<T>>())
+                .sum(),
+            mesh: mcunits.iter().map(|mcunit| mesh_size(&mcunit.domain)).sum(),
+            xs_cache: mcunits
+                .iter()
+                .map(|mcunit| vec_size(&mcunit.xs_cache.cache))
+                .sum(),
+            tallies: mcunits
+                .iter()
+                .map(|mcunit| {
+                    vec_size(&mcunit.tallies.scalar_flux_domain.cell)
+                        + vec_size(&mcunit.tallies.photon_flux_domain.cell)
+                        + vec_size(&mcunit.source_tallies)
+                })
+                .sum::<usize>()
+                + vec_size(&mcresults.fluence.cell)
+                + cell_flux,
+            nuclear_data: nuclear_data_size(&mcdata.nuclear_data) + materials,
+        }
+    }
+
+    /// Returns the sum of all sizes, in bytes.
+    pub fn total(&self) -> usize {
+        self.particles
+            + self.chunk_buffers
+            + self.mesh
+            + self.xs_cache
+            + self.tallies
+            + self.nuclear_data
+    }
+}
+
+/// Structure used to report the memory usage of a run.
+#[derive(Debug, Serialize)]
+pub struct MemoryReport {
+    /// Memory allocated for each data structure at the end of the simulation.
+    /// Particle collections never shrink, so this is also their peak.
+    pub footprint: MemoryFootprint,
+    /// Sum of the footprint, in bytes.
+    pub total: usize,
+    /// Peak resident set size of the process, in bytes. `None` if it could
+    /// not be determined.
+    pub peak_rss: Option<usize>,
+    /// Per-cycle state of the particle collections, see [CycleMemory].
+    pub cycles: Vec<CycleMemory>,
+}
+
+impl MemoryReport {
+    /// Constructor.
+    pub fn new<T: CustomFloat>(
+        mcdata: &MonteCarloData<T>,
+        mcunits: &[MonteCarloUnit<T>],
+        containers: &[ParticleContainer<T>],
+        mcresults: &MonteCarloResults<T>,
+    ) -> Self {
+        let footprint = MemoryFootprint::new(mcdata, mcunits, containers, mcresults);
+        Self {
+            footprint,
+            total: footprint.total(),
+            peak_rss: std::fs::read_to_string("/proc/self/status")
+                .ok()
+                .and_then(|status| parse_peak_rss(&status)),
+            cycles: mcresults.memory.clone(),
+        }
+    }
+
+    /// Prints the report. Only cycles during which the particle collections
+    /// were reallocated are listed. The output looks like the following:
+    ///
+    /// ```shell
+    /// [Memory Report]
+    /// structure     |  size (MiB)
+    /// particles     |     101.498
+    /// chunk buffers |      92.275
+    /// mesh          |       9.445
+    /// xs cache      |       4.198
+    /// tallies       |       3.074
+    /// nuclear data  |       0.079
+    /// total         |     210.569
+    /// peak RSS      |     236.852
+    /// cycle | particles (MiB) | reallocations
+    ///     0 |          92.275 |             2
+    /// ```
+    pub fn print(&self) {
+        let fp = &self.footprint;
+        println!("[Memory Report]");
+        println!("{:<13} | {:>11}", "structure", "size (MiB)");
+        [
+            ("particles", fp.particles),
+            ("chunk buffers", fp.chunk_buffers),
+            ("mesh", fp.mesh),
+            ("xs cache", fp.xs_cache),
+            ("tallies", fp.tallies),
+            ("nuclear data", fp.nuclear_data),
+            ("total", self.total),
+        ]
+        .iter()
+        .for_each(|(name, size)| println!("{:<13} | {:>11.3}", name, *size as f64 / MIB));
+        if let Some(peak_rss) = self.peak_rss {
+            println!("{:<13} | {:>11.3}", "peak RSS", peak_rss as f64 / MIB);
+        }
+        let reallocated: Vec<&CycleMemory> = self
+            .cycles
+            .iter()
+            .filter(|cycle| cycle.reallocations != 0)
+            .collect();
+        if reallocated.is_empty() {
+            println!("No reallocation of the particle collections");
+        } else {
+            println!("cycle | particles (MiB) | reallocations");
+            reallocated.iter().for_each(|cycle| {
+                println!(
+                    "{:>5} | {:>15.3} | {:>13}",
+                    cycle.cycle,
+                    cycle.particles as f64 / MIB,
+                    cycle.reallocations
+                )
+            });
+        }
+    }
+}
+
+//==================
+// Private functions
+//==================
+
+fn vec_size<U>(vec: &Vec<U>) -> usize {
+    vec.capacity() * size_of::<U>()
+}
+
+fn particles_size<T: CustomFloat>(containers: &[ParticleContainer<T>]) -> usize {
+    containers
+        .iter()
+        .map(|container| container.capacity() * size_of::<MCParticle<T>>())
+        .sum()
+}
+
+fn mesh_size<T: CustomFloat>(domain: &MCDomain<T>) -> usize {
+    let mesh = &domain.mesh;
+    // hash map entries hold a control byte along with the key & value
+    let map_size = domain.cell_idx_map.capacity() * (2 * size_of::<usize>() + 1);
+    vec_size(&mesh.nbr_domain_gid)
+        + vec_size(&mesh.nbr_rank)
+        + vec_size(&mesh.node)
+        + vec_size(&mesh.cell_connectivity)
+        + vec_size(&mesh.cell_geometry)
+        + vec_size(&domain.cell_state)
+        + map_size
+}
+
+fn nuclear_data_size<T: CustomFloat>(nuclear_data: &NuclearData<T>) -> usize {
+    let reactions: usize = nuclear_data
+        .isotopes
+        .iter()
+        .flat_map(|isotope| isotope.iter())
+        .flat_map(|species| species.reactions.iter())
+        .map(|reaction| {
+            let nu_bar = match &reaction.nu_bar {
+                NuBar::Tabulated { energies, values } => vec_size(energies) + vec_size(values),
+                _ => 0,
+            };
+            let scattering = match &reaction.scattering {
+                ScatteringLaw::Elastic { legendre } => vec_size(legendre),
+                _ => 0,
+            };
+            vec_size(&reaction.cross_section) + nu_bar + scattering
+        })
+        .sum();
+    let species: usize = nuclear_data
+        .isotopes
+        .iter()
+        .map(|isotope| {
+            vec_size(isotope)
+                + isotope
+                    .iter()
+                    .map(|species| vec_size(&species.reactions))
+                    .sum::<usize>()
+        })
+        .sum();
+    vec_size(&nuclear_data.isotopes) + vec_size(&nuclear_data.energies) + species + reactions
+}
+
+fn parse_peak_rss(status: &str) -> Option<usize> {
+    // line format: "VmHWM:     12345 kB"
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let mut words = line.split_whitespace().skip(1);
+    let value: usize = words.next()?.parse().ok()?;
+    match words.next() {
+        Some("kB") => Some(value * 1024),
+        _ => None,
+    }
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::particles::particle_collection::ParticleCollection;
+
+    #[test]
+    fn reallocations() {
+        let mut container: ParticleContainer<f64> = ParticleContainer::new(4, 2);
+        container
+            .extra_particles
+            .extend(vec![MCParticle::default(); 2]);
+        assert_eq!(container.take_reallocations(), 0);
+
+        // grows the extra storage
+        container
+            .extra_particles
+            .extend(vec![MCParticle::default(); 3]);
+        // grows the processing storage, empties the extra storage
+        container.clean_extra_vaults();
+        assert_eq!(container.processing_particles.len(), 5);
+        assert_eq!(container.take_reallocations(), 2);
+        assert_eq!(container.take_reallocations(), 0);
+
+        // appending from a larger collection does not count for the source
+        let mut other = ParticleCollection::with_capacity(100);
+        other.extend(vec![MCParticle::default(); 3]);
+        container.processed_particles.append(&mut other);
+        assert_eq!(other.reallocations(), 0);
+        assert_eq!(container.take_reallocations(), 0);
+
+        // reallocations of the chunk buffers are included
+        container.chunk_reallocations = 3;
+        assert_eq!(container.take_reallocations(), 3);
+        assert_eq!(container.take_reallocations(), 0);
+
+        let size = particles_size(&[container]);
+        assert!(size >= (5 + 4 + 5) * size_of::<MCParticle<f64>>());
+    }
+
+    #[test]
+    fn peak_rss() {
+        let status =
+            "Name:\tfastiron\nVmPeak:\t  200000 kB\nVmHWM:\t    1024 kB\nVmRSS:\t     512 kB\n";
+        assert_eq!(parse_peak_rss(status), Some(1024 * 1024));
+        assert_eq!(parse_peak_rss("VmRSS:\t 512 kB\n"), None);
+        assert_eq!(parse_peak_rss("VmHWM:\t 512\n"), None);
+    }
+}
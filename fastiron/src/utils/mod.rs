@@ -4,6 +4,13 @@ pub mod comm_object;
 pub mod coral_benchmark_correctness;
 pub mod decomposition_object;
 pub mod input;
+pub mod logging;
 pub mod mc_fast_timer;
 pub mod mc_processor_info;
 pub mod mc_rng_state;
+pub mod memory;
+pub mod output;
+pub mod progress;
+pub mod report;
+pub mod trace;
+pub mod vtk;
@@ -0,0 +1,261 @@
+//! Code used to name & create output files
+//!
+//! All files written by the program are placed in the output directory of the
+//! simulation and prefixed by its run name, both of which are empty by default.
+//! The two values may contain the following placeholders, replaced when the
+//! parameters are read:
+//! - `{threads}`: number of rayon threads; a value of `0` is replaced by the
+//!   number of hardware threads available.
+//! - `{units}`: number of units.
+//! - `{particles}`: target number of particles.
+//! - `{steps}`: number of steps.
+//! - `{seed}`: seed of the simulation.
+//! - `{timestamp}`: time at which the parameters were read, in seconds since
+//!   the Unix epoch.
+//!
+//! Existing files are not replaced unless the `overwrite` switch is set.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Error, ErrorKind, Result},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{constants::CustomFloat, parameters::SimulationParameters};
+
+/// Name of the tallies report file.
+pub const TALLIES_REPORT: &str = "tallies_report.csv";
+/// Name of the timers report file.
+pub const TIMERS_REPORT: &str = "timers_report.csv";
+
+/// Replaces the placeholders of the output directory & run name of the
+/// parameters.
+pub fn expand_output_names<T: CustomFloat>(sim_params: &mut SimulationParameters<T>) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    sim_params.output_dir = expand_template(&sim_params.output_dir, sim_params, timestamp);
+    sim_params.run_name = expand_template(&sim_params.run_name, sim_params, timestamp);
+}
+
+/// Replaces the placeholders of a template. See the module-level
+/// documentation for the list of placeholders.
+pub fn expand_template<T: CustomFloat>(
+    template: &str,
+    sim_params: &SimulationParameters<T>,
+    timestamp: u64,
+) -> String {
+    let threads = match sim_params.n_rayon_threads {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n as usize,
+    };
+    template
+        .replace("{threads}", &threads.to_string())
+        .replace("{units}", &sim_params.n_units.to_string())
+        .replace("{particles}", &sim_params.n_particles.to_string())
+        .replace("{steps}", &sim_params.n_steps.to_string())
+        .replace("{seed}", &sim_params.seed.to_string())
+        .replace("{timestamp}", &timestamp.to_string())
+}
+
+/// Returns the path of an output file. The run name is prepended to the name
+/// of the file, and relative paths are resolved from the output directory.
+pub fn output_path<T: CustomFloat>(
+    sim_params: &SimulationParameters<T>,
+    file_name: &str,
+) -> PathBuf {
+    let path = Path::new(file_name);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = if sim_params.run_name.is_empty() {
+        name
+    } else {
+        format!("{}_{}", sim_params.run_name, name)
+    };
+    Path::new(&sim_params.output_dir)
+        .join(path.parent().unwrap_or(Path::new("")))
+        .join(name)
+}
+
+/// Creates an output file, along with its parent directories. This fails if
+/// the file already exists, unless the `overwrite` switch is set.
+pub fn create_output<T: CustomFloat>(
+    sim_params: &SimulationParameters<T>,
+    file_name: &str,
+) -> Result<File> {
+    let path = output_path(sim_params, file_name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if sim_params.overwrite {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    options.open(&path).map_err(|e| match e.kind() {
+        ErrorKind::AlreadyExists => Error::new(
+            ErrorKind::AlreadyExists,
+            format!(
+                "{} already exists, use --overwrite to replace it",
+                path.display()
+            ),
+        ),
+        _ => e,
+    })
+}
+
+/// Opens an existing output file to append data to it.
+pub fn append_output<T: CustomFloat>(
+    sim_params: &SimulationParameters<T>,
+    file_name: &str,
+) -> Result<File> {
+    OpenOptions::new()
+        .append(true)
+        .open(output_path(sim_params, file_name))
+}
+
+/// Returns the name of the intermediate VTK file written at the end of the
+/// specified step, without extension nor run name.
+pub fn vtk_step_name<T: CustomFloat>(sim_params: &SimulationParameters<T>, step: usize) -> String {
+    format!("{}_{}", sim_params.vtk_out, step)
+}
+
+/// Returns the paths of the files written by the simulation, including the
+/// intermediate VTK files.
+pub fn output_files<T: CustomFloat>(sim_params: &SimulationParameters<T>) -> Vec<PathBuf> {
+    let mut files: Vec<String> = Vec::new();
+    if sim_params.csv {
+        files.push(TALLIES_REPORT.to_string());
+        files.push(TIMERS_REPORT.to_string());
+    }
+    if !sim_params.energy_spectrum.is_empty() {
+        files.push(sim_params.energy_spectrum.to_owned() + ".dat");
+    }
+    if !sim_params.cross_sections_out.is_empty() {
+        files.push(sim_params.cross_sections_out.to_owned() + ".dat");
+    }
+    if !sim_params.vtk_out.is_empty() {
+        files.push(sim_params.vtk_out.to_owned() + ".vtk");
+        if sim_params.vtk_frequency != 0 {
+            (1..=sim_params.n_steps)
+                .filter(|step| step.is_multiple_of(sim_params.vtk_frequency))
+                .for_each(|step| files.push(vtk_step_name(sim_params, step) + ".vtk"));
+        }
+    }
+    if !sim_params.track_out.is_empty() {
+        files.push(sim_params.track_out.to_owned() + ".csv");
+        if sim_params.track_vtk {
+            files.push(sim_params.track_out.to_owned() + ".vtk");
+        }
+    }
+    if !sim_params.json_out.is_empty() {
+        files.push(sim_params.json_out.to_owned() + ".json");
+    }
+    if !sim_params.trace_out.is_empty() {
+        files.push(sim_params.trace_out.to_owned() + ".json");
+    }
+    files
+        .iter()
+        .map(|file_name| output_path(sim_params, file_name))
+        .collect()
+}
+
+/// Returns the output files that would be replaced by the simulation. The
+/// list is empty if the `overwrite` switch is set.
+pub fn existing_outputs<T: CustomFloat>(sim_params: &SimulationParameters<T>) -> Vec<PathBuf> {
+    if sim_params.overwrite {
+        return Vec::new();
+    }
+    output_files(sim_params)
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect()
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn naming() {
+        let mut sim_params = SimulationParameters::<f64> {
+            output_dir: "out/{threads}t".to_string(),
+            run_name: "run_{seed}_{timestamp}".to_string(),
+            n_rayon_threads: 8,
+            seed: 42,
+            ..Default::default()
+        };
+        assert_eq!(
+            expand_template(&sim_params.run_name, &sim_params, 1234),
+            "run_42_1234"
+        );
+        sim_params.output_dir = expand_template(&sim_params.output_dir, &sim_params, 1234);
+        sim_params.run_name = expand_template(&sim_params.run_name, &sim_params, 1234);
+        assert_eq!(
+            output_path(&sim_params, "tallies_report.csv"),
+            Path::new("out/8t/run_42_1234_tallies_report.csv")
+        );
+        // the run name prefixes the file name, not its directory
+        assert_eq!(
+            output_path(&sim_params, "sub/mesh.vtk"),
+            Path::new("out/8t/sub/run_42_1234_mesh.vtk")
+        );
+        // absolute paths ignore the output directory
+        sim_params.run_name.clear();
+        assert_eq!(
+            output_path(&sim_params, "/tmp/mesh.vtk"),
+            Path::new("/tmp/mesh.vtk")
+        );
+    }
+
+    #[test]
+    fn overwrite() {
+        let dir = std::env::temp_dir().join("fastiron_output_overwrite");
+        let mut sim_params = SimulationParameters::<f64> {
+            output_dir: dir.to_string_lossy().into_owned(),
+            json_out: "report".to_string(),
+            ..Default::default()
+        };
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(existing_outputs(&sim_params).is_empty());
+        assert!(create_output(&sim_params, "report.json").is_ok());
+        assert_eq!(existing_outputs(&sim_params), vec![dir.join("report.json")]);
+        let err = create_output(&sim_params, "report.json").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        sim_params.overwrite = true;
+        assert!(existing_outputs(&sim_params).is_empty());
+        assert!(create_output(&sim_params, "report.json").is_ok());
+
+        // intermediate VTK files are checked too
+        sim_params.overwrite = false;
+        sim_params.vtk_out = "mesh".to_string();
+        sim_params.vtk_frequency = 2;
+        sim_params.n_steps = 5;
+        assert_eq!(
+            output_files(&sim_params),
+            vec![
+                dir.join("mesh.vtk"),
+                dir.join("mesh_2.vtk"),
+                dir.join("mesh_4.vtk"),
+                dir.join("report.json")
+            ]
+        );
+        assert!(create_output(&sim_params, &(vtk_step_name(&sim_params, 4) + ".vtk")).is_ok());
+        assert_eq!(
+            existing_outputs(&sim_params),
+            vec![dir.join("mesh_4.vtk"), dir.join("report.json")]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,281 @@
+//! Code used to report the progress of the tracking
+//!
+//! This module contains the structure used to report the progress of a cycle
+//! while particles are being tracked. Threads tracking particles accumulate
+//! their counts locally and periodically add them to shared atomic counters,
+//! see [ProgressCounter]. The thread adding its counts after the report
+//! interval has elapsed prints the report; no additional thread is spawned.
+//!
+//! Reports are printed as `info` log messages and look like the following:
+//!
+//! ```shell
+//! [Progress] cycle 3/10 | 412352/1048576 particles (39.3%) | 2.311e7 segments/s | cycle ETA 2.1s | run ETA 1m 15.4s
+//! ```
+//!
+//! The number of particles to track grows during the cycle as secondary
+//! particles are produced, so estimates are only indicative.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::data::tallies::{Balance, TalliedEvent};
+
+/// Number of particles tracked by a thread before its counts are added to
+/// the shared counters.
+pub const PROGRESS_BATCH: u64 = 256;
+
+/// Structure used to track the progress of the simulation.
+#[derive(Debug)]
+pub struct Progress {
+    /// Interval between two reports. Reporting is disabled if zero.
+    interval: Duration,
+    /// Total number of cycles of the simulation.
+    n_cycles: usize,
+    /// Index of the current cycle.
+    cycle: usize,
+    /// Start of the first cycle.
+    run_start: Option<Instant>,
+    /// Start of the tracking of the current cycle.
+    tracking_start: Instant,
+    /// Number of particles to track during the current cycle, including the
+    /// secondary particles produced so far.
+    queued: AtomicU64,
+    /// Number of particles tracked during the current cycle.
+    tracked: AtomicU64,
+    /// Number of segments of the current cycle.
+    segments: AtomicU64,
+    /// Time of the next report, in nanoseconds since the start of the tracking.
+    next_report: AtomicU64,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self::new(0.0, 0)
+    }
+}
+
+impl Progress {
+    /// Constructor. Reporting is disabled if `interval` (in seconds) is not
+    /// strictly positive.
+    pub fn new(interval: f64, n_cycles: usize) -> Self {
+        Self {
+            interval: Duration::try_from_secs_f64(interval).unwrap_or_default(),
+            n_cycles,
+            cycle: 0,
+            run_start: None,
+            tracking_start: Instant::now(),
+            queued: AtomicU64::new(0),
+            tracked: AtomicU64::new(0),
+            segments: AtomicU64::new(0),
+            next_report: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns true if progress is reported.
+    pub fn is_enabled(&self) -> bool {
+        !self.interval.is_zero()
+    }
+
+    /// Sets the index of the current cycle.
+    pub fn start_cycle(&mut self, cycle: usize) {
+        self.cycle = cycle;
+        self.run_start.get_or_insert_with(Instant::now);
+    }
+
+    /// Resets the counters before tracking `n_particles` particles.
+    pub fn start_tracking(&mut self, n_particles: usize) {
+        self.tracking_start = Instant::now();
+        *self.queued.get_mut() = n_particles as u64;
+        *self.tracked.get_mut() = 0;
+        *self.segments.get_mut() = 0;
+        *self.next_report.get_mut() = self.interval.as_nanos() as u64;
+    }
+
+    /// Adds secondary particles to the number of particles to track.
+    pub fn add_queued(&self, n_particles: usize) {
+        if self.is_enabled() {
+            self.queued.fetch_add(n_particles as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Adds tracked particles & segments to the counters, and prints a report
+    /// if the interval has elapsed since the last one.
+    pub fn add_tracked(&self, n_particles: u64, n_segments: u64) {
+        self.tracked.fetch_add(n_particles, Ordering::Relaxed);
+        self.segments.fetch_add(n_segments, Ordering::Relaxed);
+
+        let elapsed = self.tracking_start.elapsed().as_nanos() as u64;
+        let next_report = self.next_report.load(Ordering::Relaxed);
+        // only one thread wins the right to report
+        if (elapsed >= next_report)
+            && self
+                .next_report
+                .compare_exchange(
+                    next_report,
+                    elapsed + self.interval.as_nanos() as u64,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+        {
+            log::info!("{}", self.report());
+        }
+    }
+
+    /// Returns the current state of the cycle as a single line report.
+    pub fn report(&self) -> String {
+        let queued = self.queued.load(Ordering::Relaxed);
+        let tracked = self.tracked.load(Ordering::Relaxed);
+        let segments = self.segments.load(Ordering::Relaxed);
+        let elapsed = self.tracking_start.elapsed().as_secs_f64();
+
+        let fraction = if queued == 0 {
+            0.0
+        } else {
+            (tracked as f64 / queued as f64).min(1.0)
+        };
+        let cycle_eta = if fraction > 0.0 {
+            elapsed * (1.0 - fraction) / fraction
+        } else {
+            f64::NAN
+        };
+        // assume all cycles take as long as the ones done so far
+        let run_eta = self.run_start.map_or(f64::NAN, |start| {
+            let done = self.cycle as f64 + fraction;
+            if done > 0.0 {
+                start.elapsed().as_secs_f64() * (self.n_cycles as f64 - done) / done
+            } else {
+                f64::NAN
+            }
+        });
+
+        format!(
+            "[Progress] cycle {}/{} | {}/{} particles ({:.1}%) | {:.3e} segments/s | cycle ETA {} | run ETA {}",
+            self.cycle + 1,
+            self.n_cycles,
+            tracked,
+            queued,
+            100.0 * fraction,
+            segments as f64 / elapsed.max(1.0e-9),
+            format_duration(cycle_eta),
+            format_duration(run_eta),
+        )
+    }
+}
+
+/// Structure used to count the particles tracked by a thread before adding
+/// them to the shared counters of a [Progress] object.
+pub struct ProgressCounter<'a> {
+    /// Shared progress object.
+    progress: &'a Progress,
+    /// Number of particles tracked since the last update.
+    particles: u64,
+    /// Number of segments tallied in the balance at the last update.
+    segments: u64,
+}
+
+impl<'a> ProgressCounter<'a> {
+    /// Constructor. `balance` is the balance the tracking results are
+    /// tallied in.
+    pub fn new(progress: &'a Progress, balance: &Balance) -> Self {
+        Self {
+            progress,
+            particles: 0,
+            segments: balance[TalliedEvent::NumSegments],
+        }
+    }
+
+    /// Counts a tracked particle. The shared counters are updated every
+    /// [PROGRESS_BATCH] particles.
+    pub fn tick(&mut self, balance: &Balance) {
+        if !self.progress.is_enabled() {
+            return;
+        }
+        self.particles += 1;
+        if self.particles == PROGRESS_BATCH {
+            self.flush(balance);
+        }
+    }
+
+    /// Adds the local counts to the shared counters.
+    pub fn flush(&mut self, balance: &Balance) {
+        if !self.progress.is_enabled() {
+            return;
+        }
+        let segments = balance[TalliedEvent::NumSegments];
+        self.progress
+            .add_tracked(self.particles, segments - self.segments);
+        self.particles = 0;
+        self.segments = segments;
+    }
+}
+
+//==================
+// Private functions
+//==================
+
+fn format_duration(seconds: f64) -> String {
+    if !seconds.is_finite() {
+        return "-".to_string();
+    }
+    let minutes = (seconds / 60.0).floor();
+    if minutes > 0.0 {
+        format!("{}m {:.1}s", minutes, seconds - 60.0 * minutes)
+    } else {
+        format!("{seconds:.1}s")
+    }
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting() {
+        let mut progress = Progress::new(1000.0, 4);
+        progress.start_cycle(1);
+        progress.start_tracking(1000);
+        let mut balance = Balance::default();
+        let mut counter = ProgressCounter::new(&progress, &balance);
+        for _ in 0..300 {
+            balance[TalliedEvent::NumSegments] += 3;
+            counter.tick(&balance);
+        }
+        // one batch flushed
+        assert_eq!(progress.tracked.load(Ordering::Relaxed), PROGRESS_BATCH);
+        counter.flush(&balance);
+        progress.add_queued(200);
+        assert_eq!(progress.tracked.load(Ordering::Relaxed), 300);
+        assert_eq!(progress.segments.load(Ordering::Relaxed), 900);
+
+        let report = progress.report();
+        assert!(report.starts_with("[Progress] cycle 2/4 | 300/1200 particles (25.0%)"));
+    }
+
+    #[test]
+    fn disabled() {
+        let mut progress = Progress::default();
+        assert!(!progress.is_enabled());
+        progress.start_tracking(10);
+        let balance = Balance::default();
+        let mut counter = ProgressCounter::new(&progress, &balance);
+        counter.tick(&balance);
+        counter.flush(&balance);
+        progress.add_queued(10);
+        assert_eq!(progress.tracked.load(Ordering::Relaxed), 0);
+        assert_eq!(progress.queued.load(Ordering::Relaxed), 10);
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(f64::NAN), "-");
+        assert_eq!(format_duration(12.34), "12.3s");
+        assert_eq!(format_duration(75.4), "1m 15.4s");
+    }
+}
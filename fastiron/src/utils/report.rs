@@ -0,0 +1,309 @@
+//! Code used to write a machine-readable report of a run
+//!
+//! This module contains the structures used to gather the data printed
+//! through the simulation (parameters, execution information, tallies,
+//! timers, correctness tests) into a single JSON file. The schema is
+//! versioned using [SCHEMA_VERSION]; fields may be added without changing
+//! the version, but renaming or removing one requires incrementing it.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufWriter, Result, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+    constants::{sim::N_SPECIES, CustomFloat},
+    data::tallies::{Balance, TalliedEvent, N_TALLIED_EVENT},
+    montecarlo::{MonteCarloData, MonteCarloResults, MonteCarloUnit},
+    parameters::SimulationParameters,
+    particles::mc_particle::Species,
+    utils::{
+        coral_benchmark_correctness::CorrectnessTest,
+        mc_fast_timer::{self, MCFastTimerContainer, Section},
+        mc_processor_info::MCProcessorInfo,
+        memory::MemoryReport,
+        output,
+    },
+};
+
+/// Version of the report's schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Names of the particle species, in index order. See [`Species::index()`].
+const SPECIES_NAMES: [&str; N_SPECIES] = ["neutron", "photon"];
+
+/// Structure used to hold the data reported at the end of a cycle. This
+/// mirrors the output of
+/// [`Tallies::print_summary()`][crate::data::tallies::Tallies::print_summary],
+/// without merging population control events.
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleReport {
+    /// Index of the cycle.
+    pub cycle: usize,
+    /// Number of events tallied during the cycle, indexed by event name.
+    pub balance: BTreeMap<&'static str, u64>,
+    /// Statistical weight lost to absorption during the cycle.
+    pub absorbed_weight: f64,
+    /// Neutron scalar flux of the cycle.
+    pub scalar_flux: f64,
+    /// Photon scalar flux of the cycle.
+    pub photon_flux: f64,
+    /// Time spent in population control, in seconds.
+    pub population_control: f64,
+    /// Time spent tracking particles, in seconds.
+    pub cycle_tracking: f64,
+    /// Time spent in the synchronization of the cycle, in seconds.
+    pub cycle_sync: f64,
+}
+
+impl CycleReport {
+    /// Constructor. This should be called before the tallies are finalized
+    /// and the cycle timers cleared.
+    pub fn new<T: CustomFloat>(cycle: usize, mcunits: &mut [MonteCarloUnit<T>]) -> Self {
+        let balance: Balance = mcunits
+            .iter()
+            .map(|mcunit| mcunit.tallies.balance_cycle)
+            .sum();
+        let flux = |species: Species| -> f64 {
+            mcunits
+                .iter()
+                .map(|mcunit| mcunit.tallies.species_flux_sum(species).to_f64().unwrap())
+                .sum()
+        };
+        let (scalar_flux, photon_flux) = (flux(Species::Neutron), flux(Species::Photon));
+        let timers = &mut mcunits[0].fast_timer;
+        Self {
+            cycle,
+            balance: event_map(&balance.data),
+            absorbed_weight: balance.total_absorbed_weight(),
+            scalar_flux,
+            photon_flux,
+            population_control: mc_fast_timer::get_last_cycle(timers, Section::PopulationControl),
+            cycle_tracking: mc_fast_timer::get_last_cycle(timers, Section::CycleTracking),
+            cycle_sync: mc_fast_timer::get_last_cycle(timers, Section::CycleSync),
+        }
+    }
+}
+
+/// Structure used to hold information about the build & the host.
+#[derive(Debug, Serialize)]
+pub struct Metadata {
+    /// Version of fastiron.
+    pub version: &'static str,
+    /// Floating point type used for the simulation.
+    pub float_type: &'static str,
+    /// Build profile, either `debug` or `release`.
+    pub profile: &'static str,
+    /// Target architecture.
+    pub arch: &'static str,
+    /// Target operating system.
+    pub os: &'static str,
+    /// Name of the host, empty if it could not be determined.
+    pub hostname: String,
+    /// Number of hardware threads available to the process.
+    pub available_parallelism: usize,
+    /// Arguments of the command line.
+    pub command_line: Vec<String>,
+    /// Time at which the report was written, in seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl Metadata {
+    /// Constructor. Fetches the information of the current process.
+    pub fn new<T: CustomFloat>() -> Self {
+        let hostname = std::fs::read_to_string("/proc/sys/kernel/hostname")
+            .map(|name| name.trim().to_string())
+            .or_else(|_| std::env::var("HOSTNAME"))
+            .unwrap_or_default();
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            float_type: std::any::type_name::<T>(),
+            profile: if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            },
+            arch: std::env::consts::ARCH,
+            os: std::env::consts::OS,
+            hostname,
+            available_parallelism: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            command_line: std::env::args().collect(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+}
+
+/// Structure used to hold the cumulative tallies of a particle species.
+#[derive(Debug, Serialize)]
+pub struct SpeciesReport {
+    /// Number of events undergone during tracking, indexed by event name.
+    pub balance: BTreeMap<&'static str, u64>,
+    /// Statistical weight lost to absorption.
+    pub absorbed_weight: f64,
+    /// Cumulative scalar flux.
+    pub scalar_flux: f64,
+}
+
+/// Structure used to hold the statistics of a timer. Durations are given
+/// in seconds.
+#[derive(Debug, Serialize)]
+pub struct TimerReport {
+    /// Name of the timed section.
+    pub name: &'static str,
+    /// Number of measurements taken.
+    pub num_calls: u64,
+    /// Shortest cycle.
+    pub min: f64,
+    /// Average cycle.
+    pub avg: f64,
+    /// Longest cycle.
+    pub max: f64,
+    /// Total time spent in the section.
+    pub total: f64,
+}
+
+/// Structure used to hold the final energy spectrum of the problem.
+#[derive(Debug, Serialize)]
+pub struct SpectrumReport {
+    /// Energy levels of the problem.
+    pub energies: Vec<f64>,
+    /// Number of particles in each energy group at the end of the simulation.
+    pub counts: Vec<u64>,
+}
+
+/// Structure used to represent the report of a run. The report is written
+/// as a single JSON object holding the fields of the structure.
+#[derive(Debug, Serialize)]
+pub struct RunReport<'a, T: CustomFloat> {
+    /// Version of the schema of the report, see [SCHEMA_VERSION].
+    pub schema_version: u32,
+    /// Build & host information.
+    pub metadata: Metadata,
+    /// Simulation parameters. Weight windows are not included.
+    pub parameters: &'a SimulationParameters<T>,
+    /// Execution information.
+    pub execution: &'a MCProcessorInfo,
+    /// Balance & timings of each cycle.
+    pub cycles: &'a [CycleReport],
+    /// Cumulative number of events, indexed by event name.
+    pub balance: BTreeMap<&'static str, u64>,
+    /// Cumulative statistical weight lost to absorption.
+    pub absorbed_weight: f64,
+    /// Cumulative tallies of each species, indexed by species name.
+    pub species: BTreeMap<&'static str, SpeciesReport>,
+    /// Statistics of the timed sections.
+    pub timers: Vec<TimerReport>,
+    /// Number of segments per second of tracking.
+    pub figure_of_merit: f64,
+    /// Final energy spectrum.
+    pub spectrum: SpectrumReport,
+    /// Memory footprint & reallocations.
+    pub memory: &'a MemoryReport,
+    /// Results of the benchmark tests.
+    pub correctness: &'a [CorrectnessTest],
+}
+
+impl<'a, T: CustomFloat> RunReport<'a, T> {
+    /// Constructor. This should be called after the statistics of the main
+    /// timer have been updated.
+    pub fn new(
+        mcdata: &'a MonteCarloData<T>,
+        mcunits: &[MonteCarloUnit<T>],
+        mcresults: &'a MonteCarloResults<T>,
+        memory: &'a MemoryReport,
+        correctness: &'a [CorrectnessTest],
+    ) -> Self {
+        let bal = &mcresults.balance_cumulative;
+        let species = SPECIES_NAMES
+            .iter()
+            .enumerate()
+            .map(|(species_idx, name)| {
+                (
+                    *name,
+                    SpeciesReport {
+                        balance: event_map(&bal.species[species_idx]),
+                        absorbed_weight: bal.absorbed_weight[species_idx],
+                        scalar_flux: mcresults.species_flux[species_idx].to_f64().unwrap(),
+                    },
+                )
+            })
+            .collect();
+        let timers = &mcunits[0].fast_timer;
+        let tracking_time = timers.tots[Section::CycleTracking as usize].as_secs_f64();
+        Self {
+            schema_version: SCHEMA_VERSION,
+            metadata: Metadata::new::<T>(),
+            parameters: &mcdata.params.simulation_params,
+            execution: &mcdata.exec_info,
+            cycles: mcresults.cycles.as_deref().unwrap_or_default(),
+            balance: event_map(&bal.data),
+            absorbed_weight: bal.total_absorbed_weight(),
+            species,
+            timers: timer_reports(timers),
+            figure_of_merit: bal[TalliedEvent::NumSegments] as f64 / tracking_time,
+            spectrum: SpectrumReport {
+                energies: mcdata
+                    .nuclear_data
+                    .energies
+                    .iter()
+                    .map(|energy| energy.to_f64().unwrap())
+                    .collect(),
+                counts: mcresults.spectrum.census_energy_spectrum.clone(),
+            },
+            memory,
+            correctness,
+        }
+    }
+
+    /// Writes the report to the `<json_out>.json` output file.
+    pub fn write(&self) -> Result<()> {
+        let file_name = self.parameters.json_out.to_owned() + ".json";
+        let mut file = BufWriter::new(output::create_output(self.parameters, &file_name)?);
+        serde_json::to_writer_pretty(&mut file, self)?;
+        writeln!(file)?;
+        file.flush()
+    }
+}
+
+//==================
+// Private functions
+//==================
+
+fn event_map(data: &[u64; N_TALLIED_EVENT]) -> BTreeMap<&'static str, u64> {
+    TalliedEvent::ALL
+        .iter()
+        .map(|event| (event.name(), data[*event as usize]))
+        .collect()
+}
+
+fn timer_reports(timers: &MCFastTimerContainer) -> Vec<TimerReport> {
+    Section::ALL
+        .iter()
+        .enumerate()
+        .map(|(timer_idx, section)| {
+            let num_calls = timers.timers[timer_idx].num_calls;
+            // mins are initialized to the maximum duration
+            let min = if num_calls == 0 {
+                Duration::ZERO
+            } else {
+                timers.mins[timer_idx]
+            };
+            TimerReport {
+                name: section.name(),
+                num_calls,
+                min: min.as_secs_f64(),
+                avg: timers.avgs[timer_idx].as_secs_f64(),
+                max: timers.maxs[timer_idx].as_secs_f64(),
+                total: timers.tots[timer_idx].as_secs_f64(),
+            }
+        })
+        .collect()
+}
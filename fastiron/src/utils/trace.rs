@@ -0,0 +1,462 @@
+//! Code used to record per-thread timing traces
+//!
+//! This module contains the structure used to record timed events on each
+//! thread of the simulation, and to export them to the trace-event format
+//! used by Chrome's `about:tracing` and [Perfetto][1]. Events recorded on a
+//! given thread nest according to their time spans:
+//!
+//! - `cycle`: processing of a cycle, on the main thread.
+//! - sections of [`Section`][crate::utils::mc_fast_timer::Section], on the
+//!   main thread.
+//! - `chunk`: tracking of a batch of particles, on the thread processing it.
+//! - `extra_lock`: wait for the lock of the shared extra storage, at the end
+//!   of a chunk.
+//!
+//! Chunk events are buffered by the threads processing them and merged into
+//! the tracer at the end of each cycle. Recording is disabled if no output
+//! file is specified; timestamps are not even taken in this case.
+//!
+//! [1]: https://ui.perfetto.dev
+
+use std::{
+    io::{BufWriter, Result, Write},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::{constants::CustomFloat, parameters::SimulationParameters, utils::output};
+
+/// Structure used to represent a timed event.
+#[derive(Debug, Clone)]
+pub struct TraceEvent {
+    /// Name of the event.
+    pub name: &'static str,
+    /// Category of the event.
+    pub category: &'static str,
+    /// Thread identifier. See [`Tracer::thread_id()`].
+    pub thread: usize,
+    /// Start of the event, relative to the creation of the tracer.
+    pub start: Duration,
+    /// Duration of the event.
+    pub duration: Duration,
+    /// Named integer values attached to the event.
+    pub args: Vec<(&'static str, u64)>,
+}
+
+/// Structure used to accumulate the activity of a thread over the simulation.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ThreadStats {
+    /// Number of processed chunks.
+    pub chunks: u64,
+    /// Number of tracked particles.
+    pub particles: u64,
+    /// Time spent processing chunks, lock waits included.
+    pub busy: Duration,
+    /// Time spent waiting for the lock of the extra storage.
+    pub lock_wait: Duration,
+}
+
+impl ThreadStats {
+    /// Adds the statistics passed as argument to the current ones.
+    pub fn add(&mut self, other: &Self) {
+        self.chunks += other.chunks;
+        self.particles += other.particles;
+        self.busy += other.busy;
+        self.lock_wait += other.lock_wait;
+    }
+}
+
+/// Structure used to buffer the chunks recorded by a thread, so that the
+/// storage shared by all threads is not locked for each chunk. See
+/// [`Tracer::record_chunk()`] and [`Tracer::merge()`].
+#[derive(Debug, Default)]
+pub struct TraceBuffer {
+    /// Buffered events.
+    events: Vec<TraceEvent>,
+    /// Statistics of the buffered chunks, indexed by thread identifier.
+    threads: Vec<ThreadStats>,
+}
+
+impl TraceBuffer {
+    /// Returns true if nothing was recorded in the buffer.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Merges two buffers. This is used to reduce the buffers of the chunks
+    /// processed in parallel.
+    pub fn merge(mut self, mut other: Self) -> Self {
+        self.events.append(&mut other.events);
+        if self.threads.len() < other.threads.len() {
+            self.threads
+                .resize(other.threads.len(), ThreadStats::default());
+        }
+        self.threads
+            .iter_mut()
+            .zip(other.threads)
+            .for_each(|(stats, other)| stats.add(&other));
+        self
+    }
+}
+
+/// Structure used to record timed events & per-thread statistics.
+#[derive(Debug)]
+pub struct Tracer {
+    /// Switch used to enable recording.
+    enabled: bool,
+    /// Origin of the timestamps.
+    origin: Instant,
+    /// Recorded events.
+    events: Mutex<Vec<TraceEvent>>,
+    /// Statistics of each thread, indexed by thread identifier.
+    threads: Mutex<Vec<ThreadStats>>,
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl Tracer {
+    /// Constructor.
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            origin: Instant::now(),
+            events: Default::default(),
+            threads: Default::default(),
+        }
+    }
+
+    /// Returns true if events are recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the identifier of the current thread: `0` outside of rayon's
+    /// thread pools, the index of the thread in its pool plus one otherwise.
+    pub fn thread_id() -> usize {
+        rayon::current_thread_index().map_or(0, |idx| idx + 1)
+    }
+
+    /// Returns the current time if recording is enabled, `None` otherwise.
+    pub fn now(&self) -> Option<Instant> {
+        self.enabled.then(Instant::now)
+    }
+
+    /// Records an event on the current thread, from `start` to now. Nothing
+    /// is recorded if `start` is `None`.
+    pub fn record(
+        &self,
+        name: &'static str,
+        category: &'static str,
+        start: Option<Instant>,
+        args: &[(&'static str, u64)],
+    ) {
+        if let Some(start) = start {
+            self.record_span(name, category, start, Instant::now(), args);
+        }
+    }
+
+    /// Records an event on the current thread, from `start` to `end`.
+    pub fn record_span(
+        &self,
+        name: &'static str,
+        category: &'static str,
+        start: Instant,
+        end: Instant,
+        args: &[(&'static str, u64)],
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let event = self.event(name, category, start, end, args);
+        self.events.lock().unwrap().push(event);
+    }
+
+    /// Builds an event of the current thread, from `start` to `end`.
+    fn event(
+        &self,
+        name: &'static str,
+        category: &'static str,
+        start: Instant,
+        end: Instant,
+        args: &[(&'static str, u64)],
+    ) -> TraceEvent {
+        TraceEvent {
+            name,
+            category,
+            thread: Self::thread_id(),
+            start: start.saturating_duration_since(self.origin),
+            duration: end.saturating_duration_since(start),
+            args: args.to_vec(),
+        }
+    }
+
+    /// Records in `buffer` the processing of a chunk of `n_particles` particles
+    /// on the current thread. The chunk started at `start` and ends now; `lock`
+    /// is the span spent waiting for the lock of the extra storage, if any.
+    pub fn record_chunk(
+        &self,
+        buffer: &mut TraceBuffer,
+        start: Option<Instant>,
+        lock: Option<(Instant, Instant)>,
+        n_particles: usize,
+    ) {
+        let Some(start) = start else {
+            return;
+        };
+        let end = Instant::now();
+        let thread = Self::thread_id();
+        let chunk = self.event(
+            "chunk",
+            "tracking",
+            start,
+            end,
+            &[("particles", n_particles as u64)],
+        );
+        buffer.events.push(chunk);
+        if let Some((lock_start, lock_end)) = lock {
+            let lock = self.event("extra_lock", "lock", lock_start, lock_end, &[]);
+            buffer.events.push(lock);
+        }
+
+        if buffer.threads.len() <= thread {
+            buffer.threads.resize(thread + 1, ThreadStats::default());
+        }
+        let stats = &mut buffer.threads[thread];
+        stats.chunks += 1;
+        stats.particles += n_particles as u64;
+        stats.busy += end.duration_since(start);
+        if let Some((lock_start, lock_end)) = lock {
+            stats.lock_wait += lock_end.duration_since(lock_start);
+        }
+    }
+
+    /// Moves the content of `buffer` to the tracer. The shared storage is
+    /// only locked here, so buffers should be merged once per cycle rather
+    /// than once per chunk.
+    pub fn merge(&self, buffer: TraceBuffer) {
+        if buffer.is_empty() {
+            return;
+        }
+        self.events.lock().unwrap().extend(buffer.events);
+        let mut threads = self.threads.lock().unwrap();
+        if threads.len() < buffer.threads.len() {
+            threads.resize(buffer.threads.len(), ThreadStats::default());
+        }
+        threads
+            .iter_mut()
+            .zip(buffer.threads)
+            .for_each(|(stats, other)| stats.add(&other));
+    }
+
+    /// Returns a copy of the per-thread statistics, indexed by thread identifier.
+    pub fn thread_stats(&self) -> Vec<ThreadStats> {
+        self.threads.lock().unwrap().clone()
+    }
+
+    /// Prints the statistics of each thread that processed particles. Idle
+    /// time is given relatively to `process_time`, the total time spent
+    /// tracking particles. The output looks like the following:
+    ///
+    /// ```shell
+    /// [Thread Report]
+    /// thread |   chunks |  particles |   busy (s) | lock wait (s) | idle (%)
+    ///      1 |       30 |     151009 |  3.291e0   |   2.174e-5    |     1.2
+    ///      2 |       30 |     150722 |  3.245e0   |   1.035e-5    |     2.6
+    /// ```
+    pub fn print_thread_report(&self, process_time: Duration) {
+        println!("[Thread Report]");
+        println!(
+            "{:>6} | {:>8} | {:>10} | {:>10} | {:>13} | {:>8}",
+            "thread", "chunks", "particles", "busy (s)", "lock wait (s)", "idle (%)"
+        );
+        self.threads
+            .lock()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .filter(|(_, stats)| stats.chunks != 0)
+            .for_each(|(thread, stats)| {
+                let idle = 100.0
+                    * (1.0 - stats.busy.as_secs_f64() / (process_time.as_secs_f64() + 1.0e-80));
+                println!(
+                    "{:>6} | {:>8} | {:>10} | {:>10.3e} | {:>13.3e} | {:>8.1}",
+                    thread,
+                    stats.chunks,
+                    stats.particles,
+                    stats.busy.as_secs_f64(),
+                    stats.lock_wait.as_secs_f64(),
+                    idle.max(0.0),
+                );
+            });
+    }
+
+    /// Returns the recorded events in the trace-event format.
+    pub fn chrome_trace(&self) -> ChromeTrace {
+        let events = self.events.lock().unwrap();
+        let mut threads: Vec<usize> = events.iter().map(|event| event.thread).collect();
+        threads.sort_unstable();
+        threads.dedup();
+
+        let mut trace_events: Vec<ChromeEvent> = threads
+            .into_iter()
+            .map(|thread| ChromeEvent {
+                name: "thread_name",
+                cat: "__metadata",
+                ph: "M",
+                ts: 0.0,
+                dur: None,
+                pid: 0,
+                tid: thread,
+                args: [(
+                    "name".to_string(),
+                    serde_json::Value::from(match thread {
+                        0 => "main".to_string(),
+                        _ => format!("rayon-{}", thread - 1),
+                    }),
+                )]
+                .into_iter()
+                .collect(),
+            })
+            .collect();
+        trace_events.extend(events.iter().map(|event| {
+            ChromeEvent {
+                name: event.name,
+                cat: event.category,
+                ph: "X",
+                ts: event.start.as_secs_f64() * 1.0e6,
+                dur: Some(event.duration.as_secs_f64() * 1.0e6),
+                pid: 0,
+                tid: event.thread,
+                args: event
+                    .args
+                    .iter()
+                    .map(|(key, val)| (key.to_string(), serde_json::Value::from(*val)))
+                    .collect(),
+            }
+        }));
+        ChromeTrace {
+            trace_events,
+            display_time_unit: "ms",
+        }
+    }
+
+    /// Writes the recorded events to the `<trace_out>.json` output file.
+    pub fn write<T: CustomFloat>(&self, sim_params: &SimulationParameters<T>) -> Result<()> {
+        let file_name = sim_params.trace_out.to_owned() + ".json";
+        let mut file = BufWriter::new(output::create_output(sim_params, &file_name)?);
+        serde_json::to_writer(&mut file, &self.chrome_trace())?;
+        writeln!(file)?;
+        file.flush()
+    }
+}
+
+/// Structure used to serialize a trace in the trace-event format.
+#[derive(Debug, Serialize)]
+pub struct ChromeTrace {
+    /// Recorded events, preceded by the thread name metadata events.
+    #[serde(rename = "traceEvents")]
+    pub trace_events: Vec<ChromeEvent>,
+    /// Unit used by viewers to display times.
+    #[serde(rename = "displayTimeUnit")]
+    pub display_time_unit: &'static str,
+}
+
+/// Structure used to serialize an event in the trace-event format. Times
+/// are given in microseconds.
+#[derive(Debug, Serialize)]
+pub struct ChromeEvent {
+    /// Name of the event.
+    pub name: &'static str,
+    /// Category of the event.
+    pub cat: &'static str,
+    /// Phase of the event: `X` for complete events, `M` for metadata.
+    pub ph: &'static str,
+    /// Start of the event.
+    pub ts: f64,
+    /// Duration of the event, only given for complete events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dur: Option<f64>,
+    /// Process identifier, always zero.
+    pub pid: usize,
+    /// Thread identifier. See [`Tracer::thread_id()`].
+    pub tid: usize,
+    /// Named values attached to the event.
+    pub args: serde_json::Map<String, serde_json::Value>,
+}
+
+//=============
+// Unit tests
+//=============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn disabled() {
+        let tracer = Tracer::default();
+        assert!(tracer.now().is_none());
+        tracer.record("cycle", "cycle", tracer.now(), &[]);
+        let mut buffer = TraceBuffer::default();
+        tracer.record_chunk(&mut buffer, tracer.now(), None, 10);
+        assert!(buffer.is_empty());
+        tracer.merge(buffer);
+        assert!(tracer.chrome_trace().trace_events.is_empty());
+        assert!(tracer.thread_stats().is_empty());
+    }
+
+    #[test]
+    fn threads() {
+        let tracer = Tracer::new(true);
+        let start = tracer.now();
+        let buffer = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap()
+            .install(|| {
+                (0..4)
+                    .into_par_iter()
+                    .map(|_| {
+                        let mut buffer = TraceBuffer::default();
+                        let chunk_start = tracer.now();
+                        let lock_start = Instant::now();
+                        let lock = Some((lock_start, Instant::now()));
+                        tracer.record_chunk(&mut buffer, chunk_start, lock, 5);
+                        buffer
+                    })
+                    .reduce(TraceBuffer::default, TraceBuffer::merge)
+            });
+        // nothing is shared until the buffers are merged
+        assert!(tracer.thread_stats().is_empty());
+        tracer.merge(buffer);
+        tracer.record("cycle", "cycle", start, &[("cycle", 3)]);
+
+        let stats = tracer.thread_stats();
+        // nothing processed on the main thread
+        assert_eq!(stats[0], ThreadStats::default());
+        assert_eq!(stats.iter().map(|s| s.chunks).sum::<u64>(), 4);
+        assert_eq!(stats.iter().map(|s| s.particles).sum::<u64>(), 20);
+
+        let trace = serde_json::to_value(tracer.chrome_trace()).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        // 4 chunks, 4 lock waits, 1 cycle & thread names
+        let complete = events.iter().filter(|event| event["ph"] == "X").count();
+        assert_eq!(complete, 9);
+        let cycle = events
+            .iter()
+            .find(|event| event["name"] == "cycle")
+            .unwrap();
+        assert_eq!(cycle["tid"], 0);
+        assert_eq!(cycle["args"]["cycle"], 3);
+        assert!(events
+            .iter()
+            .any(|event| (event["ph"] == "M") && (event["args"]["name"] == "main")));
+    }
+}
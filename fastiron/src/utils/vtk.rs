@@ -0,0 +1,219 @@
+//! Code used to export the mesh for visualization
+//!
+//! This module contains the code used to write the mesh of the problem, the
+//! properties of its cells and the cell tallies into a file using the legacy
+//! VTK format. The resulting files can be opened with ParaView or VisIt.
+
+use std::io::{BufWriter, Result, Write};
+
+use num::zero;
+
+use crate::{
+    constants::CustomFloat,
+    montecarlo::{MonteCarloData, MonteCarloResults, MonteCarloUnit},
+    utils::output,
+};
+
+/// VTK identifier of the hexahedron cell type.
+const VTK_HEXAHEDRON: usize = 12;
+
+/// Corners of a cell, as indexed in
+/// [`CORNER_OFFSET`][crate::geometry::CORNER_OFFSET], in the order expected
+/// by VTK for a hexahedron.
+const HEXAHEDRON_CORNERS: [usize; 8] = [0, 1, 3, 2, 4, 5, 7, 6];
+
+/// Writes the mesh of all units to the `<file_name>.vtk` output file.
+///
+/// Each cell of the mesh is written as a hexahedron; the 24 facets used for
+/// tracking are a triangulation of its faces. The following cell data is
+/// written:
+/// - `material`: index of the material in the material database.
+/// - `density`: number density of the cell's material.
+/// - `volume`: volume of the cell.
+/// - `importance`: importance of the cell.
+/// - `source_weight`: weight sourced in the cell at each time step.
+/// - `domain`: global identifier of the domain owning the cell.
+/// - `unit`: index of the unit owning the cell.
+///
+/// If cell fluxes were tallied, the cumulative neutron scalar flux of each
+/// cell is also written, in total (`flux`) and for each energy group
+/// (`flux_<group>`). The flux is obtained from the track-length estimator,
+/// i.e. divided by the volume of the cell.
+pub fn write_vtk<T: CustomFloat>(
+    file_name: &str,
+    mcdata: &MonteCarloData<T>,
+    mcunits: &[MonteCarloUnit<T>],
+    mcresults: &MonteCarloResults<T>,
+) -> Result<()> {
+    let mut file = BufWriter::new(output::create_output(
+        &mcdata.params.simulation_params,
+        &(file_name.to_owned() + ".vtk"),
+    )?);
+
+    let n_points: usize = mcunits
+        .iter()
+        .map(|mcunit| mcunit.domain.mesh.node.len())
+        .sum();
+    let n_cells: usize = mcunits
+        .iter()
+        .map(|mcunit| mcunit.domain.cell_state.len())
+        .sum();
+
+    // header
+    writeln!(file, "# vtk DataFile Version 3.0")?;
+    writeln!(file, "fastiron mesh & cell tallies")?;
+    writeln!(file, "ASCII")?;
+    writeln!(file, "DATASET UNSTRUCTURED_GRID")?;
+
+    // geometry; nodes of each domain are offset by the number of previous nodes
+    writeln!(file, "POINTS {n_points} double")?;
+    for mcunit in mcunits {
+        for node in &mcunit.domain.mesh.node {
+            writeln!(file, "{} {} {}", node.x, node.y, node.z)?;
+        }
+    }
+    writeln!(
+        file,
+        "CELLS {} {}",
+        n_cells,
+        n_cells * (HEXAHEDRON_CORNERS.len() + 1)
+    )?;
+    let mut offset: usize = 0;
+    for mcunit in mcunits {
+        for cell in &mcunit.domain.mesh.cell_connectivity {
+            write!(file, "{}", HEXAHEDRON_CORNERS.len())?;
+            for corner in HEXAHEDRON_CORNERS {
+                write!(file, " {}", offset + cell.point[corner])?;
+            }
+            writeln!(file)?;
+        }
+        offset += mcunit.domain.mesh.node.len();
+    }
+    writeln!(file, "CELL_TYPES {n_cells}")?;
+    for _ in 0..n_cells {
+        writeln!(file, "{VTK_HEXAHEDRON}")?;
+    }
+
+    // cell properties
+    let source_rate: Vec<T> = mcdata
+        .material_database
+        .mat
+        .iter()
+        .map(|mat| mat.source_rate)
+        .collect();
+    let dt = mcdata.params.simulation_params.dt;
+    writeln!(file, "CELL_DATA {n_cells}")?;
+    write_scalars(
+        &mut file,
+        "material",
+        "int",
+        mcunits,
+        |_, cell_idx, mcunit| mcunit.domain.cell_state[cell_idx].material.to_string(),
+    )?;
+    write_scalars(
+        &mut file,
+        "density",
+        "double",
+        mcunits,
+        |_, cell_idx, mcunit| {
+            mcunit.domain.cell_state[cell_idx]
+                .cell_number_density
+                .to_string()
+        },
+    )?;
+    write_scalars(
+        &mut file,
+        "volume",
+        "double",
+        mcunits,
+        |_, cell_idx, mcunit| mcunit.domain.cell_state[cell_idx].volume.to_string(),
+    )?;
+    write_scalars(
+        &mut file,
+        "importance",
+        "double",
+        mcunits,
+        |_, cell_idx, mcunit| mcunit.domain.cell_state[cell_idx].importance.to_string(),
+    )?;
+    write_scalars(
+        &mut file,
+        "source_weight",
+        "double",
+        mcunits,
+        |_, cell_idx, mcunit| {
+            let cell = &mcunit.domain.cell_state[cell_idx];
+            (cell.volume * source_rate[cell.material] * dt).to_string()
+        },
+    )?;
+    write_scalars(&mut file, "domain", "int", mcunits, |_, _, mcunit| {
+        mcunit.domain.global_domain.to_string()
+    })?;
+    write_scalars(&mut file, "unit", "int", mcunits, |unit_idx, _, _| {
+        unit_idx.to_string()
+    })?;
+
+    // cell tallies
+    if let Some(cell_flux) = &mcresults.cell_flux {
+        let n_groups = mcdata.nuclear_data.num_energy_groups;
+        // tallies may not have been updated yet
+        let flux = |unit_idx: usize, cell_idx: usize, group: usize, mcunit: &MonteCarloUnit<T>| {
+            cell_flux
+                .get(unit_idx)
+                .and_then(|unit_flux| unit_flux.get(cell_idx * n_groups + group))
+                .map_or(zero(), |val| {
+                    *val / mcunit.domain.cell_state[cell_idx].volume
+                })
+        };
+        write_scalars(
+            &mut file,
+            "flux",
+            "double",
+            mcunits,
+            |unit_idx, cell_idx, mcunit| {
+                (0..n_groups)
+                    .map(|group| flux(unit_idx, cell_idx, group, mcunit))
+                    .sum::<T>()
+                    .to_string()
+            },
+        )?;
+        for group in 0..n_groups {
+            let name = format!("flux_{group}");
+            write_scalars(
+                &mut file,
+                &name,
+                "double",
+                mcunits,
+                |unit_idx, cell_idx, mcunit| flux(unit_idx, cell_idx, group, mcunit).to_string(),
+            )?;
+        }
+    }
+
+    file.flush()
+}
+
+//==================
+// Private functions
+//==================
+
+/// Writes a scalar cell data field, using `value` to format the value of
+/// each cell. The closure takes as arguments the index of the unit owning
+/// the cell, the local index of the cell and the unit itself.
+fn write_scalars<T: CustomFloat, W: Write, F>(
+    file: &mut W,
+    name: &str,
+    data_type: &str,
+    mcunits: &[MonteCarloUnit<T>],
+    mut value: F,
+) -> Result<()>
+where
+    F: FnMut(usize, usize, &MonteCarloUnit<T>) -> String,
+{
+    writeln!(file, "SCALARS {name} {data_type} 1")?;
+    writeln!(file, "LOOKUP_TABLE default")?;
+    for (unit_idx, mcunit) in mcunits.iter().enumerate() {
+        for cell_idx in 0..mcunit.domain.cell_state.len() {
+            writeln!(file, "{}", value(unit_idx, cell_idx, mcunit))?;
+        }
+    }
+    Ok(())
+}
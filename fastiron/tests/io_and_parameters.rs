@@ -1,8 +1,29 @@
 use fastiron::{
-    parameters::{GeometryParameters, ParameterError, Parameters, Shape, SimulationParameters},
+    data::{
+        nuclear_data::{FissionSpectrum, ReactionType, ScatteringLaw},
+        tallies::{Balance, TalliedEvent},
+    },
+    geometry::csg::CsgExpression,
+    init::{init_mcdata, init_mcunits, init_particle_containers, init_results},
+    parameters::{
+        AngularDistribution, Block, BoundaryFace, CrossSectionAveraging, DensityProfile,
+        EnergyDistribution, FissionSpectrumType, GeometryParameters, GroupStructure,
+        ParameterError, Parameters, ScatteringModel, Shape, SimulationParameters, SourceShape,
+        TimeProfile,
+    },
+    particles::{
+        mc_particle::{MCParticle, Species},
+        particle_collection::ParticleCollection,
+    },
+    simulation::collision_event::collision_event,
     utils::{
+        coral_benchmark_correctness::CorrectnessTest,
         input::{parse_input_file, Cli, InputError},
+        logging::LogLevel,
         mc_processor_info::{ExecPolicy, MCProcessorInfo},
+        memory::MemoryReport,
+        report::RunReport,
+        vtk::write_vtk,
     },
 };
 
@@ -31,6 +52,46 @@ fn verify_cli_parsing() {
     assert_eq!(simulation_params.n_rayon_threads, 10);
 }
 
+#[test]
+fn verify_cli_implicit_capture() {
+    use clap::Parser;
+    let cli = Cli::parse_from("./fastiron -i somefile.inp".split(' '));
+    assert!(!SimulationParameters::<f64>::from_cli(&cli).implicit_capture);
+    let cli = Cli::parse_from("./fastiron -i somefile.inp --implicit-capture".split(' '));
+    assert!(SimulationParameters::<f64>::from_cli(&cli).implicit_capture);
+
+    // the input file cannot disable the switch
+    let dir = std::env::temp_dir().join("fastiron_implicit_capture");
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("input.inp");
+    std::fs::write(&input, "Simulation:\n   implicitCapture: 0\n").unwrap();
+    let cmd_line = format!("./fastiron -i {} --implicit-capture", input.display());
+    let params = Parameters::<f64>::get_parameters(Cli::parse_from(cmd_line.split(' '))).unwrap();
+    assert!(params.simulation_params.implicit_capture);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn verify_cli_verbosity() {
+    use clap::Parser;
+    let mut cli = Cli::parse_from("./fastiron -i somefile.inp".split(' '));
+    assert_eq!(cli.log_level, LogLevel::Info);
+    assert!(!cli.quiet);
+    assert_eq!(
+        SimulationParameters::<f64>::from_cli(&cli).progress_interval,
+        0.0
+    );
+
+    cli = Cli::parse_from("./fastiron --progress 2.5 --log-level debug --quiet".split(' '));
+    assert_eq!(cli.log_level, LogLevel::Debug);
+    assert!(cli.quiet);
+    assert_eq!(
+        SimulationParameters::<f64>::from_cli(&cli).progress_interval,
+        2.5
+    );
+    assert!(Cli::try_parse_from("./fastiron --log-level loud".split(' ')).is_err());
+}
+
 #[test]
 fn verify_exec_policy_detection() {
     use clap::Parser;
@@ -275,3 +336,926 @@ fn verify_file_parsing() {
         }
     }
 }
+
+#[test]
+fn verify_density_parsing() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/density.inp".to_string(), &mut params).unwrap();
+    params.check_parameters_integrity().unwrap();
+
+    for g in params.geometry_params {
+        match g.shape {
+            Shape::Brick => {
+                assert_eq!(g.density, 0.5);
+                assert_eq!(g.density_profile, DensityProfile::Linear);
+                assert_eq!(g.density_gradient_x, 0.01);
+                assert_eq!(g.density_gradient_y, 0.0);
+                assert_eq!(g.density_gradient_z, 0.0);
+            }
+            Shape::Sphere => {
+                assert_eq!(g.density, 0.0);
+                assert_eq!(g.density_profile, DensityProfile::Uniform);
+            }
+            _ => panic!(),
+        }
+    }
+}
+
+#[test]
+fn verify_shapes() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/shapes.inp".to_string(), &mut params).unwrap();
+    params.check_parameters_integrity().unwrap();
+
+    // blocks are read in reverse order
+    let shapes: Vec<&Shape> = params.geometry_params.iter().map(|g| &g.shape).collect();
+    assert_eq!(
+        shapes,
+        vec![
+            &Shape::Plane,
+            &Shape::Ellipsoid,
+            &Shape::Cone,
+            &Shape::Cylinder,
+            &Shape::Brick
+        ]
+    );
+    let cylinder = &params.geometry_params[3];
+    assert_eq!(
+        (cylinder.x_axis, cylinder.y_axis, cylinder.z_axis),
+        (0.0, 0.0, 1.0)
+    );
+    assert_eq!(cylinder.height, 16.0);
+    let plane = &params.geometry_params[0];
+    assert_eq!((plane.x_axis, plane.y_axis, plane.z_axis), (-1.0, 0.0, 0.0));
+
+    // check material assignment
+    let mcdata = init_mcdata(params);
+    let mcunits = init_mcunits(&mcdata);
+    let material_of = |(x, y, z): (usize, usize, usize)| {
+        let gid = x + 20 * (y + 20 * z);
+        let cell = mcunits[0]
+            .domain
+            .cell_state
+            .iter()
+            .find(|cell| cell.id / 0x0100000000 == gid)
+            .unwrap();
+        mcdata.material_database.mat[cell.material].name.as_str()
+    };
+    assert_eq!(material_of((5, 5, 10)), "fuel"); // cylinder
+    assert_eq!(material_of((0, 0, 10)), "water");
+    assert_eq!(material_of((15, 15, 4)), "fuel"); // cone
+    assert_eq!(material_of((15, 15, 0)), "water");
+    assert_eq!(material_of((15, 5, 15)), "fuel"); // ellipsoid
+    assert_eq!(material_of((19, 5, 15)), "water"); // plane
+}
+
+#[test]
+fn verify_regions() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/csg.inp".to_string(), &mut params).unwrap();
+    params.check_parameters_integrity().unwrap();
+
+    // blocks are read in reverse order
+    let cladding = &params.geometry_params[3];
+    assert_eq!(cladding.name, "cladding");
+    assert_eq!(cladding.shape, Shape::Region);
+    assert_eq!(
+        cladding.region.as_ref().unwrap().names(),
+        vec!["pin", "hole"]
+    );
+    assert_eq!(cladding.x_translate, 6.0);
+    let block = &params.geometry_params[0];
+    assert_eq!((block.scale, block.z_rotate), (2.0, 90.0));
+
+    // check material assignment
+    let mcdata = init_mcdata(params);
+    let mcunits = init_mcunits(&mcdata);
+    let material_of = |(x, y, z): (usize, usize, usize)| {
+        let gid = x + 20 * (y + 20 * z);
+        let cell = mcunits[0]
+            .domain
+            .cell_state
+            .iter()
+            .find(|cell| cell.id / 0x0100000000 == gid)
+            .unwrap();
+        mcdata.material_database.mat[cell.material].name.as_str()
+    };
+    assert_eq!(material_of((6, 6, 10)), "fuel");
+    assert_eq!(material_of((9, 6, 10)), "steel"); // cladding
+    assert_eq!(material_of((6, 6, 17)), "steel"); // cladding end
+    assert_eq!(material_of((11, 6, 10)), "water");
+    assert_eq!(material_of((15, 17, 10)), "steel"); // rotated block
+    assert_eq!(material_of((18, 14, 10)), "water");
+}
+
+#[test]
+fn bad_region() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/csg.inp".to_string(), &mut params).unwrap();
+    // duplicate name & unknown geometry
+    params.geometry_params[5].name = "hole".to_string();
+    if let Err(v) = params.check_parameters_integrity() {
+        assert_eq!(
+            v,
+            vec![
+                ParameterError::BadRegion("cladding".to_string()),
+                ParameterError::BadRegion("hole".to_string()),
+                ParameterError::BadRegion("hole".to_string()),
+            ]
+        );
+    } else {
+        unreachable!()
+    }
+
+    // region referring to itself
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/csg.inp".to_string(), &mut params).unwrap();
+    params.geometry_params[3].region = CsgExpression::parse("pin & ~cladding");
+    if let Err(v) = params.check_parameters_integrity() {
+        assert_eq!(v, vec![ParameterError::BadRegion("cladding".to_string())]);
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn verify_isotope_parsing() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/isotopes.inp".to_string(), &mut params).unwrap();
+    params.check_parameters_integrity().unwrap();
+
+    let fuel = &params.material_params["fuel"];
+    assert_eq!(fuel.isotopes, vec!["U235", "U238", "O16"]);
+    assert_eq!(fuel.atom_fractions, vec![0.01, 0.32, 0.67]);
+    assert_eq!(params.isotope_params.len(), 3);
+    let o16 = &params.isotope_params["O16"];
+    assert_eq!(o16.mass, 15.86);
+    assert_eq!(o16.n_reactions, 2);
+    assert!(o16.fission_cross_section.is_empty());
+
+    // build the material database & check the resulting mixture
+    let mcdata = init_mcdata(params);
+    let material = &mcdata.material_database.mat[0];
+    assert_eq!(material.iso.len(), 3);
+    let total_fraction: f64 = material.iso.iter().map(|iso| iso.atom_fraction).sum();
+    assert!((total_fraction - 1.0).abs() < 1e-12);
+    assert_eq!(material.iso[2].mass, 15.86);
+    let o16_reactions = &mcdata.nuclear_data.isotopes[material.iso[2].gid][0].reactions;
+    assert_eq!(o16_reactions.len(), 2);
+    assert!(o16_reactions
+        .iter()
+        .all(|reaction| reaction.reaction_type != ReactionType::Fission));
+}
+
+#[test]
+fn verify_photon_parameters() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/photons.inp".to_string(), &mut params).unwrap();
+    params.check_parameters_integrity().unwrap();
+
+    let h1 = &params.isotope_params["H1"];
+    assert_eq!(h1.capture_photon_yield, 1.0);
+    assert_eq!(h1.capture_photon_energy, 2.2245);
+    assert_eq!(h1.photon_cross_section, 0.05);
+    assert_eq!(h1.photon_scattering_cross_section, "compton");
+    assert_eq!(h1.photon_absorption_cross_section, "photoabsorb");
+    assert_eq!(h1.cross_section_names().count(), 4);
+
+    // one reaction set per species
+    let mcdata = init_mcdata(params);
+    let material = &mcdata.material_database.mat[0];
+    let h1 = &mcdata.nuclear_data.isotopes[material.iso[0].gid];
+    assert_eq!(h1.len(), 2);
+    let capture = h1[Species::Neutron.index()]
+        .reactions
+        .iter()
+        .find(|reaction| reaction.reaction_type == ReactionType::Absorption)
+        .unwrap();
+    assert_eq!(capture.photon_yield, 1.0);
+    assert_eq!(capture.photon_energy, 2.2245);
+    let photon_reactions = &h1[Species::Photon.index()].reactions;
+    assert_eq!(photon_reactions.len(), 2);
+    assert!(matches!(
+        photon_reactions[0].scattering,
+        ScatteringLaw::Compton
+    ));
+    let o16 = &mcdata.nuclear_data.isotopes[material.iso[1].gid];
+    assert_eq!(o16[Species::Photon.index()].reactions.len(), 1);
+    assert_eq!(o16[Species::Neutron.index()].reactions[1].photon_yield, 0.0);
+}
+
+#[test]
+fn verify_source_parameters() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/sources.inp".to_string(), &mut params).unwrap();
+    params.check_parameters_integrity().unwrap();
+    assert_eq!(params.source_params.len(), 3);
+
+    let find = |name: &str| {
+        params
+            .source_params
+            .iter()
+            .find(|sp| sp.name == name)
+            .unwrap()
+    };
+    let beam = find("beam");
+    assert_eq!(beam.species, Species::Photon);
+    assert_eq!(beam.shape, SourceShape::Surface);
+    assert_eq!(beam.face, BoundaryFace::ZMax);
+    assert_eq!(beam.angular_distribution, AngularDistribution::Cosine);
+    assert_eq!(beam.energy_distribution, EnergyDistribution::Histogram);
+    assert_eq!(beam.histogram_boundaries, vec![0.5, 1.0, 2.0]);
+    assert_eq!(beam.histogram_weights, vec![3.0, 1.0]);
+    let pulse = find("pulse");
+    assert_eq!(pulse.shape, SourceShape::Sphere);
+    assert_eq!(pulse.time_profile, TimeProfile::Exponential);
+    assert_eq!(pulse.t_end, 5e-8);
+
+    // the pulse stops emitting after its end time
+    let mcdata = init_mcdata(params);
+    let (weight_early, weight_late): (f64, f64) = mcdata
+        .sources
+        .iter()
+        .map(|source| (source.weight(0.0, 1e-8), source.weight(1e-7, 1e-8)))
+        .fold((0.0, 0.0), |acc, ww| (acc.0 + ww.0, acc.1 + ww.1));
+    assert!((weight_late - 1.5e8).abs() < 1.0);
+    assert!(weight_early > weight_late);
+}
+
+#[test]
+fn source_shares() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/sources.inp".to_string(), &mut params).unwrap();
+    params.simulation_params.n_units = 4;
+    let mcdata = init_mcdata(params);
+    let mut mcunits = init_mcunits(&mcdata);
+
+    // the sources lie in the mesh: their weight is split across units
+    (0..mcdata.sources.len()).for_each(|source_idx| {
+        let total: f64 = mcunits
+            .iter()
+            .map(|mcunit| mcunit.source_shares[source_idx])
+            .sum();
+        assert!((total - 1.0).abs() < 1e-12);
+    });
+    let total_weight: f64 = mcunits
+        .iter_mut()
+        .map(|mcunit| {
+            mcunit.update_unit_weight(&mcdata);
+            mcunit.unit_weight
+        })
+        .sum();
+    let external_weight: f64 = mcdata
+        .sources
+        .iter()
+        .map(|source| source.weight(0.0, 1e-8))
+        .sum();
+    assert!((total_weight - external_weight).abs() < 1e-6 * external_weight);
+}
+
+#[test]
+fn bad_source() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/sources.inp".to_string(), &mut params).unwrap();
+    params.source_params[0].x_center = -10.0;
+    if let Err(v) = params.check_parameters_integrity() {
+        assert_eq!(v, vec![ParameterError::BadSource(0)]);
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn verify_weight_windows() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/weight_windows.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.check_parameters_integrity().unwrap();
+    let windows = &params.simulation_params.weight_windows;
+    assert_eq!(windows.len(), 10);
+    assert_eq!(windows[0].cell, None);
+    assert_eq!(windows[0].group, None);
+    assert_eq!(windows[1].cell, Some(1));
+    assert_eq!(windows[1].lower, 0.05);
+    assert_eq!(windows[1].upper, 0.8);
+
+    // later windows take priority
+    let mcdata = init_mcdata(params);
+    let weight_windows = mcdata.weight_windows.as_ref().unwrap();
+    assert_eq!(weight_windows.get(0, 100), (0.25, 4.0));
+    assert_eq!(weight_windows.get(1, 0), (0.05, 0.8));
+
+    // out of range cell
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/weight_windows.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.simulation_params.weight_windows[3].cell = Some(10);
+    if let Err(v) = params.check_parameters_integrity() {
+        assert_eq!(v, vec![ParameterError::BadWeightWindow(3)]);
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn verify_importance() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/importance.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.check_parameters_integrity().unwrap();
+    let importances: Vec<f64> = params
+        .geometry_params
+        .iter()
+        .map(|geometry| geometry.importance)
+        .collect();
+    // blocks are read in reverse order
+    assert_eq!(importances, vec![16.0, 8.0, 4.0, 2.0, 1.0]);
+
+    // cells inherit the importance of the geometry they belong to
+    let mcdata = init_mcdata(params);
+    let mcunits = init_mcunits(&mcdata);
+    mcunits[0].domain.cell_state.iter().for_each(|cell| {
+        let ix = cell.id / 0x0100000000;
+        assert_eq!(cell.importance, importances[4 - ix / 2]);
+    });
+}
+
+#[test]
+fn verify_boundary_conditions() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/boundaries.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.check_parameters_integrity().unwrap();
+    assert_eq!(params.simulation_params.boundary_condition, "escape");
+    assert_eq!(
+        params.simulation_params.boundary_conditions(),
+        ["white", "reflect", "periodic", "periodic", "periodic", "periodic"]
+    );
+
+    // octant condition with an overridden face
+    params.simulation_params.boundary_condition = "octant".to_string();
+    params.simulation_params.face_boundary_conditions = Default::default();
+    params.simulation_params.face_boundary_conditions[5] = "white".to_string();
+    assert_eq!(
+        params.simulation_params.boundary_conditions(),
+        ["reflect", "escape", "reflect", "escape", "reflect", "white"]
+    );
+
+    // unknown condition & unpaired periodic face
+    params.simulation_params.face_boundary_conditions[0] = "absorb".to_string();
+    params.simulation_params.face_boundary_conditions[2] = "periodic".to_string();
+    if let Err(v) = params.check_parameters_integrity() {
+        assert_eq!(
+            v,
+            vec![
+                ParameterError::BadBoundaryCondition("absorb".to_string()),
+                ParameterError::BadBoundaryCondition("periodic".to_string()),
+            ]
+        );
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn verify_volume_fractions() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/volume_fractions.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.check_parameters_integrity().unwrap();
+    assert_eq!(params.simulation_params.volume_samples, 8);
+
+    // analytic volumes, blocks are read in reverse order
+    let pi = std::f64::consts::PI;
+    let analytic: Vec<Option<f64>> = params.geometry_params.iter().map(|g| g.volume()).collect();
+    assert!((analytic[0].unwrap() - pi * 1.5 * 1.5 * 12.0).abs() < 1e-9);
+    assert!((analytic[1].unwrap() - 4.0 / 3.0 * pi * 216.0).abs() < 1e-9);
+    assert_eq!(analytic[2], Some(8000.0));
+
+    let mcdata = init_mcdata(params);
+    let mcunits = init_mcunits(&mcdata);
+    let domain = &mcunits[0].domain;
+    let sampled = &mcdata.geometry_volume;
+    assert!((sampled[2] - 8000.0).abs() < 1e-6);
+    assert!((sampled[1] - analytic[1].unwrap()).abs() / analytic[1].unwrap() < 0.01);
+
+    // partially filled cells use a mixture of their materials
+    let mat_db = &mcdata.material_database;
+    let cell_of = |(x, y, z): (usize, usize, usize)| {
+        let gid = x + 5 * (y + 5 * z);
+        domain
+            .cell_state
+            .iter()
+            .find(|cell| cell.id / 0x0100000000 == gid)
+            .unwrap()
+    };
+    let full = cell_of((2, 2, 2));
+    assert_eq!(mat_db.mat[full.material].name, "fuel");
+    assert_eq!(full.cell_number_density, 1.0);
+    let partial = cell_of((1, 2, 2));
+    let mixture = &mat_db.mat[partial.material];
+    assert!((mixture.name == "fuel+water") | (mixture.name == "water+fuel"));
+    assert!((partial.cell_number_density - 1.0).abs() < 1e-12);
+    assert!((mixture.mass > 18.0) & (mixture.mass < 235.0));
+    let fuel_fraction = (mixture.mass - 18.0) / (235.0 - 18.0);
+    assert!((mixture.source_rate - 1e10 * fuel_fraction).abs() < 1e-3);
+
+    // no mass is lost in the mixed cells
+    let cells = || {
+        mcunits
+            .iter()
+            .flat_map(|mcunit| mcunit.domain.cell_state.iter())
+    };
+    let mesh_mass: f64 = cells()
+        .map(|cell| cell.volume * cell.cell_number_density * mat_db.mat[cell.material].mass)
+        .sum();
+    let water_mass = 18.0 * (8000.0 - sampled[1] - sampled[0]);
+    let fuel_mass = 235.0 * (sampled[1] + sampled[0]);
+    assert!((mesh_mass - water_mass - fuel_mass).abs() < 1e-9 * mesh_mass);
+    let source_rate: f64 = cells()
+        .map(|cell| cell.volume * mat_db.mat[cell.material].source_rate)
+        .sum();
+    assert!((source_rate - 1e10 * (sampled[1] + sampled[0])).abs() < 1e-9 * source_rate);
+
+    // center sampling by default
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/volume_fractions.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.simulation_params.volume_samples = 0;
+    let mcdata = init_mcdata(params);
+    let mcunits = init_mcunits(&mcdata);
+    assert!(mcdata.geometry_volume.is_empty());
+    assert!(mcdata.material_database.cell_materials.is_empty());
+    let partial = mcunits[0]
+        .domain
+        .cell_state
+        .iter()
+        .find(|cell| cell.id / 0x0100000000 == 1 + 5 * (2 + 5 * 2))
+        .unwrap();
+    assert_eq!(partial.cell_number_density, 1.0);
+}
+
+#[test]
+fn vtk_output() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/rectilinear.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.simulation_params.vtk_out = "mesh".to_string();
+    params.simulation_params.n_groups = 2;
+    let mcdata = init_mcdata(params);
+    let mcunits = init_mcunits(&mcdata);
+    let mcresults = init_results(&mcdata.params);
+    assert!(mcresults.cell_flux.is_some());
+
+    let file_name = std::env::temp_dir().join("fastiron_vtk_output");
+    let file_name = file_name.to_str().unwrap();
+    write_vtk(file_name, &mcdata, &mcunits, &mcresults).unwrap();
+    let content = std::fs::read_to_string(file_name.to_owned() + ".vtk").unwrap();
+    std::fs::remove_file(file_name.to_owned() + ".vtk").unwrap();
+
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines[0], "# vtk DataFile Version 3.0");
+    assert!(lines.contains(&"CELLS 20 180"));
+    assert!(lines.contains(&"CELL_TYPES 20"));
+    assert!(lines.contains(&"CELL_DATA 20"));
+    // 7 cell properties, total flux & 2 groups
+    let fields: Vec<&str> = lines
+        .iter()
+        .filter(|line| line.starts_with("SCALARS"))
+        .map(|line| line.split(' ').nth(1).unwrap())
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            "material",
+            "density",
+            "volume",
+            "importance",
+            "source_weight",
+            "domain",
+            "unit",
+            "flux",
+            "flux_0",
+            "flux_1"
+        ]
+    );
+    // cell volumes follow the mesh planes
+    let volume_idx = lines
+        .iter()
+        .position(|line| *line == "SCALARS volume double 1")
+        .unwrap();
+    let total: f64 = lines[volume_idx + 2..volume_idx + 22]
+        .iter()
+        .map(|line| line.parse::<f64>().unwrap())
+        .sum();
+    assert!((total - 10000.0).abs() < 1e-6);
+}
+
+#[test]
+fn verify_rectilinear_mesh() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/rectilinear.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.check_parameters_integrity().unwrap();
+    let sim = &params.simulation_params;
+    assert_eq!(sim.x_planes.len(), 11);
+    assert!(sim.y_planes.is_empty());
+    assert_eq!(sim.z_planes, vec![0.0, 2.0, 10.0]);
+    // planes take precedence over nz & lz
+    assert_eq!((sim.nx, sim.ny, sim.nz), (10, 1, 2));
+    assert_eq!((sim.lx, sim.ly, sim.lz), (100.0, 10.0, 10.0));
+
+    // cells have their own volumes
+    let mcdata = init_mcdata(params);
+    let mcunits = init_mcunits(&mcdata);
+    let cell_state = &mcunits[0].domain.cell_state;
+    let volume_of = |gid: usize| {
+        cell_state
+            .iter()
+            .find(|cell| cell.id / 0x0100000000 == gid)
+            .unwrap()
+            .volume
+    };
+    assert!((volume_of(4) - 100.0).abs() < 1e-9);
+    assert!((volume_of(10 + 9) - 1600.0).abs() < 1e-9);
+    let total: f64 = cell_state.iter().map(|cell| cell.volume).sum();
+    assert!((total - 10000.0).abs() < 1e-6);
+
+    // decreasing planes
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/rectilinear.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.simulation_params.x_planes[3] = 5.0;
+    if let Err(v) = params.check_parameters_integrity() {
+        assert_eq!(v, vec![ParameterError::BadMeshPlanes]);
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn implicit_capture() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/homogeneous.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.simulation_params.implicit_capture = true;
+    let mut mcdata = init_mcdata(params);
+    mcdata.source_particle_weight = 1.0;
+
+    let mut particle = MCParticle::<f64> {
+        random_number_seed: 90374384094798327,
+        kinetic_energy: 1.0,
+        weight: 1.0,
+        energy_group: mcdata.nuclear_data.get_energy_groups(1.0),
+        ..Default::default()
+    };
+    let reactions = &mcdata.nuclear_data.isotopes[0][Species::Neutron.index()].reactions;
+    let xs = |reaction_type: ReactionType| -> f64 {
+        reactions
+            .iter()
+            .filter(|reaction| reaction.reaction_type == reaction_type)
+            .map(|reaction| reaction.cross_section[particle.energy_group])
+            .sum()
+    };
+    let absorption_xs = xs(ReactionType::Absorption);
+    particle.total_cross_section =
+        absorption_xs + xs(ReactionType::Scatter) + xs(ReactionType::Fission);
+
+    // the particle is never absorbed but loses weight
+    let mut balance = Balance::default();
+    let mut extra = ParticleCollection::with_capacity(10);
+    collision_event(&mcdata, &mut balance, 0, 1.0, &mut particle, &mut extra);
+    let expected = absorption_xs / particle.total_cross_section;
+    assert_eq!(balance[TalliedEvent::Absorb], 0);
+    assert_eq!(balance[TalliedEvent::Collision], 1);
+    assert!((balance.total_absorbed_weight() - expected).abs() < 1e-12);
+}
+
+#[test]
+fn analog_collision_weight() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/homogeneous.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    assert!(!params.simulation_params.implicit_capture);
+    let mut mcdata = init_mcdata(params);
+    mcdata.source_particle_weight = 1.0;
+    let energy_group = mcdata.nuclear_data.get_energy_groups(1.0);
+    // reactions are sampled in order: a small enough cross-section picks the
+    // first one, a scattering
+    let first_reaction = &mcdata.nuclear_data.isotopes[0][Species::Neutron.index()].reactions[0];
+    assert_eq!(first_reaction.reaction_type, ReactionType::Scatter);
+    let scatter_xs = 1e-3 * first_reaction.cross_section[energy_group];
+
+    // light particles, e.g. created by a split, do not play russian roulette
+    (0..20).for_each(|seed| {
+        let mut particle = MCParticle::<f64> {
+            random_number_seed: 90374384094798327 + seed,
+            kinetic_energy: 1.0,
+            weight: 1e-4,
+            energy_group,
+            total_cross_section: scatter_xs,
+            ..Default::default()
+        };
+        let mut balance = Balance::default();
+        let mut extra = ParticleCollection::with_capacity(10);
+        assert!(collision_event(
+            &mcdata,
+            &mut balance,
+            0,
+            1.0,
+            &mut particle,
+            &mut extra
+        ));
+        assert_eq!(balance[TalliedEvent::Scatter], 1);
+        assert_eq!(balance[TalliedEvent::WeightRr], 0);
+        assert_eq!(particle.weight, 1e-4);
+    });
+}
+
+#[test]
+fn missing_isotope() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/isotopes.inp".to_string(), &mut params).unwrap();
+    params.isotope_params.remove("O16");
+    params
+        .material_params
+        .get_mut("fuel")
+        .unwrap()
+        .atom_fractions
+        .pop();
+    // check if both the missing reference and the bad composition were noticed
+    if let Err(v) = params.check_parameters_integrity() {
+        assert!(v.contains(&ParameterError::MissingIsotope("fuel:O16".to_string())));
+        assert!(v.contains(&ParameterError::BadComposition("fuel".to_string())));
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn verify_tabulated_cross_section() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/tabulated.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.check_parameters_integrity().unwrap();
+
+    let tabulated = &params.cross_section_params["tabulated"];
+    assert!(tabulated.is_tabulated());
+    assert_eq!(tabulated.averaging, CrossSectionAveraging::Interpolated);
+    assert_eq!(
+        tabulated.table_energies,
+        vec![1e-09, 1e-06, 0.001, 1.0, 20.0]
+    );
+    assert_eq!(tabulated.table_values, vec![10.0, 10.0, 4.0, 1.0, 0.5]);
+    assert!(!params.cross_section_params["flat"].is_tabulated());
+
+    // the collapsed data follows the table's shape
+    let mcdata = init_mcdata(params);
+    let reactions = &mcdata.nuclear_data.isotopes[0][0].reactions;
+    let scatter = reactions
+        .iter()
+        .find(|reaction| reaction.reaction_type == ReactionType::Scatter)
+        .unwrap();
+    assert!(scatter.cross_section[0] > scatter.cross_section[229]);
+    assert_eq!(scatter.cross_section[0], scatter.cross_section[1]);
+}
+
+#[test]
+fn missing_table_file() {
+    let mut params = Parameters::<f64>::default();
+    if let Err(v) = parse_input_file(
+        "../input_files/debug/missing_table.inp".to_string(),
+        &mut params,
+    ) {
+        assert!(v.contains(&InputError::BadTableFile));
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn zero_table_file() {
+    let mut params = Parameters::<f64>::default();
+    if let Err(v) = parse_input_file(
+        "../input_files/debug/zero_table.inp".to_string(),
+        &mut params,
+    ) {
+        assert!(v.contains(&InputError::ZeroCrossSectionTable));
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn verify_energy_groups() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/energy_groups.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.check_parameters_integrity().unwrap();
+    assert_eq!(
+        params.simulation_params.group_structure,
+        GroupStructure::Explicit
+    );
+    params.simulation_params.update_energy_groups();
+    assert_eq!(params.simulation_params.n_groups, 4);
+    assert_eq!(params.simulation_params.e_min, 1e-09);
+    assert_eq!(params.simulation_params.e_max, 10.0);
+
+    // the group structure is used by the nuclear data
+    let mcdata = init_mcdata(params);
+    assert_eq!(mcdata.nuclear_data.num_energy_groups, 4);
+    assert_eq!(mcdata.nuclear_data.get_energy_groups(1e-07), 0);
+    assert_eq!(mcdata.nuclear_data.get_energy_groups(1e-04), 1);
+    assert_eq!(mcdata.nuclear_data.get_energy_groups(2.0), 3);
+    assert_eq!(mcdata.nuclear_data.get_energy_groups(15.0), 3);
+}
+
+#[test]
+fn named_energy_groups() {
+    let mut params = Parameters::<f64>::default();
+    params.simulation_params.group_structure = GroupStructure::Casmo4;
+    params.simulation_params.e_max = 1.0;
+    // the 0.821 MeV boundary is kept, the 20 MeV upper bound is replaced
+    assert_eq!(
+        params.simulation_params.energy_boundaries(),
+        vec![1e-09, 6.25e-7, 5.53e-3, 8.21e-1, 1.0]
+    );
+    params.simulation_params.e_max = 0.5;
+    assert_eq!(params.simulation_params.energy_boundaries().len(), 4);
+
+    // invalid explicit boundaries are noticed
+    params.simulation_params.group_structure = GroupStructure::Explicit;
+    params.simulation_params.group_boundaries = vec![1e-09, 1.0, 0.1];
+    if let Err(v) = params.check_parameters_integrity() {
+        assert!(v.contains(&ParameterError::BadEnergyGroups));
+    } else {
+        unreachable!()
+    }
+
+    // an empty list is an error, not a panic
+    let mut params = Parameters::<f64>::default();
+    let mut sim_block = Block::default();
+    sim_block.insert("groupBoundaries".to_string(), "".to_string());
+    params.update_simulation_parameters(sim_block).unwrap();
+    assert!(params.simulation_params.group_boundaries.is_empty());
+    if let Err(v) = params.check_parameters_integrity() {
+        assert!(v.contains(&ParameterError::BadEnergyGroups));
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn verify_fission_parameters() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file("../input_files/debug/fission.inp".to_string(), &mut params).unwrap();
+    params.check_parameters_integrity().unwrap();
+
+    let fission = &params.cross_section_params["fission"];
+    assert_eq!(fission.nu_bar_polynomial, vec![2.43, 0.065]);
+    assert_eq!(fission.fission_spectrum, FissionSpectrumType::Watt);
+    assert_eq!(fission.watt_a, 0.988);
+    assert_eq!(fission.watt_b, 2.249);
+    let fission238 = &params.cross_section_params["fission238"];
+    assert_eq!(fission238.nu_bar_energies, vec![1e-09, 1.0, 20.0]);
+    assert_eq!(fission238.nu_bar_values, vec![2.43, 2.5, 5.6]);
+    assert_eq!(fission238.fission_spectrum, FissionSpectrumType::Maxwellian);
+    assert_eq!(fission238.maxwell_temperature, 1.32);
+
+    // reactions carry energy-dependent multiplicities
+    let mcdata = init_mcdata(params);
+    let material = &mcdata.material_database.mat[0];
+    let u235 = &mcdata.nuclear_data.isotopes[material.iso[0].gid][0];
+    let reaction = u235
+        .reactions
+        .iter()
+        .find(|reaction| reaction.reaction_type == ReactionType::Fission)
+        .unwrap();
+    assert!((reaction.nu_bar.val(2.0) - 2.56).abs() < 1e-12);
+    assert!(matches!(reaction.spectrum, FissionSpectrum::Watt { .. }));
+    let u238 = &mcdata.nuclear_data.isotopes[material.iso[1].gid][0];
+    assert!((u238.reactions[0].nu_bar.val(10.5) - 4.05).abs() < 1e-12);
+}
+
+#[test]
+fn verify_scattering_parameters() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/scattering.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    params.check_parameters_integrity().unwrap();
+
+    let scatter = &params.cross_section_params["scatter"];
+    assert_eq!(scatter.scattering_model, ScatteringModel::Elastic);
+    assert_eq!(scatter.legendre_coefficients, vec![0.2, 0.05]);
+    assert_eq!(
+        params.cross_section_params["absorb"].scattering_model,
+        ScatteringModel::Legacy
+    );
+
+    // scattering reactions carry the anisotropy
+    let mcdata = init_mcdata(params);
+    let reaction = mcdata.nuclear_data.isotopes[0][0]
+        .reactions
+        .iter()
+        .find(|reaction| reaction.reaction_type == ReactionType::Scatter)
+        .unwrap();
+    if let ScatteringLaw::Elastic { legendre } = &reaction.scattering {
+        assert_eq!(legendre, &vec![0.2, 0.05]);
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn json_report() {
+    let mut params = Parameters::<f64>::default();
+    parse_input_file(
+        "../input_files/debug/rectilinear.inp".to_string(),
+        &mut params,
+    )
+    .unwrap();
+    let file_name = std::env::temp_dir().join("fastiron_json_report");
+    let file_name = file_name.to_str().unwrap();
+    params.simulation_params.json_out = file_name.to_string();
+    params.simulation_params.n_groups = 2;
+    let mcdata = init_mcdata(params);
+    let mut mcunits = init_mcunits(&mcdata);
+    let mut containers = init_particle_containers(&mcdata.params, &mcdata.exec_info);
+    let mut mcresults = init_results(&mcdata.params);
+    // record an empty cycle
+    mcunits[0].tallies.balance_cycle[TalliedEvent::Source] = 42;
+    mcresults.update_stats(&mut mcunits);
+    mcresults.update_memory(&mut containers);
+    let memory = MemoryReport::new(&mcdata, &mcunits, &containers, &mcresults);
+    let correctness = vec![CorrectnessTest {
+        name: "dummy",
+        pass: true,
+        values: [("ratio", 0.5)].into_iter().collect(),
+    }];
+
+    RunReport::new(&mcdata, &mcunits, &mcresults, &memory, &correctness)
+        .write()
+        .unwrap();
+    let content = std::fs::read_to_string(file_name.to_owned() + ".json").unwrap();
+    std::fs::remove_file(file_name.to_owned() + ".json").unwrap();
+
+    let report: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert_eq!(report["schema_version"], 1);
+    assert_eq!(report["metadata"]["float_type"], "f64");
+    assert_eq!(report["parameters"]["n_groups"], 2);
+    assert_eq!(report["execution"]["exec_policy"], "Sequential");
+    let cycles = report["cycles"].as_array().unwrap();
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(cycles[0]["cycle"], 0);
+    assert_eq!(cycles[0]["balance"]["source"], 42);
+    assert_eq!(report["balance"]["source"], 42);
+    assert_eq!(report["timers"].as_array().unwrap().len(), 6);
+    assert_eq!(report["spectrum"]["energies"].as_array().unwrap().len(), 3);
+    assert_eq!(report["correctness"][0]["values"]["ratio"], 0.5);
+    let memory = &report["memory"];
+    assert!(memory["footprint"]["particles"].as_u64().unwrap() > 0);
+    assert!(memory["footprint"]["mesh"].as_u64().unwrap() > 0);
+    assert_eq!(memory["cycles"][0]["reallocations"], 0);
+}